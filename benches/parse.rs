@@ -0,0 +1,21 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use falcon_key_file::{ParseOptions, parse_str};
+use std::hint::black_box;
+
+fn parse_full_keyfile(c: &mut Criterion) {
+    let content = std::fs::read_to_string("test-data/T16000M-FCS-Full.key").unwrap();
+    let options = ParseOptions::default();
+    c.bench_function("parse T16000M-FCS-Full.key", |b| {
+        b.iter(|| {
+            parse_str(
+                String::from("T16000M-FCS-Full.key"),
+                black_box(&content),
+                &options,
+            )
+            .unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, parse_full_keyfile);
+criterion_main!(benches);
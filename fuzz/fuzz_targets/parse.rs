@@ -0,0 +1,25 @@
+//! Parses arbitrary bytes and exercises the parsed key file.
+//! Run with `cargo +nightly fuzz run parse fuzz/corpus/parse`.
+
+#![no_main]
+
+use falcon_key_file::{ParseOptions, parse_reader, raw_bindings};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    for options in [ParseOptions::lenient(), ParseOptions::strict()] {
+        if let Ok(report) = parse_reader(String::from("fuzz.key"), data, &options) {
+            let keyfile = report.keyfile;
+            let _ = keyfile.to_csv();
+            let _ = keyfile.to_json();
+            let _ = keyfile.stats();
+            for callback in keyfile.iter() {
+                let _ = callback.to_string();
+                let _ = callback.key_sequence();
+            }
+        }
+    }
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = raw_bindings(text).count();
+    }
+});
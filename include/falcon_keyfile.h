@@ -0,0 +1,51 @@
+/* C API of falcon-key-file, built with the `ffi` feature. See src/ffi.rs. */
+
+#ifndef FALCON_KEYFILE_H
+#define FALCON_KEYFILE_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct FbkKeyfile FbkKeyfile;
+
+typedef struct FbkCallback {
+    const char *name;
+    /* NULL if the callback has no description. */
+    const char *description;
+    /* DirectInput scan code, 0 or 0xFFFF if unbound. */
+    uint16_t key_code;
+    /* Modifier mask: 1 shift, 2 control, 4 alt. */
+    uint16_t modifiers;
+    /* Scan code of the combo pressed first, 0 if there is none. */
+    uint16_t combo_key_code;
+    uint16_t combo_modifiers;
+    bool bound;
+} FbkCallback;
+
+/* Return NULL on failure, see fbk_last_error. */
+FbkKeyfile *fbk_keyfile_open(const char *path);
+FbkKeyfile *fbk_keyfile_parse(const char *name, const char *content);
+void fbk_keyfile_free(FbkKeyfile *keyfile);
+
+/* Callbacks are sorted by name and valid until the key file is freed. */
+size_t fbk_keyfile_len(const FbkKeyfile *keyfile);
+const FbkCallback *fbk_keyfile_callback(const FbkKeyfile *keyfile, size_t index);
+const FbkCallback *fbk_keyfile_find(const FbkKeyfile *keyfile, const char *name);
+/* Fills up to capacity entries of out, returns the total number of matches. */
+size_t fbk_keyfile_find_by_key(const FbkKeyfile *keyfile, uint16_t key_code,
+                               uint16_t modifiers, const FbkCallback **out,
+                               size_t capacity);
+
+/* The last error on this thread, NULL if there was none. */
+const char *fbk_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
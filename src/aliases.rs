@@ -0,0 +1,74 @@
+use crate::{Callback, FalconKeyfile};
+
+/// Names used in community docs and the callbacks they stand for. The trim
+/// panel wheels do the same as the trim hat on the stick.
+const BUILTIN: &[(&str, &str)] = &[
+    ("SimTrimNoseDown", "AFElevatorTrimUp"),
+    ("SimTrimNoseUp", "AFElevatorTrimDown"),
+    ("SimTrimRollLeft", "AFAileronTrimLeft"),
+    ("SimTrimRollRight", "AFAileronTrimRight"),
+];
+
+impl FalconKeyfile {
+    /// Makes `callback` find the callback `target` under the name `alias`
+    /// when the file has no callback called `alias`. Registered aliases win
+    /// over the built-in ones.
+    pub fn register_alias(&mut self, alias: &str, target: &str) {
+        self.aliases
+            .insert(String::from(alias), String::from(target));
+    }
+
+    /// The name `alias` stands for, `None` if it is no alias.
+    pub fn resolve_alias(&self, alias: &str) -> Option<&str> {
+        self.aliases.get(alias).map(String::as_str).or_else(|| {
+            BUILTIN
+                .iter()
+                .find(|(name, _)| *name == alias)
+                .map(|(_, target)| *target)
+        })
+    }
+
+    pub(crate) fn callback_by_alias(&self, alias: &str) -> Option<Callback> {
+        self.callbacks.get(self.resolve_alias(alias)?).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ParseOptions, parse_str};
+
+    #[test]
+    fn look_up_through_aliases() {
+        let mut keyfile = parse_str(
+            String::from("inline"),
+            "# aliases
+AFElevatorTrimUp -1 0 0xC8 2 0 0 1 \"STICK: TRIM Up - Nose Down\"
+SimTrimNoseUp -1 0 0xD0 4 0 0 1 \"TRIM: PITCH TRIM Wheel - NOSE UP\"
+AFGearToggle -1 0 0x22 0 0 0 1 \"Gear\"
+",
+            &ParseOptions::default(),
+        )
+        .unwrap()
+        .keyfile;
+
+        let trim = keyfile.callback("SimTrimNoseDown").unwrap();
+        assert_eq!(trim.name, "AFElevatorTrimUp");
+        // the file's own entry wins over the alias
+        assert_eq!(
+            keyfile.callback("SimTrimNoseUp").unwrap().name,
+            "SimTrimNoseUp"
+        );
+        assert!(keyfile.callback("SimTrimRollLeft").is_none());
+
+        keyfile.register_alias("LandingGear", "AFGearToggle");
+        keyfile.register_alias("SimTrimNoseDown", "AFGearToggle");
+        assert_eq!(
+            keyfile.callback("LandingGear").unwrap().name,
+            "AFGearToggle"
+        );
+        assert_eq!(
+            keyfile.resolve_alias("SimTrimNoseDown"),
+            Some("AFGearToggle")
+        );
+    }
+}
@@ -0,0 +1,243 @@
+//! An interactive browser with a section list, a fuzzy searchable list of
+//! callbacks and the details of the selected one.
+
+use falcon_key_file::{Callback, FalconKeyfile};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+/// The entry of the section list that shows every callback.
+const ALL_SECTIONS: &str = "All";
+
+#[derive(PartialEq)]
+enum Focus {
+    Sections,
+    Callbacks,
+    Search,
+}
+
+struct Browser<'a> {
+    keyfile: &'a FalconKeyfile,
+    sections: Vec<String>,
+    section: ListState,
+    query: String,
+    /// The callbacks of the selected section matching the query, best match first.
+    matches: Vec<&'a Callback>,
+    callback: ListState,
+    focus: Focus,
+}
+
+pub fn browse(keyfile: &FalconKeyfile) -> std::io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = Browser::new(keyfile).run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl<'a> Browser<'a> {
+    fn new(keyfile: &'a FalconKeyfile) -> Browser<'a> {
+        let mut sections = vec![String::from(ALL_SECTIONS)];
+        sections.extend(keyfile.sections().iter().cloned());
+        let mut browser = Browser {
+            keyfile,
+            sections,
+            section: ListState::default().with_selected(Some(0)),
+            query: String::new(),
+            matches: vec![],
+            callback: ListState::default(),
+            focus: Focus::Callbacks,
+        };
+        browser.update_matches();
+        browser
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> std::io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match (&self.focus, key.code) {
+                (Focus::Search, KeyCode::Esc | KeyCode::Enter) => self.focus = Focus::Callbacks,
+                (Focus::Search, KeyCode::Backspace) => {
+                    self.query.pop();
+                    self.update_matches();
+                }
+                (Focus::Search, KeyCode::Char(c)) => {
+                    self.query.push(c);
+                    self.update_matches();
+                }
+                (_, KeyCode::Char('q') | KeyCode::Esc) => return Ok(()),
+                (_, KeyCode::Char('/')) => self.focus = Focus::Search,
+                (Focus::Sections, KeyCode::Tab | KeyCode::Right) => self.focus = Focus::Callbacks,
+                (Focus::Callbacks, KeyCode::Tab | KeyCode::Left) => self.focus = Focus::Sections,
+                (Focus::Sections, KeyCode::Down) => {
+                    self.section.select_next();
+                    self.update_matches();
+                }
+                (Focus::Sections, KeyCode::Up) => {
+                    self.section.select_previous();
+                    self.update_matches();
+                }
+                (Focus::Callbacks, KeyCode::Down) => self.callback.select_next(),
+                (Focus::Callbacks, KeyCode::Up) => self.callback.select_previous(),
+                _ => {}
+            }
+        }
+    }
+
+    fn update_matches(&mut self) {
+        let index = self
+            .section
+            .selected()
+            .unwrap_or(0)
+            .min(self.sections.len() - 1);
+        let section = (index > 0).then(|| self.sections[index].as_str());
+
+        let mut scored: Vec<_> = self
+            .keyfile
+            .iter()
+            .filter(|c| section.is_none() || c.section.as_deref() == section)
+            .filter_map(|c| {
+                let description = c.description.as_deref().unwrap_or("");
+                let score =
+                    fuzzy_score(&self.query, &c.name).max(fuzzy_score(&self.query, description))?;
+                Some((score, c))
+            })
+            .collect();
+        scored.sort_by(|(a, x), (b, y)| b.cmp(a).then_with(|| x.name.cmp(&y.name)));
+        self.matches = scored.into_iter().map(|(_, c)| c).collect();
+        self.callback
+            .select((!self.matches.is_empty()).then_some(0));
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [search, body, help] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [sections, callbacks, details] = Layout::horizontal([
+            Constraint::Percentage(25),
+            Constraint::Percentage(40),
+            Constraint::Percentage(35),
+        ])
+        .areas(body);
+
+        let cursor = if self.focus == Focus::Search { "_" } else { "" };
+        frame.render_widget(
+            Paragraph::new(format!("{}{}", self.query, cursor))
+                .block(block("Search", self.focus == Focus::Search)),
+            search,
+        );
+
+        let highlight = Style::default().add_modifier(Modifier::REVERSED);
+        let section_list = List::new(self.sections.iter().map(String::as_str))
+            .block(block("Sections", self.focus == Focus::Sections))
+            .highlight_style(highlight);
+        frame.render_stateful_widget(section_list, sections, &mut self.section);
+
+        let callback_list = List::new(self.matches.iter().map(|c| c.name.as_str()))
+            .block(block(
+                &format!("Callbacks ({})", self.matches.len()),
+                self.focus == Focus::Callbacks,
+            ))
+            .highlight_style(highlight);
+        frame.render_stateful_widget(callback_list, callbacks, &mut self.callback);
+
+        let selected = self.callback.selected().and_then(|i| self.matches.get(i));
+        let lines = selected.map_or_else(Vec::new, |c| self.details(c));
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block("Details", false))
+                .wrap(Wrap { trim: false }),
+            details,
+        );
+
+        frame.render_widget(
+            Paragraph::new("/ search  Tab switch list  ↑↓ move  q quit"),
+            help,
+        );
+    }
+
+    fn details(&self, callback: &Callback) -> Vec<Line<'static>> {
+        let mut lines = vec![
+            Line::from(callback.name.clone()),
+            Line::from(callback.description.clone().unwrap_or_default()),
+            Line::from(""),
+        ];
+        if !callback.is_bound() {
+            lines.push(Line::from("Not bound to a key"));
+            return lines;
+        }
+        lines.push(Line::from(match callback.combo_chord() {
+            Some(combo) => format!("Chord: {}, {}", combo, callback.primary_combo()),
+            None => format!("Chord: {}", callback.primary_combo()),
+        }));
+        if let Some(section) = &callback.section {
+            lines.push(Line::from(format!("Section: {}", section)));
+        }
+
+        let conflicts: Vec<_> = self
+            .keyfile
+            .callbacks_for_combo(&callback.primary_combo())
+            .into_iter()
+            .filter(|c| c.name != callback.name)
+            .filter(|c| c.combo_chord() == callback.combo_chord())
+            .collect();
+        if !conflicts.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from("Conflicts with:"));
+            for conflict in conflicts {
+                lines.push(Line::from(format!("  {}", conflict.name)));
+            }
+        }
+        lines
+    }
+}
+
+fn block(title: &str, focused: bool) -> Block<'static> {
+    let block = Block::bordered().title(title.to_string());
+    if focused {
+        block.border_style(Style::default().add_modifier(Modifier::BOLD))
+    } else {
+        block
+    }
+}
+
+/// Scores `text` by how closely the characters of `query` appear in it in
+/// order, ignoring case. `None` if they do not all appear.
+fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+    let mut score = 0;
+    let mut previous: Option<usize> = None;
+    let mut chars = text.chars().map(|c| c.to_ascii_lowercase()).enumerate();
+    for wanted in query.chars().map(|c| c.to_ascii_lowercase()) {
+        let (index, _) = chars.find(|(_, c)| *c == wanted)?;
+        score += if previous.is_some_and(|p| p + 1 == index) {
+            3
+        } else {
+            1
+        };
+        previous = Some(index);
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefer_consecutive_matches() {
+        assert_eq!(fuzzy_score("", "SimPickle"), Some(0));
+        assert_eq!(fuzzy_score("xyz", "SimPickle"), None);
+        assert!(fuzzy_score("pick", "SimPickle") > fuzzy_score("spkl", "SimPickle"));
+    }
+}
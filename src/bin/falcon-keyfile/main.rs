@@ -0,0 +1,193 @@
+//! Command line access to the library for inspecting, checking, comparing
+//! and exporting key files.
+
+#[cfg(feature = "tui")]
+mod browse;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use falcon_key_file::export::markdown::MarkdownOptions;
+use falcon_key_file::{FalconKeyfile, ParseOptions, Visibility, parse};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(
+    name = "falcon-keyfile",
+    version,
+    about = "Work with Falcon BMS key files"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print a summary of the bindings in a key file.
+    Inspect { file: PathBuf },
+    /// Report malformed lines and chords bound to more than one callback.
+    Validate { file: PathBuf },
+    /// Show the bindings that changed from one key file to another, exits
+    /// with 1 if there are any like `diff`.
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+        #[arg(long, value_enum, default_value = "summary")]
+        format: DiffFormat,
+    },
+    /// Browse the callbacks of a key file interactively.
+    #[cfg(feature = "tui")]
+    Browse { file: PathBuf },
+    /// Write a reference document of the bindings.
+    Export {
+        file: PathBuf,
+        #[arg(long, value_enum, default_value = "md")]
+        format: Format,
+        /// Write to this file instead of standard output.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// Leave out callbacks the BMS setup UI does not show.
+        #[arg(long)]
+        visible_only: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DiffFormat {
+    /// One line per changed callback.
+    Summary,
+    /// Like `diff -u`, for posting in chats and forums.
+    Unified,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Md,
+    Json,
+    Csv,
+}
+
+fn main() -> ExitCode {
+    let result = match Cli::parse().command {
+        Command::Inspect { file } => inspect(&file),
+        Command::Validate { file } => validate(&file),
+        Command::Diff { old, new, format } => diff(&old, &new, format),
+        #[cfg(feature = "tui")]
+        Command::Browse { file } => open(&file).and_then(|keyfile| {
+            browse::browse(&keyfile)
+                .map(|()| ExitCode::SUCCESS)
+                .map_err(|e| e.to_string())
+        }),
+        Command::Export {
+            file,
+            format,
+            output,
+            visible_only,
+        } => export(&file, format, output.as_deref(), visible_only),
+    };
+    match result {
+        Ok(code) => code,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn open(path: &Path) -> Result<FalconKeyfile, String> {
+    FalconKeyfile::from_path(path).map_err(|e| e.to_string())
+}
+
+fn inspect(path: &Path) -> Result<ExitCode, String> {
+    let keyfile = open(path)?;
+    let stats = keyfile.stats();
+    println!("{}", path.display());
+    println!("  callbacks:         {}", stats.callbacks);
+    println!("  bound:             {}", stats.bound);
+    println!("  unbound:           {}", stats.unbound);
+    println!("  joystick bindings: {}", stats.joystick_bindings);
+    println!("  duplicate chords:  {}", stats.duplicate_chords);
+    if !stats.by_section.is_empty() {
+        println!("\nBound callbacks per section:");
+        for (section, count) in &stats.by_section {
+            println!("  {:5}  {}", count, section);
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn validate(path: &Path) -> Result<ExitCode, String> {
+    let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let report = parse(path.display().to_string(), &file, &ParseOptions::default())
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    let conflicts = report.keyfile.conflicts();
+
+    for warning in &report.warnings {
+        println!("warning: {}", warning);
+    }
+    for conflict in &conflicts {
+        println!(
+            "conflict: {} is bound to {}",
+            conflict.binding(),
+            conflict.callbacks.join(", ")
+        );
+    }
+
+    if report.warnings.is_empty() && conflicts.is_empty() {
+        println!("{}: no problems found", path.display());
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+fn diff(old: &Path, new: &Path, format: DiffFormat) -> Result<ExitCode, String> {
+    let diff = open(old)?.diff(&open(new)?);
+    let code = if diff.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    };
+    match format {
+        DiffFormat::Summary => {}
+        DiffFormat::Unified => {
+            print!(
+                "{}",
+                diff.to_unified(&old.display().to_string(), &new.display().to_string())
+            );
+            return Ok(code);
+        }
+        DiffFormat::Json => {
+            println!("{}", diff.to_json());
+            return Ok(code);
+        }
+    }
+    print!("{}", diff);
+    Ok(code)
+}
+
+fn export(
+    path: &Path,
+    format: Format,
+    output: Option<&Path>,
+    visible_only: bool,
+) -> Result<ExitCode, String> {
+    let mut keyfile = open(path)?;
+    if visible_only {
+        keyfile.retain(|c| c.visibility != Visibility::Hidden);
+    }
+    let document = match format {
+        Format::Md => keyfile.to_markdown(&MarkdownOptions::default()),
+        Format::Json => keyfile.to_json(),
+        Format::Csv => keyfile.to_csv(),
+    };
+    match output {
+        Some(output) => {
+            std::fs::write(output, document).map_err(|e| format!("{}: {}", output.display(), e))?
+        }
+        None => print!("{}", document),
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,124 @@
+use crate::{Callback, Key, KeyCombo, ModifierSet};
+use std::fmt;
+
+/// What has to be pressed to trigger a callback: a single chord, or a two-stage
+/// sequence such as `LAlt+C` followed by `P`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Binding {
+    Single(KeyCombo),
+    /// The first chord, then the one that triggers the callback.
+    Sequence(KeyCombo, KeyCombo),
+}
+
+impl Binding {
+    /// The chord pressed first.
+    pub fn first(&self) -> &KeyCombo {
+        match self {
+            Binding::Single(chord) | Binding::Sequence(chord, _) => chord,
+        }
+    }
+
+    /// The chord that triggers the callback.
+    pub fn last(&self) -> &KeyCombo {
+        match self {
+            Binding::Single(chord) | Binding::Sequence(_, chord) => chord,
+        }
+    }
+
+    pub fn is_sequence(&self) -> bool {
+        matches!(self, Binding::Sequence(..))
+    }
+
+    /// Whether both bindings cannot work at the same time: they are the same,
+    /// or one is the single chord the other sequence starts with, which fires
+    /// before the sequence can be completed.
+    pub fn conflicts_with(&self, other: &Binding) -> bool {
+        match (self, other) {
+            (Binding::Single(chord), Binding::Sequence(first, _))
+            | (Binding::Sequence(first, _), Binding::Single(chord)) => chord == first,
+            _ => self == other,
+        }
+    }
+}
+
+/// `LCtrl+B` or `LAlt+C, P`, as in the `diff` command.
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::Single(chord) => write!(f, "{}", chord),
+            Binding::Sequence(first, last) => write!(f, "{}, {}", first, last),
+        }
+    }
+}
+
+impl From<KeyCombo> for Binding {
+    fn from(chord: KeyCombo) -> Binding {
+        Binding::Single(chord)
+    }
+}
+
+impl Callback {
+    /// What triggers this callback, `None` if it is unbound.
+    pub fn binding(&self) -> Option<Binding> {
+        let chord = self.chord()?;
+        Some(match self.combo_chord() {
+            Some(first) => Binding::Sequence(first, chord),
+            None => Binding::Single(chord),
+        })
+    }
+
+    /// Replaces the key codes, modifiers and combo with `binding`, unbinding
+    /// the callback for `None`.
+    pub fn replace_binding(&mut self, binding: Option<Binding>) {
+        let (first, last) = match binding {
+            Some(Binding::Single(chord)) => (None, Some(chord)),
+            Some(Binding::Sequence(first, last)) => (Some(first), Some(last)),
+            None => (None, None),
+        };
+        let (key, modifiers) =
+            last.map_or((Key::Unknown, ModifierSet::EMPTY), |c| (c.key, c.modifiers));
+        self.key_code = match key {
+            Key::Unknown => 0xFFFF,
+            ref key => key.scancode(),
+        };
+        self.readable_key_code = key;
+        self.modifiers = modifiers;
+        let (key, modifiers) =
+            first.map_or((Key::Unknown, ModifierSet::EMPTY), |c| (c.key, c.modifiers));
+        self.combo_key_code = match key {
+            Key::Unknown => 0,
+            ref key => key.scancode(),
+        };
+        self.readable_combo_key_code = key;
+        self.combo_modifiers = modifiers;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FalconKeyfile;
+
+    #[test]
+    fn model_two_stage_bindings() {
+        let keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let mut pilot = keyfile.callback("SimPilotToggle").unwrap();
+        let binding = pilot.binding().unwrap();
+        assert_eq!(binding.to_string(), "LAlt+C, P");
+        assert_eq!(binding.first().to_string(), "LAlt+C");
+        assert_eq!(binding.last().to_string(), "P");
+
+        let single = Binding::from(KeyCombo::parse("LAlt+C").unwrap());
+        assert!(single.conflicts_with(&binding));
+        assert!(binding.conflicts_with(&single));
+        assert!(!Binding::from(KeyCombo::parse("P").unwrap()).conflicts_with(&binding));
+
+        pilot.replace_binding(Some(single.clone()));
+        assert_eq!(pilot.binding(), Some(single));
+        assert_eq!(pilot.combo_key_code, 0);
+        pilot.replace_binding(None);
+        assert!(!pilot.is_bound());
+        assert_eq!(pilot.binding(), None);
+    }
+}
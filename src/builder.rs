@@ -0,0 +1,347 @@
+use crate::{
+    Callback, Conflict, FalconKeyfile, Key, KeyCombo, Modifier, ModifierSet, ParseComboError,
+    Visibility,
+};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Why `KeyfileBuilder::build` rejected a keyfile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// The callback was added more than once.
+    DuplicateCallback(String),
+    /// The callback is bound to a key without a scan code BMS understands.
+    InvalidKey { name: String, key: Key },
+    /// More than one callback is bound to the same chord.
+    DuplicateChord(Conflict),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::DuplicateCallback(name) => write!(f, "callback {} added twice", name),
+            BuildError::InvalidKey { name, key } => {
+                write!(
+                    f,
+                    "callback {} is bound to {:?}, which cannot be bound",
+                    name, key
+                )
+            }
+            BuildError::DuplicateChord(conflict) => write!(
+                f,
+                "{} are all bound to {}",
+                conflict.callbacks.join(", "),
+                conflict.combo
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// Builds a keyfile from scratch, section by section.
+#[derive(Debug, Clone)]
+pub struct KeyfileBuilder {
+    name: String,
+    sections: Vec<String>,
+    callbacks: Vec<Callback>,
+}
+
+impl KeyfileBuilder {
+    pub fn new(name: &str) -> KeyfileBuilder {
+        KeyfileBuilder {
+            name: String::from(name),
+            sections: vec![],
+            callbacks: vec![],
+        }
+    }
+
+    /// Starts a section, the callbacks added after it are listed under it.
+    pub fn section(mut self, name: &str) -> KeyfileBuilder {
+        self.sections.push(String::from(name));
+        self
+    }
+
+    /// Adds a callback bound to `combo`.
+    pub fn bind(self, name: &str, combo: KeyCombo, description: &str) -> KeyfileBuilder {
+        let callback = Callback {
+            key_code: combo.key.scancode(),
+            readable_key_code: combo.key,
+            modifiers: combo.modifiers,
+            ..unbound_callback(name, description)
+        };
+        self.callback(callback)
+    }
+
+    /// Adds a callback without a key.
+    pub fn unbound(self, name: &str, description: &str) -> KeyfileBuilder {
+        self.callback(unbound_callback(name, description))
+    }
+
+    /// Adds a callback as it is, apart from its section.
+    pub fn callback(mut self, mut callback: Callback) -> KeyfileBuilder {
+        callback.section = self.sections.last().cloned();
+        self.callbacks.push(callback);
+        self
+    }
+
+    /// The keyfile, if no callback was added twice, every bound key has a scan
+    /// code and no two callbacks share a chord.
+    pub fn build(self) -> Result<FalconKeyfile, BuildError> {
+        let mut callbacks = HashMap::new();
+        for callback in self.callbacks {
+            for key in [
+                &callback.readable_key_code,
+                &callback.readable_combo_key_code,
+            ] {
+                if callback.is_bound() && matches!(key, Key::Other(_)) {
+                    return Err(BuildError::InvalidKey {
+                        name: callback.name,
+                        key: key.clone(),
+                    });
+                }
+            }
+            if callbacks.contains_key(&callback.name) {
+                return Err(BuildError::DuplicateCallback(callback.name));
+            }
+            callbacks.insert(callback.name.clone(), callback);
+        }
+
+        let mut keyfile = FalconKeyfile::new(self.name, callbacks);
+        keyfile.sections = self.sections;
+        if let Some(conflict) = keyfile.conflicts().into_iter().next() {
+            return Err(BuildError::DuplicateChord(conflict));
+        }
+        Ok(keyfile)
+    }
+}
+
+/// Why `CallbackBuilder::build` rejected a callback.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallbackError {
+    /// A chord string could not be parsed.
+    Chord(ParseComboError),
+    /// The key has no scan code BMS understands.
+    InvalidKey(Key),
+    /// A two-stage combo was given without the chord that follows it.
+    ComboWithoutChord,
+    /// A modifier key held together with itself, such as `LShift+LShift`.
+    ModifierOnItself(KeyCombo),
+}
+
+impl fmt::Display for CallbackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallbackError::Chord(error) => write!(f, "invalid chord: {}", error),
+            CallbackError::InvalidKey(key) => write!(f, "{:?} cannot be bound", key),
+            CallbackError::ComboWithoutChord => write!(f, "combo given without a chord"),
+            CallbackError::ModifierOnItself(combo) => {
+                write!(f, "{} holds a modifier together with itself", combo)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CallbackError {}
+
+/// Builds a `Callback` from keys and modifiers, deriving the raw codes.
+#[derive(Debug, Clone)]
+pub struct CallbackBuilder {
+    callback: Callback,
+    chord: Option<Result<KeyCombo, ParseComboError>>,
+    combo: Option<Result<KeyCombo, ParseComboError>>,
+}
+
+impl CallbackBuilder {
+    pub fn new(name: &str) -> CallbackBuilder {
+        CallbackBuilder {
+            callback: unbound_callback(name, ""),
+            chord: None,
+            combo: None,
+        }
+    }
+
+    /// The key and modifiers that trigger the callback.
+    pub fn chord(mut self, chord: KeyCombo) -> CallbackBuilder {
+        self.chord = Some(Ok(chord));
+        self
+    }
+
+    /// The chord as a string such as `LCtrl+UpArrow`, checked on `build`.
+    pub fn chord_str(mut self, chord: &str) -> CallbackBuilder {
+        self.chord = Some(chord.parse());
+        self
+    }
+
+    /// The combo pressed before the chord for a two-stage binding.
+    pub fn combo(mut self, combo: KeyCombo) -> CallbackBuilder {
+        self.combo = Some(Ok(combo));
+        self
+    }
+
+    /// The combo as a string such as `LAlt+C`, checked on `build`.
+    pub fn combo_str(mut self, combo: &str) -> CallbackBuilder {
+        self.combo = Some(combo.parse());
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> CallbackBuilder {
+        self.callback.description = Some(String::from(description));
+        self
+    }
+
+    pub fn section(mut self, section: &str) -> CallbackBuilder {
+        self.callback.section = Some(String::from(section));
+        self
+    }
+
+    pub fn visibility(mut self, visibility: Visibility) -> CallbackBuilder {
+        self.callback.visibility = visibility;
+        self
+    }
+
+    pub fn build(self) -> Result<Callback, CallbackError> {
+        let mut callback = self.callback;
+        let chord = self.chord.transpose().map_err(CallbackError::Chord)?;
+        let combo = self.combo.transpose().map_err(CallbackError::Chord)?;
+        if combo.is_some() && chord.is_none() {
+            return Err(CallbackError::ComboWithoutChord);
+        }
+
+        for combo in chord.iter().chain(combo.iter()) {
+            if matches!(combo.key, Key::Unknown | Key::Other(_)) {
+                return Err(CallbackError::InvalidKey(combo.key.clone()));
+            }
+            let held = match combo.key {
+                Key::LShift => Some(Modifier::LSHIFT),
+                Key::LControl => Some(Modifier::LCONTROL),
+                _ => None,
+            };
+            if held.is_some_and(|modifier| combo.modifiers.contains(modifier)) {
+                return Err(CallbackError::ModifierOnItself(combo.clone()));
+            }
+        }
+
+        if let Some(chord) = chord {
+            callback.key_code = chord.key.scancode();
+            callback.readable_key_code = chord.key;
+            callback.modifiers = chord.modifiers;
+        }
+        if let Some(combo) = combo {
+            callback.combo_key_code = combo.key.scancode();
+            callback.readable_combo_key_code = combo.key;
+            callback.combo_modifiers = combo.modifiers;
+        }
+        if callback.description.as_deref() == Some("") {
+            callback.description = None;
+        }
+        Ok(callback)
+    }
+}
+
+impl Callback {
+    pub fn builder(name: &str) -> CallbackBuilder {
+        CallbackBuilder::new(name)
+    }
+}
+
+fn unbound_callback(name: &str, description: &str) -> Callback {
+    Callback {
+        name: String::from(name),
+        key_code: 0xFFFF,
+        readable_key_code: Key::Unknown,
+        modifiers: ModifierSet::EMPTY,
+        combo_key_code: 0,
+        readable_combo_key_code: Key::Unknown,
+        combo_modifiers: ModifierSet::EMPTY,
+        visibility: Visibility::Visible,
+        description: Some(String::from(description)),
+        section: None,
+        line: None,
+        raw_line: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_keyfile() {
+        let gear: KeyCombo = "G".parse().unwrap();
+        let keyfile = KeyfileBuilder::new("scratch.key")
+            .section("3.05 GEAR PANEL")
+            .bind("AFGearToggle", gear.clone(), "GEAR: LG Handle - Toggle")
+            .unbound("SimHookToggle", "GEAR: HOOK Switch - Toggle")
+            .section("6.03 KEYBOARD FLIGHT CONTROLS")
+            .bind("AFElevatorTrimUp", "LCtrl+Up".parse().unwrap(), "Trim")
+            .build()
+            .unwrap();
+
+        assert_eq!(keyfile.len(), 3);
+        assert_eq!(keyfile.sections().len(), 2);
+        assert_eq!(keyfile.callbacks_for_combo(&gear)[0].name, "AFGearToggle");
+        assert_eq!(
+            keyfile
+                .callback("SimHookToggle")
+                .unwrap()
+                .section
+                .as_deref(),
+            Some("3.05 GEAR PANEL")
+        );
+
+        let error = KeyfileBuilder::new("scratch.key")
+            .bind("AFGearToggle", gear.clone(), "Gear")
+            .bind("SimHookToggle", gear.clone(), "Hook")
+            .build()
+            .unwrap_err();
+        assert!(matches!(error, BuildError::DuplicateChord(_)));
+        let error = KeyfileBuilder::new("scratch.key")
+            .bind(
+                "AFGearToggle",
+                KeyCombo::new(Key::Other(0x54), ModifierSet::EMPTY),
+                "Gear",
+            )
+            .build()
+            .unwrap_err();
+        assert!(matches!(error, BuildError::InvalidKey { .. }));
+    }
+
+    #[test]
+    fn build_callback() {
+        let callback = Callback::builder("SimPilotToggle")
+            .chord_str("P")
+            .combo_str("LAlt+C")
+            .description("Autopilot")
+            .build()
+            .unwrap();
+        assert_eq!(callback.key_code, 0x19);
+        assert_eq!(callback.combo_key_code, 0x2E);
+        assert_eq!(callback.combo_modifiers.to_mask(), 4);
+        assert_eq!(callback.secondary_combo().unwrap().to_string(), "LAlt+C");
+
+        let unbound = Callback::builder("SimHookToggle").build().unwrap();
+        assert!(!unbound.is_bound());
+        assert_eq!(unbound.description, None);
+
+        let error = |builder: CallbackBuilder| builder.build().unwrap_err();
+        assert!(matches!(
+            error(Callback::builder("A").chord_str("LCtrl+")),
+            CallbackError::Chord(_)
+        ));
+        assert_eq!(
+            error(Callback::builder("A").combo_str("LAlt+C")),
+            CallbackError::ComboWithoutChord
+        );
+        assert!(matches!(
+            error(
+                Callback::builder("A").chord(KeyCombo::new(Key::LShift, Modifier::LSHIFT.into()))
+            ),
+            CallbackError::ModifierOnItself(_)
+        ));
+        assert_eq!(
+            error(Callback::builder("A").chord_str("0x54")),
+            CallbackError::InvalidKey(Key::Other(0x54))
+        );
+    }
+}
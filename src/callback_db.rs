@@ -0,0 +1,90 @@
+//! A table of known BMS callbacks, embedded from `data/callbacks.tsv`.
+
+use levenshtein::levenshtein;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+const TABLE: &str = include_str!("../data/callbacks.tsv");
+
+/// A callback BMS knows, with its canonical description.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnownCallback {
+    pub name: &'static str,
+    /// The section of the full key file the callback is listed in, such as
+    /// `1.01 UI FUNCTIONS`.
+    pub category: &'static str,
+    pub description: &'static str,
+}
+
+/// Lookups in the embedded callback table.
+pub struct KnownCallbacks;
+
+impl KnownCallbacks {
+    pub fn lookup(name: &str) -> Option<&'static KnownCallback> {
+        let table = table();
+        table.by_name.get(name).map(|&i| &table.callbacks[i])
+    }
+
+    /// Every known callback, sorted by name.
+    pub fn all() -> &'static [KnownCallback] {
+        &table().callbacks
+    }
+
+    /// The known callback closest to `name`, for proposing a fix for typos.
+    pub fn closest(name: &str) -> Option<&'static KnownCallback> {
+        Self::all()
+            .iter()
+            .min_by_key(|known| levenshtein(name, known.name))
+    }
+}
+
+struct Table {
+    callbacks: Vec<KnownCallback>,
+    by_name: HashMap<&'static str, usize>,
+}
+
+fn table() -> &'static Table {
+    static TABLE_CELL: OnceLock<Table> = OnceLock::new();
+    TABLE_CELL.get_or_init(|| {
+        let callbacks: Vec<_> = TABLE
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| {
+                let mut columns = line.splitn(3, '\t');
+                Some(KnownCallback {
+                    name: columns.next()?,
+                    category: columns.next()?,
+                    description: columns.next()?,
+                })
+            })
+            .collect();
+        let by_name = callbacks
+            .iter()
+            .enumerate()
+            .map(|(i, known)| (known.name, i))
+            .collect();
+        Table { callbacks, by_name }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_known_callbacks() {
+        let known = KnownCallbacks::lookup("AFGearToggle").unwrap();
+        assert_eq!(known.description, "GEAR: LG Handle - Toggle");
+        assert!(!known.category.is_empty());
+        assert!(KnownCallbacks::lookup("AFGearTogle").is_none());
+        assert_eq!(
+            KnownCallbacks::closest("AFGearTogle").unwrap().name,
+            "AFGearToggle"
+        );
+        assert!(
+            KnownCallbacks::all()
+                .windows(2)
+                .all(|w| w[0].name < w[1].name)
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use crate::joystick::SHIFT_OFFSET;
+use crate::{Callback, FalconKeyfile, JoystickBinding, JoystickInput, Visibility};
+
+impl Visibility {
+    /// The column a key file line stores the visibility in.
+    pub(crate) fn column(&self) -> String {
+        match self {
+            Visibility::Visible => String::from("1"),
+            Visibility::Locked => String::from("-0"),
+            Visibility::Header => String::from("-1"),
+            Visibility::Hidden => String::from("-2"),
+            Visibility::Other(value) => value.to_string(),
+        }
+    }
+}
+
+impl FalconKeyfile {
+    /// Writes the key file in a normalized form, so that the same bindings
+    /// always produce the same text and a change touches as few lines as
+    /// possible.
+    ///
+    /// Callbacks without a section come first, then every section in file
+    /// order under a header line. Within a group keyboard lines are sorted by
+    /// callback name and followed by the joystick lines sorted by input. Key
+    /// codes are written as `0x` with upper case digits and the callback names
+    /// are padded to a common width. Comments, the sound column and the
+    /// `SimDoNothing` entries other than section headers are not kept.
+    pub fn canonicalize(&self) -> String {
+        let width = self
+            .callbacks
+            .keys()
+            .chain(self.joystick.iter().map(|b| &b.callback))
+            .map(String::len)
+            .chain(std::iter::once("SimDoNothing".len()))
+            .max()
+            .unwrap_or_default();
+
+        // sections only known from callbacks, e.g. ones added by a builder
+        let mut extra: Vec<&String> = self
+            .callbacks
+            .values()
+            .filter_map(|c| c.section.as_ref())
+            .chain(self.joystick.iter().filter_map(|b| b.section.as_ref()))
+            .filter(|s| !self.sections.contains(s))
+            .collect();
+        extra.sort();
+        extra.dedup();
+
+        let mut text = format!("# {}\n", self.name);
+        self.write_group(&mut text, None, width);
+        for section in self.sections.iter().chain(extra) {
+            text.push_str(&format!(
+                "{:width$} -1 0 0XFFFFFFFF 0 0 0 -1 \"======== {} ========\"\n",
+                "SimDoNothing", section
+            ));
+            self.write_group(&mut text, Some(section), width);
+        }
+        text
+    }
+
+    fn write_group(&self, text: &mut String, section: Option<&str>, width: usize) {
+        let mut callbacks: Vec<_> = self
+            .callbacks
+            .values()
+            .filter(|c| c.section.as_deref() == section)
+            .collect();
+        callbacks.sort_by(|a, b| a.name.cmp(&b.name));
+        for callback in callbacks {
+            text.push_str(&key_line(callback, width));
+        }
+
+        let mut joystick: Vec<_> = self
+            .joystick
+            .iter()
+            .filter(|b| b.section.as_deref() == section)
+            .collect();
+        joystick.sort_by_key(|b| (input_order(b), b.callback.clone()));
+        for binding in joystick {
+            text.push_str(&joystick_line(binding, width));
+        }
+    }
+}
+
+fn key_line(callback: &Callback, width: usize) -> String {
+    let key = match callback.key_code {
+        0xFFFF => String::from("0XFFFFFFFF"),
+        code => hex(code),
+    };
+    let combo = match callback.combo_key_code {
+        0 => String::from("0"),
+        code => hex(code),
+    };
+    let mut line = format!(
+        "{:width$} -1 0 {} {} {} {} {}",
+        callback.name,
+        key,
+        callback.modifiers.to_mask(),
+        combo,
+        callback.combo_modifiers.to_mask(),
+        callback.visibility.column()
+    );
+    push_description(&mut line, callback.description.as_deref());
+    line
+}
+
+fn joystick_line(binding: &JoystickBinding, width: usize) -> String {
+    let mut line = match binding.input {
+        JoystickInput::Button(button) => {
+            let number = if binding.shifted {
+                button + SHIFT_OFFSET
+            } else {
+                button
+            };
+            format!("{:width$} {} -1 -2 0 0x0 -1", binding.callback, number)
+        }
+        JoystickInput::Pov { hat, direction } => {
+            format!(
+                "{:width$} {} -1 -3 {} 0x0 -1",
+                binding.callback, hat, direction
+            )
+        }
+    };
+    push_description(&mut line, binding.description.as_deref());
+    line
+}
+
+/// Buttons before POV hats, shifted buttons after the unshifted ones.
+fn input_order(binding: &JoystickBinding) -> (u8, u16, u16) {
+    match binding.input {
+        JoystickInput::Button(button) => (0, binding.shifted as u16, button),
+        JoystickInput::Pov { hat, direction } => (1, hat, direction as u16),
+    }
+}
+
+fn hex(code: u16) -> String {
+    format!("0x{:02X}", code)
+}
+
+fn push_description(line: &mut String, description: Option<&str>) {
+    if let Some(description) = description {
+        line.push_str(&format!(" \"{}\"", description));
+    }
+    line.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParseOptions, parse_str};
+
+    #[test]
+    fn canonical_form_round_trips() {
+        let keyfile = FalconKeyfile::from_path("test-data/T16000M-FCS-Full.key").unwrap();
+        let canonical = keyfile.canonicalize();
+        assert!(canonical.contains("\nSimPilotToggle "));
+
+        let parsed = parse_str(
+            String::from("T16000M-FCS-Full.key"),
+            &canonical,
+            &ParseOptions::strict(),
+        )
+        .unwrap()
+        .keyfile;
+        assert!(keyfile.diff(&parsed).is_empty());
+        assert_eq!(
+            parsed.joystick_bindings().len(),
+            keyfile.joystick_bindings().len()
+        );
+        assert_eq!(parsed.sections(), keyfile.sections());
+        assert_eq!(parsed.canonicalize(), canonical);
+    }
+}
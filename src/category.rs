@@ -0,0 +1,148 @@
+use crate::{Callback, FalconKeyfile};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A rough classification of callbacks by the prefix of their name, see
+/// `Category::of`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Category {
+    /// `AF`: flight controls, gear, flaps, brakes and the canopy.
+    FlightControls,
+    /// `SimICP`: the integrated control panel.
+    Icp,
+    /// `SimHUD`: the head-up display and its controls.
+    Hud,
+    /// `SimCBE`: the OSBs around the MFDs.
+    Mfd,
+    /// `Sim`: every other cockpit function.
+    Cockpit,
+    /// `OTW`, `FOV`: out the window views and the camera.
+    Views,
+    /// `Radio`: the radio menus.
+    Radio,
+    /// `Flight`, `Element`, `Wingman`: commands to other flight members.
+    Wingmen,
+    /// `ATC`, `AWACS`, `Tanker`: calls to support units.
+    Support,
+    /// `Time`: time acceleration and pausing.
+    Time,
+    /// Anything else, such as UI functions.
+    Other,
+}
+
+/// Name prefixes and their category, the longest matching prefix wins.
+const PREFIXES: &[(&str, Category)] = &[
+    ("AF", Category::FlightControls),
+    ("SimICP", Category::Icp),
+    ("SimHUD", Category::Hud),
+    ("SimCBE", Category::Mfd),
+    ("SimMFD", Category::Mfd),
+    ("Sim", Category::Cockpit),
+    ("OTW", Category::Views),
+    ("FOV", Category::Views),
+    ("Recenter", Category::Views),
+    ("Radio", Category::Radio),
+    ("Flight", Category::Wingmen),
+    ("Element", Category::Wingmen),
+    ("Wingman", Category::Wingmen),
+    ("ATC", Category::Support),
+    ("AWACS", Category::Support),
+    ("Tanker", Category::Support),
+    ("Time", Category::Time),
+];
+
+impl Category {
+    pub const ALL: [Category; 11] = [
+        Category::FlightControls,
+        Category::Icp,
+        Category::Hud,
+        Category::Mfd,
+        Category::Cockpit,
+        Category::Views,
+        Category::Radio,
+        Category::Wingmen,
+        Category::Support,
+        Category::Time,
+        Category::Other,
+    ];
+
+    /// The category of a callback name.
+    pub fn of(callback_name: &str) -> Category {
+        PREFIXES
+            .iter()
+            .filter(|(prefix, _)| callback_name.starts_with(prefix))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(Category::Other, |(_, category)| *category)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Category::FlightControls => "Flight controls",
+            Category::Icp => "ICP",
+            Category::Hud => "HUD",
+            Category::Mfd => "MFD",
+            Category::Cockpit => "Cockpit",
+            Category::Views => "Views",
+            Category::Radio => "Radio",
+            Category::Wingmen => "Wingmen",
+            Category::Support => "Support",
+            Category::Time => "Time",
+            Category::Other => "Other",
+        }
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Callback {
+    pub fn category(&self) -> Category {
+        Category::of(&self.name)
+    }
+}
+
+impl FalconKeyfile {
+    /// The callbacks grouped by category, each group sorted by name.
+    /// Categories without callbacks are left out.
+    pub fn by_category(&self) -> BTreeMap<Category, Vec<&Callback>> {
+        let mut groups: BTreeMap<Category, Vec<&Callback>> = BTreeMap::new();
+        for callback in self.callbacks.values() {
+            groups
+                .entry(callback.category())
+                .or_default()
+                .push(callback);
+        }
+        for callbacks in groups.values_mut() {
+            callbacks.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_by_prefix() {
+        assert_eq!(Category::of("SimICPCom1"), Category::Icp);
+        assert_eq!(Category::of("SimPickle"), Category::Cockpit);
+        assert_eq!(Category::of("AFGearToggle"), Category::FlightControls);
+        assert_eq!(Category::of("OTWViewLeft"), Category::Views);
+        assert_eq!(Category::of("WingmanGiveStatus"), Category::Wingmen);
+        assert_eq!(Category::of("LoadCampaign"), Category::Other);
+
+        let keyfile = FalconKeyfile::from_path("test-data/T16000M-FCS-Full.key").unwrap();
+        let groups = keyfile.by_category();
+        assert_eq!(groups.values().map(Vec::len).sum::<usize>(), keyfile.len());
+        assert!(
+            groups[&Category::Icp]
+                .iter()
+                .all(|c| c.name.starts_with("SimICP"))
+        );
+    }
+}
@@ -0,0 +1,315 @@
+use crate::key::named_keys;
+use crate::{Key, Modifier, ModifierSet};
+use std::fmt;
+use std::str::FromStr;
+
+/// A key together with the modifiers held while pressing it, e.g. `LCtrl+LShift+UpArrow`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyCombo {
+    pub key: Key,
+    pub modifiers: ModifierSet,
+}
+
+impl KeyCombo {
+    pub fn new(key: Key, modifiers: ModifierSet) -> KeyCombo {
+        KeyCombo { key, modifiers }
+    }
+
+    /// Parses a chord as people write it: besides the `LCtrl+LShift+UpArrow`
+    /// form of `from_str` this accepts `-` and spaces between the parts, key
+    /// names of several words and the names of `ChordFormat`, such as
+    /// `lalt c`, `ALT-c`, `Shift Page Up`, `LC-UP` or `Left Control + Up Arrow`.
+    pub fn parse(text: &str) -> Result<KeyCombo, ParseComboError> {
+        let strict = text.parse::<KeyCombo>();
+        if strict.is_ok() || strict == Err(ParseComboError::Empty) {
+            return strict;
+        }
+
+        // a trailing `+` or `-` right after a separator is the key, as in `Ctrl+-`
+        let text = text.trim();
+        let (rest, last) = match text.char_indices().last() {
+            Some((i, '+' | '-')) if i == 0 || text[..i].ends_with(['+', '-', ' ']) => {
+                (&text[..i], Some(&text[i..]))
+            }
+            _ => (text, None),
+        };
+        let words: Vec<&str> = rest
+            .split(['+', '-', ' ', '\t'])
+            .filter(|word| !word.is_empty())
+            .chain(last)
+            .collect();
+
+        let mut modifiers = ModifierSet::EMPTY;
+        let mut key_words = vec![];
+        let mut words = words.into_iter().peekable();
+        while let Some(word) = words.next() {
+            // `Left Control` as written by `ChordFormat::Long`
+            let left_modifier = word
+                .eq_ignore_ascii_case("left")
+                .then(|| words.peek().and_then(|next| parse_modifier(next)))
+                .flatten();
+            if let Some(modifier) = left_modifier {
+                words.next();
+                modifiers.insert(modifier);
+            } else if let Some(modifier) = parse_modifier(word) {
+                modifiers.insert(modifier);
+            } else {
+                key_words.push(word);
+            }
+        }
+
+        match key_words.len() {
+            0 => Err(ParseComboError::MissingKey),
+            count => match parse_key(&key_words.concat()) {
+                Some(key) => Ok(KeyCombo { key, modifiers }),
+                None if count > 1 && key_words.iter().all(|word| parse_key(word).is_some()) => {
+                    Err(ParseComboError::MultipleKeys)
+                }
+                None => Err(ParseComboError::UnknownKey(key_words.join(" "))),
+            },
+        }
+    }
+
+    /// Whether Windows handles this combo itself, so BMS never sees it.
+    pub fn is_reserved(&self) -> bool {
+        let alt = ModifierSet::from(Modifier::LALT);
+        let ctrl = ModifierSet::from(Modifier::LCONTROL);
+        match self.key {
+            Key::LWin | Key::RWin | Key::PrintScr => true,
+            Key::Tab | Key::F4 | Key::Space => self.modifiers == alt,
+            Key::Escape => {
+                self.modifiers == ctrl
+                    || self.modifiers == alt
+                    || self.modifiers == ctrl | Modifier::LSHIFT
+            }
+            Key::Delete => self.modifiers == ctrl | Modifier::LALT,
+            _ => false,
+        }
+    }
+}
+
+/// Modifiers in the order they are written in a combo.
+pub(crate) const MODIFIER_ORDER: [Modifier; 3] =
+    [Modifier::LCONTROL, Modifier::LSHIFT, Modifier::LALT];
+
+/// The names of the modifiers as written in a combo, e.g. `["LCtrl", "LShift"]`.
+pub(crate) fn modifier_names(modifiers: ModifierSet) -> Vec<&'static str> {
+    MODIFIER_ORDER
+        .into_iter()
+        .filter(|m| modifiers.contains(*m))
+        .map(|m| match m {
+            Modifier::LCONTROL => "LCtrl",
+            Modifier::LSHIFT => "LShift",
+            Modifier::LALT => "LAlt",
+        })
+        .collect()
+}
+
+/// The name of a key as written in a combo, e.g. `UpArrow` or `0x54`.
+pub(crate) fn key_name(key: &Key) -> String {
+    match key {
+        Key::Other(number) => format!("{:#04X}", number),
+        key => format!("{:?}", key),
+    }
+}
+
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for name in modifier_names(self.modifiers) {
+            write!(f, "{}+", name)?;
+        }
+        write!(f, "{}", key_name(&self.key))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseComboError {
+    Empty,
+    UnknownKey(String),
+    MissingKey,
+    MultipleKeys,
+}
+
+impl fmt::Display for ParseComboError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseComboError::Empty => write!(f, "empty key combo"),
+            ParseComboError::UnknownKey(name) => write!(f, "unknown key '{}'", name),
+            ParseComboError::MissingKey => write!(f, "key combo has only modifiers"),
+            ParseComboError::MultipleKeys => write!(f, "key combo has more than one key"),
+        }
+    }
+}
+
+impl std::error::Error for ParseComboError {}
+
+/// Parses combos such as `LCtrl+LShift+UpArrow`, `ctrl+shift+up` or `Alt+0x2E`.
+impl FromStr for KeyCombo {
+    type Err = ParseComboError;
+
+    fn from_str(s: &str) -> Result<KeyCombo, ParseComboError> {
+        if s.trim().is_empty() {
+            return Err(ParseComboError::Empty);
+        }
+
+        let mut modifiers = ModifierSet::EMPTY;
+        let mut key = None;
+        for part in s.split('+').map(str::trim) {
+            if let Some(modifier) = parse_modifier(part) {
+                modifiers.insert(modifier);
+            } else if key.is_some() {
+                return Err(ParseComboError::MultipleKeys);
+            } else {
+                key = Some(
+                    parse_key(part).ok_or_else(|| ParseComboError::UnknownKey(part.to_string()))?,
+                );
+            }
+        }
+
+        key.map(|key| KeyCombo { key, modifiers })
+            .ok_or(ParseComboError::MissingKey)
+    }
+}
+
+pub(crate) fn parse_modifier(name: &str) -> Option<Modifier> {
+    match name.to_lowercase().as_str() {
+        "lshift" | "shift" | "lshft" | "shft" | "ls" => Some(Modifier::LSHIFT),
+        "lctrl" | "ctrl" | "lcontrol" | "control" | "lctl" | "ctl" | "lc" => {
+            Some(Modifier::LCONTROL)
+        }
+        "lalt" | "alt" | "lmenu" | "la" => Some(Modifier::LALT),
+        _ => None,
+    }
+}
+
+pub(crate) fn parse_key(name: &str) -> Option<Key> {
+    let lower = name.to_lowercase();
+    if let Some(hex) = lower.strip_prefix("0x") {
+        return u16::from_str_radix(hex, 16).ok().map(Key::from_scancode);
+    }
+
+    let alias = match lower.as_str() {
+        "up" => Some(Key::UpArrow),
+        "down" => Some(Key::DownArrow),
+        "left" => Some(Key::LeftArrow),
+        "right" => Some(Key::RightArrow),
+        "dn" => Some(Key::DownArrow),
+        "lt" => Some(Key::LeftArrow),
+        "rt" => Some(Key::RightArrow),
+        "esc" => Some(Key::Escape),
+        "enter" | "ret" => Some(Key::Return),
+        "bksp" => Some(Key::Backspace),
+        "spc" => Some(Key::Space),
+        "caps" => Some(Key::CapsLock),
+        "numlk" => Some(Key::Numlock),
+        "scrlk" => Some(Key::ScrollLock),
+        "prtsc" => Some(Key::PrintScr),
+        "num*" => Some(Key::Multiply),
+        "num/" => Some(Key::Divide),
+        "num-" => Some(Key::Subtract),
+        "num+" => Some(Key::Add),
+        "num." => Some(Key::Decimal),
+        "nument" => Some(Key::NumpadEnter),
+        "pgup" => Some(Key::PageUp),
+        "pgdn" => Some(Key::PageDown),
+        "del" => Some(Key::Delete),
+        "ins" => Some(Key::Insert),
+        "-" => Some(Key::Minus),
+        "=" => Some(Key::Equals),
+        "[" => Some(Key::LeftBracket),
+        "]" => Some(Key::RightBracket),
+        ";" => Some(Key::Semicolon),
+        "'" => Some(Key::Apostrophe),
+        "`" => Some(Key::BackQuote),
+        "\\" => Some(Key::Backslash),
+        "," => Some(Key::Comma),
+        "." => Some(Key::Period),
+        "/" => Some(Key::Slash),
+        _ => None,
+    };
+    if alias.is_some() {
+        return alias;
+    }
+
+    // single digits and key names as spelled by the `Key` variants
+    let lower = match lower.as_str() {
+        digit @ ("0" | "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9") => {
+            format!("num{}", digit)
+        }
+        _ => lower,
+    };
+    named_keys().find(|key| format!("{:?}", key).to_lowercase() == lower)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_and_parse_round_trip() {
+        let combo = KeyCombo::new(
+            Key::UpArrow,
+            [Modifier::LSHIFT, Modifier::LCONTROL].into_iter().collect(),
+        );
+        assert_eq!(combo.to_string(), "LCtrl+LShift+UpArrow");
+        assert_eq!("LCtrl+LShift+UpArrow".parse::<KeyCombo>().unwrap(), combo);
+        assert_eq!("shift + ctrl + up".parse::<KeyCombo>().unwrap(), combo);
+
+        let other = KeyCombo::new(Key::Other(0x54), ModifierSet::EMPTY);
+        assert_eq!(other.to_string(), "0x54");
+        assert_eq!(other.to_string().parse::<KeyCombo>().unwrap(), other);
+    }
+
+    #[test]
+    fn parse_human_chords() {
+        let parse = |text| KeyCombo::parse(text).unwrap().to_string();
+        assert_eq!(parse("lalt c"), "LAlt+C");
+        assert_eq!(parse("LCtrl+Shift+F1"), "LCtrl+LShift+F1");
+        assert_eq!(parse("ALT-c"), "LAlt+C");
+        assert_eq!(parse("Shift Page Up"), "LShift+PageUp");
+        assert_eq!(parse("LC-UP"), "LCtrl+UpArrow");
+        assert_eq!(parse("Left Control + Up Arrow"), "LCtrl+UpArrow");
+        assert_eq!(parse("Left Arrow"), "LeftArrow");
+        assert_eq!(parse("ctrl+-"), "LCtrl+Minus");
+        assert_eq!(parse("alt -"), "LAlt+Minus");
+        assert_eq!(parse("NUM-"), "Subtract");
+        assert_eq!(parse("numpad 7"), "Numpad7");
+
+        let combo = KeyCombo::parse("lctrl lshift c").unwrap();
+        assert_eq!(combo.key.scancode(), 0x2E);
+        assert_eq!(combo.modifiers.to_mask(), 3);
+
+        assert_eq!(KeyCombo::parse(" "), Err(ParseComboError::Empty));
+        assert_eq!(
+            KeyCombo::parse("ctrl alt"),
+            Err(ParseComboError::MissingKey)
+        );
+        assert_eq!(
+            KeyCombo::parse("alt a b"),
+            Err(ParseComboError::MultipleKeys)
+        );
+        assert_eq!(
+            KeyCombo::parse("alt hyper key"),
+            Err(ParseComboError::UnknownKey(String::from("hyper key")))
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!("".parse::<KeyCombo>(), Err(ParseComboError::Empty));
+        assert_eq!(
+            "Ctrl+Alt".parse::<KeyCombo>(),
+            Err(ParseComboError::MissingKey)
+        );
+        assert_eq!(
+            "A+B".parse::<KeyCombo>(),
+            Err(ParseComboError::MultipleKeys)
+        );
+        assert_eq!(
+            "Ctrl+Hyper".parse::<KeyCombo>(),
+            Err(ParseComboError::UnknownKey(String::from("Hyper")))
+        );
+        assert_eq!("alt+1".parse::<KeyCombo>().unwrap().key, Key::Num1);
+    }
+}
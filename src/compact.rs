@@ -0,0 +1,184 @@
+//! A compact read-only form of key files for holding many of them at once.
+//!
+//! Names, descriptions and sections are interned in an `Interner` shared by
+//! all compacted files, so a callback name present in a hundred files is
+//! stored once. `CompactKeyfile::to_keyfile` turns one back into a
+//! `FalconKeyfile` when it needs to be edited or written.
+
+use crate::{Callback, FalconKeyfile, Key, KeyCombo, ModifierSet, Visibility, Workspace};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Shared storage for repeated strings.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    strings: HashSet<Arc<str>>,
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Interner::default()
+    }
+
+    /// The shared copy of `text`, added on first use.
+    pub fn intern(&mut self, text: &str) -> Arc<str> {
+        if let Some(existing) = self.strings.get(text) {
+            return existing.clone();
+        }
+        let text: Arc<str> = Arc::from(text);
+        self.strings.insert(text.clone());
+        text
+    }
+
+    /// The number of distinct strings.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+/// A callback of a `CompactKeyfile`, see `Callback` for the fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactCallback {
+    pub name: Arc<str>,
+    pub key_code: u16,
+    pub modifiers: ModifierSet,
+    pub combo_key_code: u16,
+    pub combo_modifiers: ModifierSet,
+    pub visibility: Visibility,
+    pub description: Option<Arc<str>>,
+    pub section: Option<Arc<str>>,
+}
+
+impl CompactCallback {
+    fn new(callback: &Callback, interner: &mut Interner) -> CompactCallback {
+        CompactCallback {
+            name: interner.intern(&callback.name),
+            key_code: callback.key_code,
+            modifiers: callback.modifiers,
+            combo_key_code: callback.combo_key_code,
+            combo_modifiers: callback.combo_modifiers,
+            visibility: callback.visibility,
+            description: callback.description.as_deref().map(|d| interner.intern(d)),
+            section: callback.section.as_deref().map(|s| interner.intern(s)),
+        }
+    }
+
+    pub fn to_callback(&self) -> Callback {
+        Callback {
+            name: self.name.to_string(),
+            key_code: self.key_code,
+            readable_key_code: Key::from_scancode(self.key_code),
+            modifiers: self.modifiers,
+            combo_key_code: self.combo_key_code,
+            readable_combo_key_code: Key::from_scancode(self.combo_key_code),
+            combo_modifiers: self.combo_modifiers,
+            visibility: self.visibility,
+            description: self.description.as_deref().map(String::from),
+            section: self.section.as_deref().map(String::from),
+            line: None,
+            raw_line: None,
+        }
+    }
+}
+
+/// The callbacks of a key file sorted by name, with shared strings.
+#[derive(Debug, Clone)]
+pub struct CompactKeyfile {
+    pub name: Arc<str>,
+    callbacks: Box<[CompactCallback]>,
+}
+
+impl CompactKeyfile {
+    pub fn callbacks(&self) -> &[CompactCallback] {
+        &self.callbacks
+    }
+
+    pub fn callback(&self, name: &str) -> Option<&CompactCallback> {
+        self.callbacks
+            .binary_search_by(|c| (*c.name).cmp(name))
+            .ok()
+            .map(|index| &self.callbacks[index])
+    }
+
+    /// The callbacks whose last chord is exactly this one, sorted by name,
+    /// including two-stage bindings ending with it as in
+    /// `FalconKeyfile::callbacks_for_combo`.
+    pub fn callbacks_for_combo(&self, combo: &KeyCombo) -> Vec<&CompactCallback> {
+        let key_code = combo.key.scancode();
+        self.callbacks
+            .iter()
+            .filter(|c| c.key_code == key_code && c.modifiers == combo.modifiers)
+            .collect()
+    }
+
+    /// A `FalconKeyfile` with the same callbacks. Line numbers, joystick
+    /// bindings and the section order are not kept by the compact form.
+    pub fn to_keyfile(&self) -> FalconKeyfile {
+        let callbacks: HashMap<_, _> = self
+            .callbacks
+            .iter()
+            .map(|c| (c.name.to_string(), c.to_callback()))
+            .collect();
+        FalconKeyfile::new(self.name.to_string(), callbacks)
+    }
+}
+
+impl FalconKeyfile {
+    /// The compact form of this key file, sharing strings through `interner`.
+    pub fn compact(&self, interner: &mut Interner) -> CompactKeyfile {
+        let mut callbacks: Vec<_> = self
+            .callbacks
+            .values()
+            .map(|c| CompactCallback::new(c, interner))
+            .collect();
+        callbacks.sort_by(|a, b| a.name.cmp(&b.name));
+        CompactKeyfile {
+            name: interner.intern(&self.name),
+            callbacks: callbacks.into_boxed_slice(),
+        }
+    }
+}
+
+impl Workspace {
+    /// The compact form of every key file, all sharing one interner.
+    pub fn compact(&self) -> (Vec<CompactKeyfile>, Interner) {
+        let mut interner = Interner::new();
+        let keyfiles = self
+            .keyfiles()
+            .iter()
+            .map(|k| k.compact(&mut interner))
+            .collect();
+        (keyfiles, interner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn share_strings_between_files() {
+        let keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let mut interner = Interner::new();
+        let first = keyfile.compact(&mut interner);
+        let strings = interner.len();
+        let second = keyfile.compact(&mut interner);
+        assert_eq!(interner.len(), strings);
+
+        let a = first.callback("SimPilotToggle").unwrap();
+        let b = second.callback("SimPilotToggle").unwrap();
+        assert!(Arc::ptr_eq(&a.name, &b.name));
+        assert!(
+            a.to_callback()
+                .same_binding(&keyfile.callback("SimPilotToggle").unwrap())
+        );
+
+        let restored = first.to_keyfile();
+        assert_eq!(restored.len(), keyfile.len());
+        assert!(restored.diff(&keyfile).is_empty());
+    }
+}
@@ -0,0 +1,161 @@
+//! Settings from `Falcon BMS.cfg` and `Falcon BMS User.cfg`, which hold lines
+//! like
+//!
+//! ```text
+//! set g_bMouseButton4TogglesClickable 1   // comment
+//! ```
+//!
+//! BMS reads `User/Config/Falcon BMS.cfg` first and lets the user file
+//! override it, `BmsConfig::with_overrides` does the same.
+
+use std::path::Path;
+
+/// One `set` line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigEntry {
+    pub name: String,
+    /// The value as written, quotes removed.
+    pub value: String,
+    /// The 1-based line the entry was read from.
+    pub line: usize,
+}
+
+/// The settings of a config file in file order, a later entry of the same
+/// name replaces an earlier one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BmsConfig {
+    entries: Vec<ConfigEntry>,
+}
+
+/// Name fragments of the settings concerning keyboard and controllers.
+const INPUT_FRAGMENTS: [&str; 8] = [
+    "key", "input", "joystick", "throttle", "hotas", "mouse", "button", "axis",
+];
+
+impl BmsConfig {
+    /// Reads the `set` lines of a config file, everything else is ignored.
+    pub fn parse_str(content: &str) -> BmsConfig {
+        let mut config = BmsConfig::default();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.split("//").next().unwrap_or_default().trim();
+            let mut words = line.splitn(3, char::is_whitespace);
+            if !words.next().is_some_and(|w| w.eq_ignore_ascii_case("set")) {
+                continue;
+            }
+            let (Some(name), Some(value)) = (words.next(), words.next()) else {
+                continue;
+            };
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            config.set(ConfigEntry {
+                name: String::from(name),
+                value: String::from(value),
+                line: index + 1,
+            });
+        }
+        config
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> std::io::Result<BmsConfig> {
+        let bytes = std::fs::read(path)?;
+        Ok(BmsConfig::parse_str(&crate::encoding::decode(&bytes)))
+    }
+
+    /// This config with the entries of `overrides` replacing its own.
+    pub fn with_overrides(mut self, overrides: &BmsConfig) -> BmsConfig {
+        for entry in &overrides.entries {
+            self.set(entry.clone());
+        }
+        self
+    }
+
+    fn set(&mut self, entry: ConfigEntry) {
+        match self
+            .entries
+            .iter_mut()
+            .find(|e| e.name.eq_ignore_ascii_case(&entry.name))
+        {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    pub fn entries(&self) -> &[ConfigEntry] {
+        &self.entries
+    }
+
+    /// The entry of this name, ignoring case like BMS does.
+    pub fn get(&self, name: &str) -> Option<&ConfigEntry> {
+        self.entries
+            .iter()
+            .find(|e| e.name.eq_ignore_ascii_case(name))
+    }
+
+    /// A `g_b` style flag, `None` if missing or not `0` or `1`.
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        match self.get(name)?.value.as_str() {
+            "1" => Some(true),
+            "0" => Some(false),
+            _ => None,
+        }
+    }
+
+    pub fn get_int(&self, name: &str) -> Option<i64> {
+        self.get(name)?.value.parse().ok()
+    }
+
+    pub fn get_float(&self, name: &str) -> Option<f64> {
+        self.get(name)?.value.parse().ok()
+    }
+
+    /// The settings concerning keyboard, mouse and controllers, picked by
+    /// their names.
+    pub fn input_entries(&self) -> impl Iterator<Item = &ConfigEntry> {
+        self.entries.iter().filter(|entry| {
+            let name = entry.name.to_ascii_lowercase();
+            INPUT_FRAGMENTS
+                .iter()
+                .any(|fragment| name.contains(fragment))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_config_overrides_defaults() {
+        let defaults = BmsConfig::parse_str(
+            "// Falcon BMS.cfg\n\
+             set g_bMouseButton4TogglesClickable 0 // default\n\
+             set g_fCockpitFOV 60.0\n\
+             set g_sThrottleName \"Throttle - HOTAS Warthog\"\n\
+             not a setting\n",
+        );
+        let user = BmsConfig::parse_str("set g_bMouseButton4TogglesClickable 1\n");
+        let config = defaults.with_overrides(&user);
+
+        assert_eq!(
+            config.get_bool("g_bMouseButton4TogglesClickable"),
+            Some(true)
+        );
+        assert_eq!(
+            config.get("g_bmousebutton4togglesclickable").unwrap().line,
+            1
+        );
+        assert_eq!(config.get_float("g_fCockpitFOV"), Some(60.0));
+        assert_eq!(
+            config.get("g_sThrottleName").unwrap().value,
+            "Throttle - HOTAS Warthog"
+        );
+        let input: Vec<_> = config.input_entries().map(|e| e.name.as_str()).collect();
+        assert_eq!(
+            input,
+            ["g_bMouseButton4TogglesClickable", "g_sThrottleName"]
+        );
+    }
+}
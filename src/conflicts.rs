@@ -0,0 +1,149 @@
+use crate::{Binding, Callback, FalconKeyfile, KeyCombo};
+use std::collections::HashMap;
+
+/// Callbacks that are all triggered by the same chord, so only one of them can
+/// actually fire in BMS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub combo: KeyCombo,
+    /// The combo pressed before `combo` for two-stage bindings.
+    pub secondary_combo: Option<KeyCombo>,
+    /// Names of the conflicting callbacks, sorted.
+    pub callbacks: Vec<String>,
+}
+
+impl Conflict {
+    /// The shared chord, or the two-stage sequence ending with it.
+    pub fn binding(&self) -> Binding {
+        match &self.secondary_combo {
+            Some(first) => Binding::Sequence(first.clone(), self.combo.clone()),
+            None => Binding::Single(self.combo.clone()),
+        }
+    }
+}
+
+impl FalconKeyfile {
+    /// Groups of bound callbacks sharing the same key, modifiers and combo
+    /// sequence, sorted by chord.
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        let mut by_chord: HashMap<(KeyCombo, Option<KeyCombo>), Vec<&Callback>> = HashMap::new();
+        for callback in self.callbacks.values().filter(|c| c.is_bound()) {
+            by_chord
+                .entry((callback.primary_combo(), callback.secondary_combo()))
+                .or_default()
+                .push(callback);
+        }
+
+        let mut conflicts: Vec<_> = by_chord
+            .into_iter()
+            .filter(|(_, callbacks)| callbacks.len() > 1)
+            .map(|((combo, secondary_combo), callbacks)| {
+                let mut callbacks: Vec<_> = callbacks.iter().map(|c| c.name.clone()).collect();
+                callbacks.sort();
+                Conflict {
+                    combo,
+                    secondary_combo,
+                    callbacks,
+                }
+            })
+            .collect();
+        conflicts.sort_by_key(|c| {
+            (
+                c.combo.key.scancode(),
+                c.combo.modifiers.to_mask(),
+                c.secondary_combo.as_ref().map(|s| s.to_string()),
+            )
+        });
+        conflicts
+    }
+
+    /// Groups of callbacks bound to a single chord together with the two-stage
+    /// bindings starting with it, which cannot be completed because the single
+    /// chord fires first. `combo` is the shared chord, `secondary_combo` is
+    /// always `None`. Sorted by chord.
+    pub fn sequence_conflicts(&self) -> Vec<Conflict> {
+        let mut by_first: HashMap<KeyCombo, (Vec<&str>, Vec<&str>)> = HashMap::new();
+        for callback in self.callbacks.values() {
+            let Some(binding) = callback.binding() else {
+                continue;
+            };
+            let (singles, sequences) = by_first.entry(binding.first().clone()).or_default();
+            match binding {
+                Binding::Single(_) => singles.push(&callback.name),
+                Binding::Sequence(..) => sequences.push(&callback.name),
+            }
+        }
+
+        let mut conflicts: Vec<_> = by_first
+            .into_iter()
+            .filter(|(_, (singles, sequences))| !singles.is_empty() && !sequences.is_empty())
+            .map(|(combo, (singles, sequences))| {
+                let mut callbacks: Vec<_> = singles
+                    .into_iter()
+                    .chain(sequences)
+                    .map(String::from)
+                    .collect();
+                callbacks.sort();
+                Conflict {
+                    combo,
+                    secondary_combo: None,
+                    callbacks,
+                }
+            })
+            .collect();
+        conflicts.sort_by_key(|c| (c.combo.key.scancode(), c.combo.modifiers.to_mask()));
+        conflicts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FalconKeyfile, ParseOptions, parse_str};
+
+    #[test]
+    fn find_conflicting_bindings() {
+        let content = "# header
+AFBrakesToggle -1 0 0x30 0 0 0 1 \"Brakes\"
+SimHookToggle -1 0 0x30 0 0 0 1 \"Hook\"
+AFGearToggle -1 0 0x30 2 0 0 1 \"Gear\"
+SimPilotToggle -1 0 0x30 0 0x2E 4 1 \"Autopilot\"
+AFFlapsToggle -1 0 0XFFFFFFFF 0 0 0 1 \"Flaps\"
+AFAirbrakesToggle -1 0 0XFFFFFFFF 0 0 0 1 \"Airbrakes\"
+";
+        let keyfile = parse_str(String::from("inline"), content, &ParseOptions::default())
+            .unwrap()
+            .keyfile;
+        let conflicts = keyfile.conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].combo.to_string(), "B");
+        assert_eq!(conflicts[0].secondary_combo, None);
+        assert_eq!(
+            conflicts[0].callbacks,
+            vec!["AFBrakesToggle", "SimHookToggle"]
+        );
+
+        let keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        assert!(keyfile.conflicts().iter().all(|c| c.callbacks.len() > 1));
+    }
+
+    #[test]
+    fn find_sequence_conflicts() {
+        let content = "# header
+SimHookToggle -1 0 0x2E 4 0 0 1 \"Hook\"
+SimPilotToggle -1 0 0x19 0 0x2E 4 1 \"Autopilot\"
+AFGearToggle -1 0 0x22 0 0x2E 4 1 \"Gear\"
+AFFlapsToggle -1 0 0x21 0 0x2E 2 1 \"Flaps\"
+";
+        let keyfile = parse_str(String::from("inline"), content, &ParseOptions::default())
+            .unwrap()
+            .keyfile;
+        assert!(keyfile.conflicts().is_empty());
+        let conflicts = keyfile.sequence_conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].combo.to_string(), "LAlt+C");
+        assert_eq!(
+            conflicts[0].callbacks,
+            vec!["AFGearToggle", "SimHookToggle", "SimPilotToggle"]
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use crate::FalconKeyfile;
+
+/// How much of a master callback list a keyfile covers, see
+/// `FalconKeyfile::coverage_of`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Coverage {
+    /// Categories in the order of the master list.
+    pub categories: Vec<CategoryCoverage>,
+}
+
+/// The callbacks of one category of the master list.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CategoryCoverage {
+    pub category: String,
+    /// Callbacks bound to a key or joystick input.
+    pub bound: Vec<String>,
+    /// Callbacks listed in the keyfile without a binding.
+    pub unbound: Vec<String>,
+    /// Callbacks the keyfile does not list at all.
+    pub absent: Vec<String>,
+}
+
+impl CategoryCoverage {
+    pub fn total(&self) -> usize {
+        self.bound.len() + self.unbound.len() + self.absent.len()
+    }
+}
+
+impl Coverage {
+    pub fn bound(&self) -> usize {
+        self.categories.iter().map(|c| c.bound.len()).sum()
+    }
+
+    pub fn total(&self) -> usize {
+        self.categories.iter().map(CategoryCoverage::total).sum()
+    }
+
+    /// Share of bound callbacks of the whole master list, 0 to 1.
+    pub fn ratio(&self) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => self.bound() as f64 / total as f64,
+        }
+    }
+}
+
+impl FalconKeyfile {
+    /// Coverage of a master list of callback names and their categories.
+    pub fn coverage_of<'a>(
+        &self,
+        master: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Coverage {
+        let mut coverage = Coverage::default();
+        for (name, category) in master {
+            let index = match coverage
+                .categories
+                .iter()
+                .position(|c| c.category == category)
+            {
+                Some(index) => index,
+                None => {
+                    coverage.categories.push(CategoryCoverage {
+                        category: String::from(category),
+                        ..CategoryCoverage::default()
+                    });
+                    coverage.categories.len() - 1
+                }
+            };
+            let entry = &mut coverage.categories[index];
+
+            let callback = self.callbacks.get(name);
+            let name = String::from(name);
+            if callback.is_some_and(|c| c.is_bound())
+                || self.joystick.iter().any(|b| b.callback == name)
+            {
+                entry.bound.push(name);
+            } else if callback.is_some() {
+                entry.unbound.push(name);
+            } else {
+                entry.absent.push(name);
+            }
+        }
+        coverage
+    }
+
+    /// Coverage of the embedded callback table, by section of the full key file.
+    #[cfg(feature = "callback-db")]
+    pub fn coverage(&self) -> Coverage {
+        self.coverage_of(
+            crate::KnownCallbacks::all()
+                .iter()
+                .map(|known| (known.name, known.category)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FalconKeyfile;
+
+    #[test]
+    fn cover_master_list() {
+        let keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let master = [
+            ("AFElevatorTrimUp", "Trim"),
+            ("SimCursorUpLeft", "Cursor"),
+            ("AFElevatorTrimDown", "Trim"),
+            ("SimNoSuchCallback", "Cursor"),
+        ];
+        let coverage = keyfile.coverage_of(master);
+
+        assert_eq!(coverage.categories.len(), 2);
+        assert_eq!(coverage.categories[0].category, "Trim");
+        assert_eq!(coverage.categories[0].bound.len(), 2);
+        assert_eq!(coverage.categories[1].unbound, vec!["SimCursorUpLeft"]);
+        assert_eq!(coverage.categories[1].absent, vec!["SimNoSuchCallback"]);
+        assert_eq!(coverage.ratio(), 0.5);
+    }
+}
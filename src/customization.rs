@@ -0,0 +1,191 @@
+use crate::diff::binding;
+use crate::joystick::input_name;
+use crate::{Callback, CallbackChange, FalconKeyfile, JoystickBinding};
+use std::fmt;
+
+/// How a key file differs from the stock one it was made from, see
+/// `FalconKeyfile::customizations`. Every list is sorted by callback name.
+#[derive(Debug, Clone, Default)]
+pub struct Customizations {
+    /// Callbacks bound to a different chord or combo than in stock, including
+    /// ones only bound or only unbound on one side.
+    pub rebound: Vec<CallbackChange>,
+    /// Callbacks the stock file does not have.
+    pub added: Vec<Callback>,
+    /// Stock callbacks missing from the file.
+    pub missing: Vec<Callback>,
+    /// Joystick bindings the stock file does not have.
+    pub joystick_added: Vec<JoystickBinding>,
+    /// Stock joystick bindings missing from the file.
+    pub joystick_removed: Vec<JoystickBinding>,
+}
+
+impl Customizations {
+    /// Whether the bindings are the stock ones.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of changed bindings.
+    pub fn len(&self) -> usize {
+        self.rebound.len()
+            + self.added.len()
+            + self.missing.len()
+            + self.joystick_added.len()
+            + self.joystick_removed.len()
+    }
+}
+
+/// One change per line in the style of the `diff` command, for pasting into
+/// support threads.
+impl fmt::Display for Customizations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.rebound {
+            writeln!(
+                f,
+                "~ {} {} -> {}",
+                change.name(),
+                binding(&change.old),
+                binding(&change.new)
+            )?;
+        }
+        for callback in &self.added {
+            writeln!(f, "+ {} {}", callback.name, binding(callback))?;
+        }
+        for callback in &self.missing {
+            writeln!(f, "- {} {}", callback.name, binding(callback))?;
+        }
+        for binding in &self.joystick_added {
+            writeln!(f, "+ {} {}", binding.callback, input(binding))?;
+        }
+        for binding in &self.joystick_removed {
+            writeln!(f, "- {} {}", binding.callback, input(binding))?;
+        }
+        Ok(())
+    }
+}
+
+fn input(binding: &JoystickBinding) -> String {
+    input_name(binding.input, binding.shifted)
+}
+
+/// Joystick bindings of `a` that `b` does not have, sorted by callback.
+fn missing_joystick(a: &FalconKeyfile, b: &FalconKeyfile) -> Vec<JoystickBinding> {
+    let mut missing: Vec<_> = a
+        .joystick
+        .iter()
+        .filter(|x| {
+            !b.joystick
+                .iter()
+                .any(|y| x.callback == y.callback && x.input == y.input && x.shifted == y.shifted)
+        })
+        .cloned()
+        .collect();
+    missing.sort_by(|x, y| x.callback.cmp(&y.callback));
+    missing
+}
+
+impl FalconKeyfile {
+    /// The bindings that differ from `stock`. Descriptions, visibility, order
+    /// and comments are not customizations.
+    pub fn customizations(&self, stock: &FalconKeyfile) -> Customizations {
+        let diff = stock.diff(self);
+        Customizations {
+            rebound: diff.rebound,
+            added: diff.added,
+            missing: diff.removed,
+            joystick_added: missing_joystick(self, stock),
+            joystick_removed: missing_joystick(stock, self),
+        }
+    }
+}
+
+/// The stock key files embedded with the `stock-keyfiles` feature. Only
+/// versions with a copy in `test-data` are available.
+#[cfg(feature = "stock-keyfiles")]
+mod stock {
+    use crate::{BmsVersion, FalconKeyfile, ParseOptions, parse_str};
+
+    /// `BMS-Full-T16000M-FCS-US`, the full key file shipped with 4.37.
+    const FULL_4_37: &[u8] = include_bytes!("../test-data/T16000M-FCS-Full.key");
+
+    impl FalconKeyfile {
+        /// The stock full key file of BMS `version`, `None` if none is embedded.
+        pub fn stock(version: BmsVersion) -> Option<FalconKeyfile> {
+            let bytes = match version {
+                BmsVersion::V4_37 => FULL_4_37,
+                _ => return None,
+            };
+            let text = crate::encoding::decode(bytes);
+            let name = format!("BMS {} - Full.key", version);
+            parse_str(name, &text, &ParseOptions::default())
+                .ok()
+                .map(|report| report.keyfile)
+        }
+
+        /// The customizations against the stock key file of `version`, `None`
+        /// if none is embedded.
+        pub fn customizations_from_stock(
+            &self,
+            version: BmsVersion,
+        ) -> Option<crate::Customizations> {
+            Some(self.customizations(&FalconKeyfile::stock(version)?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KeyCombo, ModifierSet};
+
+    #[test]
+    fn report_changes_from_stock() {
+        let stock = FalconKeyfile::from_path("test-data/T16000M-FCS-Full.key").unwrap();
+        assert!(stock.customizations(&stock).is_empty());
+
+        let mut mine = stock.clone();
+        mine.set_binding(
+            "SimPilotToggle",
+            KeyCombo::new(crate::Key::F12, ModifierSet::EMPTY),
+        )
+        .unwrap();
+        mine.retain(|c| c.name != "SimHookToggle");
+        let customizations = mine.customizations(&stock);
+        assert_eq!(customizations.rebound.len(), 1);
+        assert_eq!(customizations.rebound[0].name(), "SimPilotToggle");
+        assert_eq!(customizations.missing[0].name, "SimHookToggle");
+        assert_eq!(
+            customizations.len(),
+            2 + customizations.joystick_removed.len()
+        );
+        assert!(
+            customizations
+                .to_string()
+                .starts_with("~ SimPilotToggle LAlt+C, P -> LAlt+C, F12\n")
+        );
+    }
+
+    #[cfg(feature = "stock-keyfiles")]
+    #[test]
+    fn report_changes_from_embedded_stock() {
+        use crate::BmsVersion;
+
+        let mut mine = FalconKeyfile::from_path("test-data/T16000M-FCS-Full.key").unwrap();
+        assert!(
+            mine.customizations_from_stock(BmsVersion::V4_37)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(mine.customizations_from_stock(BmsVersion::V4_34).is_none());
+
+        mine.set_binding(
+            "SimPilotToggle",
+            KeyCombo::new(crate::Key::F12, ModifierSet::EMPTY),
+        )
+        .unwrap();
+        let customizations = mine.customizations_from_stock(BmsVersion::V4_37).unwrap();
+        assert_eq!(customizations.len(), 1);
+        assert_eq!(customizations.rebound[0].name(), "SimPilotToggle");
+    }
+}
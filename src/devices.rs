@@ -0,0 +1,122 @@
+//! The controller order of BMS' `User/Config/DeviceSorting.txt`, which lists
+//! one DirectInput device per line:
+//!
+//! ```text
+//! {F1B7E2B0-8C59-11EB-8001-444553540000} "Joystick - HOTAS Warthog"
+//! {F1B7E2B0-8C59-11EB-8002-444553540000} "Throttle - HOTAS Warthog"
+//! ```
+//!
+//! DX buttons in a key file are numbered across all devices in this order,
+//! each device taking `BUTTONS_PER_DEVICE` numbers.
+
+use crate::{JoystickBinding, JoystickInput};
+use std::path::Path;
+
+/// How many DX button numbers BMS reserves for every device.
+pub const BUTTONS_PER_DEVICE: u16 = 32;
+
+/// A controller in the device order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Device {
+    /// The instance GUID including braces, if the line has one.
+    pub guid: Option<String>,
+    pub name: String,
+}
+
+/// The devices of `DeviceSorting.txt` by slot.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeviceSorting {
+    pub devices: Vec<Device>,
+}
+
+impl DeviceSorting {
+    /// Reads one device per non-empty line. Lines without a GUID are taken
+    /// as a bare device name.
+    pub fn parse_str(content: &str) -> DeviceSorting {
+        let devices = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with(['#', ';']))
+            .map(|line| {
+                let (guid, rest) = match line.strip_prefix('{').and_then(|l| l.split_once('}')) {
+                    Some((guid, rest)) => (Some(format!("{{{}}}", guid)), rest.trim()),
+                    None => (None, line),
+                };
+                let name = rest
+                    .strip_prefix('"')
+                    .and_then(|r| r.strip_suffix('"'))
+                    .unwrap_or(rest);
+                Device {
+                    guid,
+                    name: String::from(name),
+                }
+            })
+            .collect();
+        DeviceSorting { devices }
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> std::io::Result<DeviceSorting> {
+        let bytes = std::fs::read(path)?;
+        Ok(DeviceSorting::parse_str(&crate::encoding::decode(&bytes)))
+    }
+
+    pub fn device(&self, slot: usize) -> Option<&Device> {
+        self.devices.get(slot)
+    }
+
+    /// The controller a joystick button binding belongs to, `None` for POV
+    /// hats and slots beyond the listed devices.
+    pub fn device_for(&self, binding: &JoystickBinding) -> Option<&Device> {
+        self.device(binding.device_slot()?)
+    }
+}
+
+impl JoystickBinding {
+    /// The slot in the device order the button belongs to, `None` for POV
+    /// hats.
+    pub fn device_slot(&self) -> Option<usize> {
+        match self.input {
+            JoystickInput::Button(button) => Some((button / BUTTONS_PER_DEVICE) as usize),
+            JoystickInput::Pov { .. } => None,
+        }
+    }
+
+    /// The 0-based button number on its own device.
+    pub fn device_button(&self) -> Option<u16> {
+        match self.input {
+            JoystickInput::Button(button) => Some(button % BUTTONS_PER_DEVICE),
+            JoystickInput::Pov { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FalconKeyfile;
+
+    #[test]
+    fn resolve_buttons_to_devices() {
+        let sorting = DeviceSorting::parse_str(
+            "{F1B7E2B0-8C59-11EB-8001-444553540000} \"T.16000M\"\r\n\
+             \r\n\
+             TWCS Throttle\r\n",
+        );
+        assert_eq!(
+            sorting.devices[0].guid.as_deref(),
+            Some("{F1B7E2B0-8C59-11EB-8001-444553540000}")
+        );
+        assert_eq!(sorting.devices[0].name, "T.16000M");
+        assert_eq!(sorting.devices[1].guid, None);
+        assert_eq!(sorting.devices[1].name, "TWCS Throttle");
+
+        let keyfile = FalconKeyfile::from_path("test-data/T16000M-FCS-Full.key").unwrap();
+        let pickle = keyfile
+            .joystick_bindings()
+            .iter()
+            .find(|b| b.callback == "SimPickle")
+            .unwrap();
+        assert_eq!(pickle.device_button(), Some(2));
+        assert_eq!(sorting.device_for(pickle).unwrap().name, "T.16000M");
+    }
+}
@@ -0,0 +1,117 @@
+use std::fmt;
+
+/// How much a diagnostic matters, from parser internals to problems in the
+/// key file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Trace,
+    Debug,
+    Warning,
+}
+
+/// A message from the parser, see `Diagnostics`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub level: Level,
+    /// The 1-based line of the key file, if the message is about one.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Receives what the parser has to say, see `parse_str_with`. The plain
+/// parse functions use `LogDiagnostics`, a `Vec<Diagnostic>` collects them.
+pub trait Diagnostics {
+    fn report(&mut self, diagnostic: Diagnostic);
+
+    /// Whether diagnostics of `level` are wanted at all, so that messages
+    /// nobody reads are not formatted.
+    fn enabled(&self, _level: Level) -> bool {
+        true
+    }
+}
+
+/// Forwards diagnostics to the `log` crate.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogDiagnostics;
+
+fn log_level(level: Level) -> log::Level {
+    match level {
+        Level::Trace => log::Level::Trace,
+        Level::Debug => log::Level::Debug,
+        Level::Warning => log::Level::Warn,
+    }
+}
+
+impl Diagnostics for LogDiagnostics {
+    fn report(&mut self, diagnostic: Diagnostic) {
+        log::log!(log_level(diagnostic.level), "{}", diagnostic.message);
+    }
+
+    fn enabled(&self, level: Level) -> bool {
+        log::log_enabled!(log_level(level))
+    }
+}
+
+impl Diagnostics for Vec<Diagnostic> {
+    fn report(&mut self, diagnostic: Diagnostic) {
+        self.push(diagnostic);
+    }
+}
+
+/// Reports a diagnostic, formatting the message only if it is wanted.
+pub(crate) fn report(
+    diagnostics: &mut dyn Diagnostics,
+    level: Level,
+    line: Option<usize>,
+    message: impl FnOnce() -> String,
+) {
+    if diagnostics.enabled(level) {
+        diagnostics.report(Diagnostic {
+            level,
+            line,
+            message: message(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParseOptions, parse_str_with};
+
+    #[test]
+    fn collect_diagnostics() {
+        let content = std::fs::read_to_string("test-data/malformed.key").unwrap();
+        let mut diagnostics: Vec<Diagnostic> = vec![];
+        let report = parse_str_with(
+            String::from("malformed.key"),
+            &content,
+            &ParseOptions::default(),
+            &mut diagnostics,
+        )
+        .unwrap();
+
+        let warnings: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.level == Level::Warning)
+            .map(|d| (d.line, d.message.clone()))
+            .collect();
+        let expected: Vec<_> = report
+            .warnings
+            .iter()
+            .map(|w| (Some(w.line()), w.to_string()))
+            .collect();
+        assert_eq!(warnings, expected);
+        assert!(diagnostics.iter().any(|d| d.level == Level::Trace));
+        assert_eq!(
+            diagnostics.last().unwrap().message,
+            "Parsed key file with 3 callbacks."
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use crate::{Callback, FalconKeyfile};
+use std::fmt;
+
+/// The differences between two versions of a key file, each list sorted by
+/// callback name.
+#[derive(Debug, Clone, Default)]
+pub struct KeyfileDiff {
+    /// Callbacks only present in the newer file.
+    pub added: Vec<Callback>,
+    /// Callbacks only present in the older file.
+    pub removed: Vec<Callback>,
+    /// Callbacks bound to a different chord or combo sequence.
+    pub rebound: Vec<CallbackChange>,
+    /// Callbacks with the same binding but a changed description or visibility.
+    pub modified: Vec<CallbackChange>,
+}
+
+/// A callback present in both files, before and after the change.
+#[derive(Debug, Clone)]
+pub struct CallbackChange {
+    pub old: Callback,
+    pub new: Callback,
+}
+
+impl CallbackChange {
+    pub fn name(&self) -> &str {
+        &self.new.name
+    }
+}
+
+impl KeyfileDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.rebound.is_empty()
+            && self.modified.is_empty()
+    }
+
+    /// Renders the diff like `diff -u` for posting in chats and forums, with
+    /// `old` and `new` naming the files in the header. Every callback gets a
+    /// `-` line with its old and a `+` line with its new state, sorted by
+    /// name. Description changes are shown with the description and the
+    /// visibility.
+    pub fn to_unified(&self, old: &str, new: &str) -> String {
+        let mut entries: Vec<(&str, String)> = vec![];
+        for callback in &self.removed {
+            entries.push((&callback.name, format!("-{}\n", binding_line(callback))));
+        }
+        for callback in &self.added {
+            entries.push((&callback.name, format!("+{}\n", binding_line(callback))));
+        }
+        for change in &self.rebound {
+            let lines = format!(
+                "-{}\n+{}\n",
+                binding_line(&change.old),
+                binding_line(&change.new)
+            );
+            entries.push((change.name(), lines));
+        }
+        for change in &self.modified {
+            let lines = format!(
+                "-{}\n+{}\n",
+                details_line(&change.old),
+                details_line(&change.new)
+            );
+            entries.push((change.name(), lines));
+        }
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut text = format!("--- {}\n+++ {}\n", old, new);
+        for (_, lines) in entries {
+            text.push_str(&lines);
+        }
+        text
+    }
+}
+
+/// One change per line as printed by the `diff` command, such as
+/// `~ AFBrakesToggle B -> LCtrl+B`.
+impl fmt::Display for KeyfileDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for callback in &self.added {
+            writeln!(f, "+ {} {}", callback.name, binding(callback))?;
+        }
+        for callback in &self.removed {
+            writeln!(f, "- {} {}", callback.name, binding(callback))?;
+        }
+        for change in &self.rebound {
+            writeln!(
+                f,
+                "~ {} {} -> {}",
+                change.name(),
+                binding(&change.old),
+                binding(&change.new)
+            )?;
+        }
+        for change in &self.modified {
+            writeln!(f, "~ {} description or visibility changed", change.name())?;
+        }
+        Ok(())
+    }
+}
+
+/// The chord and the combo before it as written for people, such as
+/// `LAlt+C, P`.
+pub(crate) fn binding(callback: &Callback) -> String {
+    callback
+        .binding()
+        .map_or_else(|| String::from("(unbound)"), |b| b.to_string())
+}
+
+fn binding_line(callback: &Callback) -> String {
+    format!("{} {}", callback.name, binding(callback))
+}
+
+fn details_line(callback: &Callback) -> String {
+    format!(
+        "{} \"{}\" {:?}",
+        callback.name,
+        callback.description.as_deref().unwrap_or_default(),
+        callback.visibility
+    )
+}
+
+impl FalconKeyfile {
+    /// Compares this key file to `other`, treating `other` as the newer version.
+    pub fn diff(&self, other: &FalconKeyfile) -> KeyfileDiff {
+        let mut diff = KeyfileDiff::default();
+
+        for (name, new) in &other.callbacks {
+            let Some(old) = self.callbacks.get(name) else {
+                diff.added.push(new.clone());
+                continue;
+            };
+            let change = || CallbackChange {
+                old: old.clone(),
+                new: new.clone(),
+            };
+            if !old.same_binding(new) {
+                diff.rebound.push(change());
+            } else if old.description != new.description || old.visibility != new.visibility {
+                diff.modified.push(change());
+            }
+        }
+        diff.removed = self
+            .callbacks
+            .values()
+            .filter(|c| !other.callbacks.contains_key(&c.name))
+            .cloned()
+            .collect();
+
+        diff.added.sort_by(|a, b| a.name.cmp(&b.name));
+        diff.removed.sort_by(|a, b| a.name.cmp(&b.name));
+        diff.rebound.sort_by(|a, b| a.name().cmp(b.name()));
+        diff.modified.sort_by(|a, b| a.name().cmp(b.name()));
+        diff
+    }
+}
+
+impl Callback {
+    /// Whether both callbacks are triggered by the same chord and combo sequence.
+    pub fn same_binding(&self, other: &Callback) -> bool {
+        (self.is_bound() == other.is_bound())
+            && (!self.is_bound()
+                || (self.primary_combo() == other.primary_combo()
+                    && self.secondary_combo() == other.secondary_combo()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ParseOptions, Visibility, parse_str};
+
+    #[test]
+    fn diff_two_versions() {
+        let old = "# header
+AFBrakesToggle -1 0 0x30 0 0 0 1 \"Brakes\"
+AFGearToggle -1 0 0x22 0 0 0 1 \"Gear\"
+SimHookToggle -1 0 0x23 0 0 0 1 \"Hook\"
+AFFlapsToggle -1 0 0XFFFFFFFF 0 0 0 1 \"Flaps\"
+";
+        let new = "# header
+AFBrakesToggle -1 0 0x30 2 0 0 1 \"Brakes\"
+AFGearToggle -1 0 0x22 0 0 0 -0 \"Landing Gear\"
+SimPilotToggle -1 0 0x19 0 0x2E 4 1 \"Autopilot\"
+AFFlapsToggle -1 0 0 0 0 0 1 \"Flaps\"
+";
+        let parse = |content| {
+            parse_str(String::from("inline"), content, &ParseOptions::default())
+                .unwrap()
+                .keyfile
+        };
+        let diff = parse(old).diff(&parse(new));
+
+        let names = |callbacks: &[crate::Callback]| -> Vec<String> {
+            callbacks.iter().map(|c| c.name.clone()).collect()
+        };
+        assert_eq!(names(&diff.added), vec!["SimPilotToggle"]);
+        assert_eq!(names(&diff.removed), vec!["SimHookToggle"]);
+        assert_eq!(diff.rebound.len(), 1);
+        assert_eq!(diff.rebound[0].name(), "AFBrakesToggle");
+        assert_eq!(diff.modified.len(), 1);
+        assert_eq!(diff.modified[0].new.visibility, Visibility::Locked);
+        assert_eq!(
+            diff.modified[0].new.description.as_deref(),
+            Some("Landing Gear")
+        );
+
+        assert!(parse(old).diff(&parse(old)).is_empty());
+    }
+
+    #[test]
+    fn render_unified_text() {
+        let parse = |content| {
+            parse_str(String::from("inline"), content, &ParseOptions::default())
+                .unwrap()
+                .keyfile
+        };
+        let old = parse(
+            "# old
+AFBrakesToggle -1 0 0x30 0 0 0 1 \"Brakes\"
+AFGearToggle -1 0 0x22 0 0 0 1 \"Gear\"
+SimHookToggle -1 0 0x23 0 0 0 1 \"Hook\"
+",
+        );
+        let new = parse(
+            "# new
+AFBrakesToggle -1 0 0x30 2 0 0 1 \"Brakes\"
+AFGearToggle -1 0 0x22 0 0 0 -0 \"Landing Gear\"
+SimPilotToggle -1 0 0x19 0 0x2E 4 1 \"Autopilot\"
+",
+        );
+        assert_eq!(
+            old.diff(&new).to_unified("old.key", "new.key"),
+            "--- old.key
++++ new.key
+-AFBrakesToggle B
++AFBrakesToggle LCtrl+B
+-AFGearToggle \"Gear\" Visible
++AFGearToggle \"Landing Gear\" Locked
+-SimHookToggle H
++SimPilotToggle LAlt+C, P
+"
+        );
+        assert_eq!(
+            old.diff(&new).to_string(),
+            "+ SimPilotToggle LAlt+C, P
+- SimHookToggle H
+~ AFBrakesToggle B -> LCtrl+B
+~ AFGearToggle description or visibility changed
+"
+        );
+    }
+}
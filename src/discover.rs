@@ -0,0 +1,200 @@
+//! Finding BMS installations and the key file the pilot currently uses.
+//!
+//! Installations are listed in the registry, so `installations` and
+//! `discover` are only available on Windows. An installation at a known path,
+//! e.g. inside a Wine prefix, can be inspected on any platform.
+//!
+//! BMS keeps the selected key file in the binary pilot file
+//! `User/Config/<callsign>.pop`. Its layout is not documented, so the pilot
+//! file is searched for the name of a key file that exists next to it.
+
+use crate::{BmsConfig, DeviceSorting, FalconKeyfile, PathError, Workspace, WorkspaceScan};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// A BMS installation, e.g. `Falcon BMS 4.37` in `C:\Falcon BMS 4.37`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BmsInstallation {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+#[derive(Debug)]
+pub enum DiscoverError {
+    /// No installation was found in the registry.
+    NotInstalled,
+    /// No pilot file names a key file of the installation.
+    NoActiveKeyfile,
+    /// The active key file could not be parsed.
+    Path(PathError),
+}
+
+impl fmt::Display for DiscoverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiscoverError::NotInstalled => write!(f, "no Falcon BMS installation found"),
+            DiscoverError::NoActiveKeyfile => write!(f, "no pilot file selects a key file"),
+            DiscoverError::Path(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for DiscoverError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DiscoverError::Path(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl BmsInstallation {
+    pub fn new(name: impl Into<String>, path: impl Into<PathBuf>) -> BmsInstallation {
+        BmsInstallation {
+            name: name.into(),
+            path: path.into(),
+        }
+    }
+
+    /// The directory holding the key and pilot files.
+    pub fn config_dir(&self) -> PathBuf {
+        self.path.join("User").join("Config")
+    }
+
+    /// `Falcon BMS.cfg` with the overrides of `Falcon BMS User.cfg`, missing
+    /// files count as empty.
+    pub fn config(&self) -> BmsConfig {
+        let read =
+            |name: &str| BmsConfig::from_path(self.config_dir().join(name)).unwrap_or_default();
+        read("Falcon BMS.cfg").with_overrides(&read("Falcon BMS User.cfg"))
+    }
+
+    /// The controller order of `DeviceSorting.txt`.
+    pub fn device_sorting(&self) -> std::io::Result<DeviceSorting> {
+        DeviceSorting::from_path(self.config_dir().join("DeviceSorting.txt"))
+    }
+
+    /// Every key file of the installation.
+    pub fn keyfiles(&self) -> std::io::Result<WorkspaceScan> {
+        Workspace::scan(self.config_dir())
+    }
+
+    /// The name of the key file selected by the most recently saved pilot,
+    /// without the `.key` extension.
+    pub fn active_keyfile_name(&self) -> Option<String> {
+        let files: Vec<_> = std::fs::read_dir(self.config_dir())
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .collect();
+        let with_extension = |extension: &str| {
+            files
+                .iter()
+                .filter(|p| {
+                    p.extension()
+                        .is_some_and(|e| e.eq_ignore_ascii_case(extension))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let keyfiles: Vec<String> = with_extension("key")
+            .into_iter()
+            .filter_map(|p| Some(p.file_stem()?.to_string_lossy().into_owned()))
+            .collect();
+        let mut pilots = with_extension("pop");
+        pilots.sort_by_key(|p| std::cmp::Reverse(modified(p)));
+
+        pilots.into_iter().find_map(|pilot| {
+            let bytes = std::fs::read(pilot).ok()?;
+            strings(&bytes).into_iter().find_map(|text| {
+                let stem = text.strip_suffix(".key").unwrap_or(&text);
+                keyfiles
+                    .iter()
+                    .find(|k| k.eq_ignore_ascii_case(stem))
+                    .cloned()
+            })
+        })
+    }
+
+    /// Parses the key file selected by the most recently saved pilot.
+    pub fn active_keyfile(&self) -> Result<FalconKeyfile, DiscoverError> {
+        let name = self
+            .active_keyfile_name()
+            .ok_or(DiscoverError::NoActiveKeyfile)?;
+        FalconKeyfile::from_path(self.config_dir().join(format!("{}.key", name)))
+            .map_err(DiscoverError::Path)
+    }
+}
+
+fn modified(path: &Path) -> Option<std::time::SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// The runs of printable ASCII in `bytes` of at least three characters.
+fn strings(bytes: &[u8]) -> Vec<String> {
+    bytes
+        .split(|b| !(0x20..0x7F).contains(b))
+        .filter(|run| run.len() >= 3)
+        .map(|run| String::from_utf8_lossy(run).trim().to_string())
+        .collect()
+}
+
+/// The installations listed in the registry, newest version first.
+#[cfg(windows)]
+pub fn installations() -> Vec<BmsInstallation> {
+    use winreg::RegKey;
+    use winreg::enums::HKEY_LOCAL_MACHINE;
+
+    let Ok(benchmark_sims) =
+        RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey(r"SOFTWARE\WOW6432Node\Benchmark Sims")
+    else {
+        return vec![];
+    };
+    let mut installations: Vec<_> = benchmark_sims
+        .enum_keys()
+        .filter_map(Result::ok)
+        .filter_map(|name| {
+            let key = benchmark_sims.open_subkey(&name).ok()?;
+            let path: String = key.get_value("baseDir").ok()?;
+            Some(BmsInstallation::new(name, path))
+        })
+        .filter(|installation| installation.path.is_dir())
+        .collect();
+    installations.sort_by(|a, b| b.name.cmp(&a.name));
+    installations
+}
+
+/// Parses the active key file of the newest installation.
+#[cfg(windows)]
+pub fn discover() -> Result<FalconKeyfile, DiscoverError> {
+    installations()
+        .first()
+        .ok_or(DiscoverError::NotInstalled)?
+        .active_keyfile()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_keyfile_selected_by_pilot() {
+        let root = std::env::temp_dir().join(format!("falcon-discover-{}", std::process::id()));
+        let installation = BmsInstallation::new("Falcon BMS 4.37", &root);
+        let config = installation.config_dir();
+        std::fs::create_dir_all(&config).unwrap();
+        std::fs::copy("test-data/basic.key", config.join("My Keys.key")).unwrap();
+        std::fs::copy("test-data/basic.key", config.join("BMS - Full.key")).unwrap();
+        let mut pilot = vec![0u8, 7, 1];
+        pilot.extend(b"Viper\0\0\x02My Keys\0\x10\x11");
+        std::fs::write(config.join("Viper.pop"), pilot).unwrap();
+
+        assert_eq!(
+            installation.active_keyfile_name().as_deref(),
+            Some("My Keys")
+        );
+        let keyfile = installation.active_keyfile().unwrap();
+        assert!(keyfile.callback("SimPilotToggle").is_some());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
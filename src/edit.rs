@@ -0,0 +1,328 @@
+use crate::{Callback, Conflict, FalconKeyfile, Key, KeyCombo, ModifierSet};
+use std::fmt;
+
+/// Why an edit of a keyfile was rejected, the keyfile is left unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditError {
+    /// The keyfile has no callback of that name.
+    UnknownCallback(String),
+    /// The key has no scan code BMS understands.
+    InvalidKey(Key),
+    /// Renaming would overwrite the callback of that name.
+    NameTaken(String),
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditError::UnknownCallback(name) => write!(f, "unknown callback {}", name),
+            EditError::InvalidKey(key) => write!(f, "{:?} cannot be bound", key),
+            EditError::NameTaken(name) => write!(f, "callback {} already exists", name),
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+/// Whether `FalconKeyfile::remap` changes the keyfile or only reports what
+/// it would do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemapMode {
+    Apply,
+    DryRun,
+}
+
+/// The rebinds of a remap and the conflicts they lead to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemapReport {
+    /// Names of the rebound callbacks with their old and new chord, sorted by name.
+    pub rebound: Vec<(String, KeyCombo, KeyCombo)>,
+    /// Conflicts after the remap that involve a rebound callback.
+    pub conflicts: Vec<Conflict>,
+}
+
+impl FalconKeyfile {
+    /// Binds the callback `name` to `combo`, keeping the combo pressed before
+    /// it for two-stage bindings.
+    pub fn set_binding(&mut self, name: &str, combo: KeyCombo) -> Result<(), EditError> {
+        if matches!(combo.key, Key::Unknown | Key::Other(_)) {
+            return Err(EditError::InvalidKey(combo.key));
+        }
+        let callback = self
+            .callbacks
+            .get_mut(name)
+            .ok_or_else(|| EditError::UnknownCallback(String::from(name)))?;
+        callback.key_code = combo.key.scancode();
+        callback.readable_key_code = combo.key;
+        callback.modifiers = combo.modifiers;
+        self.reindex();
+        Ok(())
+    }
+
+    /// Unbinds the callback `name` from the keyboard, two-stage binding included.
+    pub fn remove_binding(&mut self, name: &str) -> Result<(), EditError> {
+        let callback = self
+            .callbacks
+            .get_mut(name)
+            .ok_or_else(|| EditError::UnknownCallback(String::from(name)))?;
+        callback.replace_binding(None);
+        self.reindex();
+        Ok(())
+    }
+
+    /// Exchanges the keys, modifiers and combos of the callbacks `a` and `b`.
+    pub fn swap_bindings(&mut self, a: &str, b: &str) -> Result<(), EditError> {
+        for name in [a, b] {
+            if !self.callbacks.contains_key(name) {
+                return Err(EditError::UnknownCallback(String::from(name)));
+            }
+        }
+        if a == b {
+            return Ok(());
+        }
+        let first = self.callbacks[a].clone();
+        let second = self.callbacks[b].clone();
+        copy_binding(self.callbacks.get_mut(a).unwrap(), &second);
+        copy_binding(self.callbacks.get_mut(b).unwrap(), &first);
+        self.reindex();
+        Ok(())
+    }
+
+    /// Rebinds every bound callback whose chord `f` maps to a new one. The
+    /// combo pressed before the chord of two-stage bindings is kept. Nothing
+    /// is changed if `f` returns a key that cannot be bound.
+    pub fn remap<F>(&mut self, mode: RemapMode, mut f: F) -> Result<RemapReport, EditError>
+    where
+        F: FnMut(&KeyCombo) -> Option<KeyCombo>,
+    {
+        let mut rebound = vec![];
+        for callback in self.callbacks.values().filter(|c| c.is_bound()) {
+            let old = callback.primary_combo();
+            if let Some(new) = f(&old).filter(|new| *new != old) {
+                if matches!(new.key, Key::Unknown | Key::Other(_)) {
+                    return Err(EditError::InvalidKey(new.key));
+                }
+                rebound.push((callback.name.clone(), old, new));
+            }
+        }
+        rebound.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut remapped = self.clone();
+        for (name, _, new) in &rebound {
+            remapped.set_binding(name, new.clone())?;
+        }
+        let conflicts = remapped
+            .conflicts()
+            .into_iter()
+            .filter(|c| {
+                c.callbacks
+                    .iter()
+                    .any(|name| rebound.iter().any(|(rebound, _, _)| rebound == name))
+            })
+            .collect();
+
+        if mode == RemapMode::Apply {
+            *self = remapped;
+        }
+        Ok(RemapReport { rebound, conflicts })
+    }
+
+    /// Moves every chord on the `from` modifier layer to the `to` layer, such
+    /// as everything on `LAlt` to `LCtrl+LAlt`.
+    pub fn move_layer(
+        &mut self,
+        from: ModifierSet,
+        to: ModifierSet,
+        mode: RemapMode,
+    ) -> Result<RemapReport, EditError> {
+        self.remap(mode, |combo| {
+            (combo.modifiers == from).then(|| KeyCombo::new(combo.key.clone(), to))
+        })
+    }
+
+    /// Renames the callback `old` to `new`, its joystick bindings included.
+    pub fn rename_callback(&mut self, old: &str, new: &str) -> Result<(), EditError> {
+        if !self.callbacks.contains_key(old) {
+            return Err(EditError::UnknownCallback(String::from(old)));
+        }
+        if self.callbacks.contains_key(new) {
+            return Err(EditError::NameTaken(String::from(new)));
+        }
+        let mut callback = self.callbacks.remove(old).unwrap();
+        callback.name = String::from(new);
+        self.callbacks.insert(String::from(new), callback);
+        for binding in self.joystick.iter_mut().filter(|b| b.callback == old) {
+            binding.callback = String::from(new);
+        }
+        self.reindex();
+        Ok(())
+    }
+
+    /// Keeps only the callbacks `keep` returns true for, with their joystick
+    /// bindings. Sections left without callbacks are dropped.
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&Callback) -> bool,
+    {
+        self.callbacks.retain(|_, callback| keep(callback));
+        let callbacks = &self.callbacks;
+        self.joystick
+            .retain(|b| callbacks.contains_key(&b.callback));
+        let used = |section: &String| {
+            self.callbacks
+                .values()
+                .filter_map(|c| c.section.as_ref())
+                .chain(self.joystick.iter().filter_map(|b| b.section.as_ref()))
+                .any(|s| s == section)
+        };
+        let sections = self.sections.iter().filter(|s| used(s)).cloned().collect();
+        self.sections = sections;
+        self.reindex();
+    }
+
+    /// A copy with only the callbacks `keep` returns true for, see `retain`.
+    pub fn subset<F>(&self, keep: F) -> FalconKeyfile
+    where
+        F: FnMut(&Callback) -> bool,
+    {
+        let mut subset = self.clone();
+        subset.retain(keep);
+        subset
+    }
+}
+
+/// Copies the keys, modifiers and combo of `from` to `to`.
+pub(crate) fn copy_binding(to: &mut Callback, from: &Callback) {
+    to.key_code = from.key_code;
+    to.readable_key_code = from.readable_key_code.clone();
+    to.modifiers = from.modifiers;
+    to.combo_key_code = from.combo_key_code;
+    to.readable_combo_key_code = from.readable_combo_key_code.clone();
+    to.combo_modifiers = from.combo_modifiers;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CockpitSystem, FreeComboOptions, Modifier};
+
+    #[test]
+    fn edit_bindings() {
+        let mut keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let combo = keyfile.free_combos(&FreeComboOptions::default())[0].clone();
+
+        keyfile
+            .set_binding("SimCursorUpLeft", combo.clone())
+            .unwrap();
+        assert_eq!(
+            keyfile.callbacks_for_combo(&combo)[0].name,
+            "SimCursorUpLeft"
+        );
+
+        keyfile.remove_binding("SimCursorUpLeft").unwrap();
+        assert!(keyfile.callbacks_for_combo(&combo).is_empty());
+        assert!(!keyfile.callback("SimCursorUpLeft").unwrap().is_bound());
+
+        keyfile
+            .rename_callback("AFElevatorTrimUp", "AFTrimUp")
+            .unwrap();
+        assert!(keyfile.callback("AFElevatorTrimUp").is_none());
+        let combo: KeyCombo = "LCtrl+UpArrow".parse().unwrap();
+        assert_eq!(keyfile.callbacks_for_combo(&combo)[0].name, "AFTrimUp");
+
+        assert_eq!(
+            keyfile.set_binding("SimNoSuchCallback", combo),
+            Err(EditError::UnknownCallback(String::from(
+                "SimNoSuchCallback"
+            )))
+        );
+        assert_eq!(
+            keyfile.set_binding(
+                "AFTrimUp",
+                KeyCombo::new(Key::Other(0x54), ModifierSet::EMPTY)
+            ),
+            Err(EditError::InvalidKey(Key::Other(0x54)))
+        );
+        assert_eq!(
+            keyfile.rename_callback("AFTrimUp", "AFElevatorUp"),
+            Err(EditError::NameTaken(String::from("AFElevatorUp")))
+        );
+    }
+
+    #[test]
+    fn swap_two_bindings() {
+        let mut keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let up = keyfile.callback("AFElevatorTrimUp").unwrap();
+        let down = keyfile.callback("AFElevatorTrimDown").unwrap();
+
+        keyfile
+            .swap_bindings("AFElevatorTrimUp", "AFElevatorTrimDown")
+            .unwrap();
+        assert!(
+            keyfile
+                .callback("AFElevatorTrimUp")
+                .unwrap()
+                .same_binding(&down)
+        );
+        assert_eq!(
+            keyfile.callbacks_for_combo(&up.primary_combo())[0].name,
+            "AFElevatorTrimDown"
+        );
+        assert_eq!(
+            keyfile.swap_bindings("AFElevatorTrimUp", "SimNoSuchCallback"),
+            Err(EditError::UnknownCallback(String::from(
+                "SimNoSuchCallback"
+            )))
+        );
+    }
+
+    #[test]
+    fn keep_a_subset() {
+        let keyfile = FalconKeyfile::from_path("test-data/T16000M-FCS-Full.key").unwrap();
+        let hotas = keyfile.subset(|c| c.cockpit_system() == CockpitSystem::Hotas);
+        assert!(hotas.contains("SimPickle"));
+        assert!(!hotas.contains("SimICPCom1"));
+        assert!(hotas.len() < keyfile.len());
+        assert!(
+            hotas
+                .joystick_bindings()
+                .iter()
+                .all(|b| hotas.contains(&b.callback))
+        );
+        assert!(hotas.sections().len() < keyfile.sections().len());
+        assert!(hotas.iter().all(|c| {
+            c.section
+                .as_ref()
+                .is_none_or(|s| hotas.sections().contains(s))
+        }));
+
+        let mut bound = keyfile.clone();
+        bound.retain(Callback::is_bound);
+        assert!(bound.unbound_callbacks().is_empty());
+        assert_eq!(bound.len(), keyfile.iter().filter(|c| c.is_bound()).count());
+    }
+
+    #[test]
+    fn move_modifier_layer() {
+        let mut keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let alt = ModifierSet::from(Modifier::LALT);
+        let ctrl_alt = alt | Modifier::LCONTROL;
+        let on_alt = keyfile
+            .iter()
+            .filter(|c| c.is_bound() && c.modifiers == alt)
+            .count();
+
+        let report = keyfile
+            .move_layer(alt, ctrl_alt, RemapMode::DryRun)
+            .unwrap();
+        assert_eq!(report.rebound.len(), on_alt);
+        assert!(keyfile.iter().any(|c| c.is_bound() && c.modifiers == alt));
+
+        let applied = keyfile.move_layer(alt, ctrl_alt, RemapMode::Apply).unwrap();
+        assert_eq!(applied, report);
+        assert!(!keyfile.iter().any(|c| c.is_bound() && c.modifiers == alt));
+        for conflict in &report.conflicts {
+            assert_eq!(conflict.combo.modifiers, ctrl_alt);
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{Callback, Edit, EditError, FalconKeyfile, KeyCombo};
+use std::collections::VecDeque;
+
+/// How many edits `KeyfileEditor::new` keeps for undoing.
+const DEFAULT_HISTORY: usize = 100;
+
+/// An applied edit together with what it replaced.
+#[derive(Debug, Clone)]
+struct Change {
+    edit: Edit,
+    /// The callbacks the edit changed, as they were before.
+    before: Vec<Callback>,
+}
+
+/// A keyfile that records its edits so they can be undone and redone.
+#[derive(Debug, Clone)]
+pub struct KeyfileEditor {
+    keyfile: FalconKeyfile,
+    undo: VecDeque<Change>,
+    redo: Vec<Edit>,
+    history: usize,
+}
+
+impl KeyfileEditor {
+    pub fn new(keyfile: FalconKeyfile) -> KeyfileEditor {
+        KeyfileEditor::with_history(keyfile, DEFAULT_HISTORY)
+    }
+
+    /// An editor that forgets the oldest edits beyond `history`.
+    pub fn with_history(keyfile: FalconKeyfile, history: usize) -> KeyfileEditor {
+        KeyfileEditor {
+            keyfile,
+            undo: VecDeque::new(),
+            redo: vec![],
+            history,
+        }
+    }
+
+    pub fn keyfile(&self) -> &FalconKeyfile {
+        &self.keyfile
+    }
+
+    pub fn into_keyfile(self) -> FalconKeyfile {
+        self.keyfile
+    }
+
+    /// Applies `edit` and records it, discarding the edits that could be redone.
+    /// A failed edit changes nothing and keeps them.
+    pub fn apply(&mut self, edit: Edit) -> Result<(), EditError> {
+        self.record(edit)?;
+        self.redo.clear();
+        Ok(())
+    }
+
+    pub fn set_binding(&mut self, name: &str, combo: KeyCombo) -> Result<(), EditError> {
+        self.apply(Edit::SetBinding {
+            name: String::from(name),
+            combo,
+        })
+    }
+
+    pub fn remove_binding(&mut self, name: &str) -> Result<(), EditError> {
+        self.apply(Edit::RemoveBinding {
+            name: String::from(name),
+        })
+    }
+
+    pub fn rename_callback(&mut self, old: &str, new: &str) -> Result<(), EditError> {
+        self.apply(Edit::RenameCallback {
+            old: String::from(old),
+            new: String::from(new),
+        })
+    }
+
+    pub fn swap_bindings(&mut self, a: &str, b: &str) -> Result<(), EditError> {
+        self.apply(Edit::SwapBindings {
+            a: String::from(a),
+            b: String::from(b),
+        })
+    }
+
+    /// Reverts the last edit, returning it or `None` if there is nothing to undo.
+    pub fn undo(&mut self) -> Option<&Edit> {
+        let change = self.undo.pop_back()?;
+        match &change.edit {
+            Edit::RenameCallback { old, new } => {
+                // the name is free again, since the rename took it
+                self.keyfile.rename_callback(new, old).unwrap();
+            }
+            _ => {
+                for callback in change.before {
+                    self.keyfile
+                        .callbacks
+                        .insert(callback.name.clone(), callback);
+                }
+                self.keyfile.reindex();
+            }
+        }
+        self.redo.push(change.edit);
+        self.redo.last()
+    }
+
+    /// Applies the last undone edit again, returning it or `None` if there is
+    /// nothing to redo.
+    pub fn redo(&mut self) -> Option<&Edit> {
+        let edit = self.redo.pop()?;
+        // the keyfile is back in the state the edit succeeded in before
+        self.record(edit).unwrap();
+        self.undo.back().map(|change| &change.edit)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    fn record(&mut self, edit: Edit) -> Result<(), EditError> {
+        let touched: Vec<&str> = match &edit {
+            Edit::SetBinding { name, .. } | Edit::RemoveBinding { name } => vec![name],
+            Edit::SwapBindings { a, b } => vec![a, b],
+            Edit::RenameCallback { .. } => vec![],
+        };
+        let before = touched
+            .iter()
+            .filter_map(|name| self.keyfile.callbacks.get(*name).cloned())
+            .collect();
+
+        edit.apply(&mut self.keyfile)?;
+        self.undo.push_back(Change { edit, before });
+        if self.undo.len() > self.history {
+            self.undo.pop_front();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_and_redo_edits() {
+        let keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let mut editor = KeyfileEditor::with_history(keyfile, 2);
+
+        editor.remove_binding("AFElevatorTrimUp").unwrap();
+        editor
+            .rename_callback("AFElevatorTrimUp", "AFTrimUp")
+            .unwrap();
+        editor
+            .swap_bindings("AFTrimUp", "AFElevatorTrimDown")
+            .unwrap();
+        assert!(editor.remove_binding("SimNoSuchCallback").is_err());
+
+        assert!(matches!(editor.undo(), Some(Edit::SwapBindings { .. })));
+        assert!(matches!(editor.undo(), Some(Edit::RenameCallback { .. })));
+        // the removal fell out of the history
+        assert!(editor.undo().is_none());
+        assert!(
+            !editor
+                .keyfile()
+                .callback("AFElevatorTrimUp")
+                .unwrap()
+                .is_bound()
+        );
+
+        // a failed edit keeps what can be redone
+        assert!(editor.remove_binding("SimNoSuchCallback").is_err());
+        assert!(editor.can_redo());
+        editor.redo().unwrap();
+        editor.redo().unwrap();
+        assert!(!editor.can_redo());
+        let keyfile = editor.keyfile();
+        assert!(!keyfile.callback("AFElevatorTrimDown").unwrap().is_bound());
+        assert!(keyfile.callback("AFTrimUp").unwrap().is_bound());
+    }
+}
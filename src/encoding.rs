@@ -0,0 +1,167 @@
+use log::*;
+use std::borrow::Cow;
+
+/// Text encodings key files are found in. BMS itself writes plain ASCII, but
+/// files edited with Notepad often end up as UTF-16 or Windows-1252.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Windows1252,
+}
+
+/// Decodes the raw bytes of a key file, dropping any byte order mark.
+pub(crate) fn decode(bytes: &[u8]) -> Cow<'_, str> {
+    let (encoding, content) = sniff(bytes);
+    debug!("Decoding key file as {:?}", encoding);
+
+    match encoding {
+        Encoding::Utf8 => match std::str::from_utf8(content) {
+            Ok(text) => Cow::Borrowed(text),
+            Err(_) => Cow::Owned(decode_windows_1252(content)),
+        },
+        Encoding::Utf16Le => Cow::Owned(decode_utf16(content, u16::from_le_bytes)),
+        Encoding::Utf16Be => Cow::Owned(decode_utf16(content, u16::from_be_bytes)),
+        Encoding::Windows1252 => Cow::Owned(decode_windows_1252(content)),
+    }
+}
+
+impl Encoding {
+    /// The bytes of a code unit, lines are always read in whole units.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn unit(self) -> usize {
+        match self {
+            Encoding::Utf16Le | Encoding::Utf16Be => 2,
+            Encoding::Utf8 | Encoding::Windows1252 => 1,
+        }
+    }
+
+    /// Whether `line`, read in whole code units, ends with a newline.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn ends_line(self, line: &[u8]) -> bool {
+        match self {
+            Encoding::Utf16Le => line.len().is_multiple_of(2) && line.ends_with(&[b'\n', 0]),
+            Encoding::Utf16Be => line.len().is_multiple_of(2) && line.ends_with(&[0, b'\n']),
+            Encoding::Utf8 | Encoding::Windows1252 => line.ends_with(b"\n"),
+        }
+    }
+
+    /// Decodes one line without its line break. UTF-8 lines that turn out to
+    /// be invalid are taken as Windows-1252.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn decode_line(self, line: &[u8]) -> String {
+        let text = match self {
+            Encoding::Utf8 => match std::str::from_utf8(line) {
+                Ok(text) => String::from(text),
+                Err(_) => decode_windows_1252(line),
+            },
+            Encoding::Utf16Le => decode_utf16(line, u16::from_le_bytes),
+            Encoding::Utf16Be => decode_utf16(line, u16::from_be_bytes),
+            Encoding::Windows1252 => decode_windows_1252(line),
+        };
+        String::from(text.trim_end_matches(['\n', '\r']))
+    }
+}
+
+/// The encoding of a file that starts with `head`, for decoding it line by
+/// line as it is read, and the length of its byte order mark.
+#[cfg(feature = "tokio")]
+pub(crate) fn sniff_start(head: &[u8]) -> (Encoding, usize) {
+    let (encoding, rest) = sniff(head);
+    let encoding = match (encoding, std::str::from_utf8(head)) {
+        // cut off in the middle of a character
+        (Encoding::Windows1252, Err(error)) if error.error_len().is_none() => Encoding::Utf8,
+        _ => encoding,
+    };
+    debug!("Decoding key file as {:?}", encoding);
+    (encoding, head.len() - rest.len())
+}
+
+/// Detects the encoding from the byte order mark, falling back to looking for
+/// the zero bytes of ASCII text in UTF-16.
+fn sniff(bytes: &[u8]) -> (Encoding, &[u8]) {
+    if let Some(rest) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return (Encoding::Utf8, rest);
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        return (Encoding::Utf16Le, rest);
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        return (Encoding::Utf16Be, rest);
+    }
+
+    let sample = &bytes[..bytes.len().min(512) & !1];
+    if !sample.is_empty() {
+        let zeros_at = |offset: usize| {
+            sample
+                .iter()
+                .skip(offset)
+                .step_by(2)
+                .filter(|b| **b == 0)
+                .count()
+        };
+        let half = sample.len() / 2;
+        if zeros_at(1) > half / 2 && zeros_at(0) == 0 {
+            return (Encoding::Utf16Le, bytes);
+        }
+        if zeros_at(0) > half / 2 && zeros_at(1) == 0 {
+            return (Encoding::Utf16Be, bytes);
+        }
+    }
+
+    if std::str::from_utf8(bytes).is_ok() {
+        (Encoding::Utf8, bytes)
+    } else {
+        (Encoding::Windows1252, bytes)
+    }
+}
+
+fn decode_utf16(bytes: &[u8], to_unit: fn([u8; 2]) -> u16) -> String {
+    let units = bytes
+        .chunks_exact(2)
+        .map(|pair| to_unit([pair[0], pair[1]]));
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+/// The characters Windows-1252 places in 0x80..=0x9F, where Latin-1 has
+/// control characters. Undefined positions decode to the replacement character.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '€', '\u{FFFD}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{FFFD}', 'Ž',
+    '\u{FFFD}', '\u{FFFD}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{FFFD}',
+    'ž', 'Ÿ',
+];
+
+fn decode_windows_1252(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&b| match b {
+            0x80..=0x9F => WINDOWS_1252_HIGH[(b - 0x80) as usize],
+            _ => b as char,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_utf16_with_and_without_bom() {
+        let text = "AFBrakesToggle -1 0 0x30 0 0 0 1 \"Brakes\"";
+        let mut le = vec![0xFF, 0xFE];
+        le.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        assert_eq!(decode(&le), text);
+
+        let be: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        assert_eq!(decode(&be), text);
+    }
+
+    #[test]
+    fn decode_windows_1252_and_utf8_bom() {
+        assert_eq!(decode(b"\"Caf\xe9 \x80\""), "\"Café €\"");
+        assert_eq!(decode("\u{FEFF}# ü".as_bytes()), "# ü");
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{Callback, Key, KeyCombo, Modifier};
+
+/// Scan code of the left alt key, which has no `Key` of its own.
+pub(crate) const LALT_SCANCODE: u16 = 0x38;
+
+/// A key going down or up.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeyEvent {
+    pub key: Key,
+    pub pressed: bool,
+}
+
+impl KeyEvent {
+    pub fn down(key: Key) -> KeyEvent {
+        KeyEvent { key, pressed: true }
+    }
+
+    pub fn up(key: Key) -> KeyEvent {
+        KeyEvent {
+            key,
+            pressed: false,
+        }
+    }
+}
+
+impl Modifier {
+    /// The key holding the modifier, left alt is `Key::Other(0x38)`.
+    pub fn key(&self) -> Key {
+        match self {
+            Modifier::LSHIFT => Key::LShift,
+            Modifier::LCONTROL => Key::LControl,
+            Modifier::LALT => Key::Other(LALT_SCANCODE),
+        }
+    }
+}
+
+impl KeyCombo {
+    /// Modifiers down, the key pressed and released, modifiers up.
+    pub fn key_sequence(&self) -> Vec<KeyEvent> {
+        let modifiers = self.modifiers.to_vec();
+        let mut events: Vec<_> = modifiers.iter().map(|m| KeyEvent::down(m.key())).collect();
+        events.push(KeyEvent::down(self.key.clone()));
+        events.push(KeyEvent::up(self.key.clone()));
+        events.extend(modifiers.iter().rev().map(|m| KeyEvent::up(m.key())));
+        events
+    }
+}
+
+impl Callback {
+    /// The key presses that trigger the callback in BMS: the combo of a
+    /// two-stage binding first, then the chord. Empty for unbound callbacks.
+    pub fn key_sequence(&self) -> Vec<KeyEvent> {
+        if !self.is_bound() {
+            return vec![];
+        }
+        let mut events = self
+            .combo_chord()
+            .map_or_else(Vec::new, |combo| combo.key_sequence());
+        events.extend(self.primary_combo().key_sequence());
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn press_two_stage_binding() {
+        let callback = Callback::builder("SimPilotToggle")
+            .chord_str("LShift+P")
+            .combo_str("LAlt+C")
+            .build()
+            .unwrap();
+        assert_eq!(
+            callback.key_sequence(),
+            vec![
+                KeyEvent::down(Key::Other(0x38)),
+                KeyEvent::down(Key::C),
+                KeyEvent::up(Key::C),
+                KeyEvent::up(Key::Other(0x38)),
+                KeyEvent::down(Key::LShift),
+                KeyEvent::down(Key::P),
+                KeyEvent::up(Key::P),
+                KeyEvent::up(Key::LShift),
+            ]
+        );
+        assert!(
+            Callback::builder("SimHookToggle")
+                .build()
+                .unwrap()
+                .key_sequence()
+                .is_empty()
+        );
+    }
+}
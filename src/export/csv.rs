@@ -0,0 +1,294 @@
+//! CSV export and import for spreadsheets.
+//!
+//! The columns are `callback,description,key,modifiers,combo,section`, where
+//! `key` is the key name (`UpArrow`), `modifiers` the modifiers joined by `+`
+//! (`LCtrl+LShift`) and `combo` the chord pressed first in two-stage bindings
+//! (`LAlt+C`). Unbound callbacks have an empty `key`. Fields containing commas or
+//! quotes are quoted, fields spanning several lines are not supported.
+
+use crate::combo::{key_name, modifier_names, parse_key, parse_modifier};
+use crate::{Callback, FalconKeyfile, Key, KeyCombo, ModifierSet, Visibility};
+use std::collections::HashMap;
+use std::fmt;
+
+const HEADER: [&str; 6] = [
+    "callback",
+    "description",
+    "key",
+    "modifiers",
+    "combo",
+    "section",
+];
+
+/// A row of a CSV file that could not be imported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvRowError {
+    /// The 1-based row number, the header being row 1.
+    pub row: usize,
+    pub message: String,
+}
+
+impl fmt::Display for CsvRowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "row {}: {}", self.row, self.message)
+    }
+}
+
+/// The outcome of importing a CSV file: the rows that could be read and the
+/// errors for those that could not.
+#[derive(Debug, Clone)]
+pub struct CsvImport {
+    pub keyfile: FalconKeyfile,
+    pub errors: Vec<CsvRowError>,
+}
+
+impl FalconKeyfile {
+    /// Writes one row per callback, sorted by name.
+    pub fn to_csv(&self) -> String {
+        let mut callbacks: Vec<_> = self.callbacks.values().collect();
+        callbacks.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut csv = HEADER.join(",");
+        csv.push('\n');
+        for callback in callbacks {
+            let (key, modifiers) = if callback.is_bound() {
+                (
+                    key_name(&callback.readable_key_code),
+                    modifier_names(callback.modifiers).join("+"),
+                )
+            } else {
+                (String::new(), String::new())
+            };
+            let fields = [
+                callback.name.clone(),
+                callback.description.clone().unwrap_or_default(),
+                key,
+                modifiers,
+                callback
+                    .combo_chord()
+                    .map(|c| c.to_string())
+                    .unwrap_or_default(),
+                callback.section.clone().unwrap_or_default(),
+            ];
+            let fields: Vec<_> = fields.iter().map(|f| quote(f)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Builds a key file from CSV rows in the format written by `to_csv`.
+    pub fn from_csv(name: String, csv: &str) -> CsvImport {
+        let mut keyfile = FalconKeyfile::new(name, HashMap::new());
+        let errors = keyfile.update_from_csv(csv);
+        CsvImport { keyfile, errors }
+    }
+
+    /// Rebinds callbacks from CSV rows, adding callbacks that do not exist yet.
+    /// Fields whose column is missing from the header are left as they are.
+    /// Rows that cannot be read are skipped and returned as errors.
+    pub fn update_from_csv(&mut self, csv: &str) -> Vec<CsvRowError> {
+        let mut errors = vec![];
+        let mut lines = csv.lines().enumerate();
+
+        let header = lines.next().map(|(_, line)| split_row(line));
+        let columns: HashMap<String, usize> = match header {
+            Some(Ok(header)) => header
+                .into_iter()
+                .enumerate()
+                .map(|(i, name)| (name.trim().to_lowercase(), i))
+                .collect(),
+            _ => {
+                errors.push(CsvRowError {
+                    row: 1,
+                    message: String::from("missing or unreadable header"),
+                });
+                return errors;
+            }
+        };
+        if !columns.contains_key("callback") {
+            errors.push(CsvRowError {
+                row: 1,
+                message: String::from("missing callback column"),
+            });
+            return errors;
+        }
+
+        for (index, line) in lines {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let row = index + 1;
+            match split_row(line).and_then(|fields| self.apply_row(&columns, &fields)) {
+                Ok(()) => {}
+                Err(message) => errors.push(CsvRowError { row, message }),
+            }
+        }
+        self.reindex();
+        errors
+    }
+
+    fn apply_row(
+        &mut self,
+        columns: &HashMap<String, usize>,
+        fields: &[String],
+    ) -> Result<(), String> {
+        let field = |column: &str| {
+            columns
+                .get(column)
+                .and_then(|i| fields.get(*i))
+                .map(|f| f.trim())
+                .filter(|f| !f.is_empty())
+        };
+
+        // a missing column keeps the value, an empty cell clears it
+        let has = |column: &str| columns.contains_key(column);
+
+        let name = field("callback").ok_or("missing callback name")?;
+        let key = match field("key") {
+            Some(key) => Some(parse_key(key).ok_or_else(|| format!("unknown key '{}'", key))?),
+            None => has("key").then_some(Key::Unknown),
+        };
+        let mut modifiers = ModifierSet::EMPTY;
+        for modifier in field("modifiers").into_iter().flat_map(|m| m.split('+')) {
+            modifiers.insert(
+                parse_modifier(modifier.trim())
+                    .ok_or_else(|| format!("unknown modifier '{}'", modifier))?,
+            );
+        }
+        let modifiers = has("modifiers").then_some(modifiers);
+        let combo = match field("combo") {
+            Some(combo) => Some(Some(combo.parse::<KeyCombo>().map_err(|e| e.to_string())?)),
+            None => has("combo").then_some(None),
+        };
+
+        let callback = self
+            .callbacks
+            .entry(String::from(name))
+            .or_insert_with(|| Callback {
+                name: String::from(name),
+                key_code: 0xFFFF,
+                readable_key_code: Key::Unknown,
+                modifiers: ModifierSet::EMPTY,
+                combo_key_code: 0,
+                readable_combo_key_code: Key::Unknown,
+                combo_modifiers: ModifierSet::EMPTY,
+                visibility: Visibility::Visible,
+                description: None,
+                section: None,
+                line: None,
+                raw_line: None,
+            });
+        if let Some(key) = key {
+            callback.key_code = key.scancode();
+            callback.readable_key_code = key;
+        }
+        if let Some(modifiers) = modifiers {
+            callback.modifiers = modifiers;
+        }
+        if let Some(combo) = combo {
+            callback.combo_key_code = combo.as_ref().map_or(0, |c| c.key.scancode());
+            callback.readable_combo_key_code =
+                combo.as_ref().map_or(Key::Unknown, |c| c.key.clone());
+            callback.combo_modifiers = combo.map_or(ModifierSet::EMPTY, |c| c.modifiers);
+        }
+        if let Some(description) = field("description") {
+            callback.description = Some(String::from(description));
+        }
+        if let Some(section) = field("section") {
+            callback.section = Some(String::from(section));
+        }
+        Ok(())
+    }
+}
+
+fn quote(field: &str) -> String {
+    if field.contains([',', '"']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        String::from(field)
+    }
+}
+
+fn split_row(line: &str) -> Result<Vec<String>, String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(String::from("unterminated quoted field"));
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_round_trip() {
+        let keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let csv = keyfile.to_csv();
+        assert!(csv.starts_with("callback,description,key,modifiers,combo,section\n"));
+        assert!(csv.contains("\nAFElevatorUp,"));
+
+        let import = FalconKeyfile::from_csv(String::from("basic.csv"), &csv);
+        assert_eq!(import.errors, vec![]);
+        let diff = keyfile.diff(&import.keyfile);
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.rebound.is_empty());
+    }
+
+    #[test]
+    fn report_errors_per_row() {
+        let mut keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let csv = "callback,key,modifiers
+AFBrakesToggle,B,LCtrl
+AFGearToggle,Hyper,
+,B,
+SimHookToggle,\"H,LAlt
+";
+        let errors = keyfile.update_from_csv(csv);
+        let rows: Vec<_> = errors.iter().map(|e| e.row).collect();
+        assert_eq!(rows, vec![3, 4, 5]);
+        assert_eq!(errors[0].to_string(), "row 3: unknown key 'Hyper'");
+
+        let brakes = keyfile.callback("AFBrakesToggle").unwrap();
+        assert_eq!(brakes.primary_combo().to_string(), "LCtrl+B");
+        let combo: KeyCombo = "LCtrl+B".parse().unwrap();
+        let bound = keyfile.callbacks_for_combo(&combo);
+        assert!(bound.iter().any(|c| c.name == "AFBrakesToggle"));
+    }
+
+    #[test]
+    fn keep_fields_without_a_column() {
+        let mut keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let csv = "callback,key
+SimPilotToggle,F12
+";
+        assert_eq!(keyfile.update_from_csv(csv), vec![]);
+        let pilot = keyfile.callback("SimPilotToggle").unwrap();
+        assert_eq!(pilot.binding().unwrap().to_string(), "LAlt+C, F12");
+
+        let csv = "callback,description
+AFElevatorUp,Nose down
+";
+        assert_eq!(keyfile.update_from_csv(csv), vec![]);
+        let elevator = keyfile.callback("AFElevatorUp").unwrap();
+        assert_eq!(elevator.primary_combo().to_string(), "LCtrl+LShift+UpArrow");
+        assert_eq!(elevator.description.as_deref(), Some("Nose down"));
+    }
+}
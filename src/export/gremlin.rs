@@ -0,0 +1,127 @@
+//! Joystick Gremlin profiles, every joystick button bound to a callback with
+//! a keyboard binding becomes a macro pressing that binding.
+//!
+//! POV hats and the BMS shifted layer have no direct counterpart in Gremlin
+//! and are left out.
+
+use crate::{Callback, FalconKeyfile, JoystickInput, KeyEvent};
+
+/// The Gremlin profile version written.
+const PROFILE_VERSION: u32 = 9;
+
+/// The device the buttons belong to, as Gremlin identifies it.
+#[derive(Debug, Clone)]
+pub struct GremlinOptions {
+    pub device_name: String,
+    /// The DirectInput GUID including braces.
+    pub device_guid: String,
+    /// The Gremlin mode the actions are added to.
+    pub mode: String,
+}
+
+impl Default for GremlinOptions {
+    fn default() -> GremlinOptions {
+        GremlinOptions {
+            device_name: String::from("Joystick"),
+            device_guid: String::from("{00000000-0000-0000-0000-000000000000}"),
+            mode: String::from("Default"),
+        }
+    }
+}
+
+impl FalconKeyfile {
+    pub fn to_gremlin_xml(&self, options: &GremlinOptions) -> String {
+        let mut xml = format!(
+            "<?xml version=\"1.0\" ?>\n<profile version=\"{}\">\n<devices>\n<device device-guid=\"{}\" label=\"\" name=\"{}\" type=\"joystick\">\n<mode name=\"{}\">\n",
+            PROFILE_VERSION,
+            escape(&options.device_guid),
+            escape(&options.device_name),
+            escape(&options.mode)
+        );
+
+        let mut buttons: Vec<(u16, &Callback)> = self
+            .joystick
+            .iter()
+            .filter(|binding| !binding.shifted)
+            .filter_map(|binding| match binding.input {
+                JoystickInput::Button(button) => Some((button, &binding.callback)),
+                JoystickInput::Pov { .. } => None,
+            })
+            .filter_map(|(button, name)| {
+                self.callbacks
+                    .get(name)
+                    .filter(|c| c.is_bound())
+                    .map(|c| (button, c))
+            })
+            .collect();
+        buttons.sort_by_key(|(button, callback)| (*button, callback.name.clone()));
+        buttons.dedup_by_key(|(button, _)| *button);
+
+        for (button, callback) in buttons {
+            xml.push_str(&button_xml(button, callback));
+        }
+        xml.push_str("</mode>\n</device>\n</devices>\n<vjoy-devices/>\n<merge-axis/>\n<plugins/>\n</profile>\n");
+        xml
+    }
+}
+
+/// A button with a macro action, Gremlin numbers buttons from 1.
+fn button_xml(button: u16, callback: &Callback) -> String {
+    let description = callback.description.as_deref().unwrap_or(&callback.name);
+    let mut xml = format!(
+        "<button description=\"{}\" id=\"{}\">\n<container type=\"basic\">\n<action-sets>\n<action-set>\n<macro>\n<actions>\n",
+        escape(description),
+        button + 1
+    );
+    for event in callback.key_sequence() {
+        xml.push_str(&key_xml(&event));
+    }
+    xml.push_str("</actions>\n<properties>\n<exclusive/>\n</properties>\n</macro>\n</action-set>\n</action-sets>\n</container>\n</button>\n");
+    xml
+}
+
+/// BMS marks extended keys with the high bit of the scan code.
+fn key_xml(event: &KeyEvent) -> String {
+    let scancode = event.key.scancode();
+    format!(
+        "<key extended=\"{}\" press=\"{}\" scan-code=\"{}\"/>\n",
+        if scancode & 0x80 != 0 {
+            "True"
+        } else {
+            "False"
+        },
+        if event.pressed { "True" } else { "False" },
+        scancode & 0x7F
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_button_macros() {
+        let keyfile = FalconKeyfile::from_path("test-data/T16000M-FCS-Full.key").unwrap();
+        let xml = keyfile.to_gremlin_xml(&GremlinOptions::default());
+
+        assert!(xml.starts_with("<?xml version=\"1.0\" ?>\n<profile version=\"9\">"));
+        assert!(xml.ends_with("</profile>\n"));
+        // SimPickle is on DX3 and bound to LAlt+Space
+        let callback = keyfile.callback("SimPickle").unwrap();
+        assert!(xml.contains(&format!(
+            "<button description=\"{}\" id=\"3\">",
+            callback.description.unwrap()
+        )));
+        assert_eq!(
+            xml.matches("<button ").count(),
+            xml.matches("</button>").count()
+        );
+    }
+}
@@ -0,0 +1,291 @@
+//! Standalone HTML reference cards with searchable binding tables and an
+//! optional keyboard showing which keys are in use.
+
+use super::callbacks_by_section;
+use crate::{Callback, FalconKeyfile, Key, KeyboardLayout};
+
+/// Controls the content of the HTML page.
+#[derive(Debug, Clone, Default)]
+pub struct HtmlOptions {
+    /// The page title, the key file's name if not set.
+    pub title: Option<String>,
+    /// Leave out callbacks that are not bound to a key.
+    pub skip_unbound: bool,
+    /// Leave out callbacks the BMS setup UI does not show.
+    pub skip_hidden: bool,
+    /// Draw a keyboard above the tables, clicking a key filters the tables.
+    pub keyboard: bool,
+    /// What to print on the keys of the keyboard.
+    pub layout: KeyboardLayout,
+}
+
+/// The rows of the main block of a US keyboard, used for the visual keyboard.
+const MAIN_ROWS: [&[Key]; 6] = [
+    &[
+        Key::Escape,
+        Key::F1,
+        Key::F2,
+        Key::F3,
+        Key::F4,
+        Key::F5,
+        Key::F6,
+        Key::F7,
+        Key::F8,
+        Key::F9,
+        Key::F10,
+        Key::F11,
+        Key::F12,
+    ],
+    &[
+        Key::BackQuote,
+        Key::Num1,
+        Key::Num2,
+        Key::Num3,
+        Key::Num4,
+        Key::Num5,
+        Key::Num6,
+        Key::Num7,
+        Key::Num8,
+        Key::Num9,
+        Key::Num0,
+        Key::Minus,
+        Key::Equals,
+        Key::Backspace,
+    ],
+    &[
+        Key::Tab,
+        Key::Q,
+        Key::W,
+        Key::E,
+        Key::R,
+        Key::T,
+        Key::Y,
+        Key::U,
+        Key::I,
+        Key::O,
+        Key::P,
+        Key::LeftBracket,
+        Key::RightBracket,
+        Key::Backslash,
+    ],
+    &[
+        Key::CapsLock,
+        Key::A,
+        Key::S,
+        Key::D,
+        Key::F,
+        Key::G,
+        Key::H,
+        Key::J,
+        Key::K,
+        Key::L,
+        Key::Semicolon,
+        Key::Apostrophe,
+        Key::Return,
+    ],
+    &[
+        Key::LShift,
+        Key::Z,
+        Key::X,
+        Key::C,
+        Key::V,
+        Key::B,
+        Key::N,
+        Key::M,
+        Key::Comma,
+        Key::Period,
+        Key::Slash,
+    ],
+    &[
+        Key::LControl,
+        Key::LWin,
+        Key::Space,
+        Key::RWin,
+        Key::Apps,
+        Key::RControl,
+    ],
+];
+
+/// The navigation block and arrow keys, aligned with `MAIN_ROWS`.
+const NAVIGATION_ROWS: [&[Key]; 6] = [
+    &[Key::PrintScr, Key::ScrollLock],
+    &[Key::Insert, Key::Home, Key::PageUp],
+    &[Key::Delete, Key::End, Key::PageDown],
+    &[],
+    &[Key::UpArrow],
+    &[Key::LeftArrow, Key::DownArrow, Key::RightArrow],
+];
+
+/// The numeric keypad, aligned with `MAIN_ROWS`.
+const NUMPAD_ROWS: [&[Key]; 6] = [
+    &[],
+    &[Key::Numlock, Key::Divide, Key::Multiply, Key::Subtract],
+    &[Key::Numpad7, Key::Numpad8, Key::Numpad9, Key::Add],
+    &[Key::Numpad4, Key::Numpad5, Key::Numpad6],
+    &[Key::Numpad1, Key::Numpad2, Key::Numpad3, Key::NumpadEnter],
+    &[Key::Numpad0, Key::Decimal],
+];
+
+const KEYBOARD_BLOCKS: [[&[Key]; 6]; 3] = [MAIN_ROWS, NAVIGATION_ROWS, NUMPAD_ROWS];
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em}
+input{font-size:1.1em;padding:.3em;width:30em;margin-bottom:1em}
+table{border-collapse:collapse;margin-bottom:1.5em;min-width:40em}
+th,td{border:1px solid #bbb;padding:.2em .5em;text-align:left}
+th{background:#eee}
+.keyboard{display:flex;gap:1em;margin-bottom:1.5em}
+.row{display:flex;gap:.2em;margin-bottom:.2em;min-height:2em}
+.block:nth-child(2) .row{justify-content:center}
+.key{border:1px solid #888;border-radius:.3em;min-width:2.5em;padding:.3em;text-align:center;cursor:pointer;font-size:.8em}
+.used{background:#fd8}
+.hidden{display:none}";
+
+const SCRIPT: &str = "const search = document.getElementById('search');
+function filter(query) {
+  query = query.toLowerCase();
+  document.querySelectorAll('section').forEach(section => {
+    let visible = 0;
+    section.querySelectorAll('tbody tr').forEach(row => {
+      const match = row.textContent.toLowerCase().includes(query) || row.dataset.key === query;
+      row.classList.toggle('hidden', !match);
+      if (match) visible++;
+    });
+    section.classList.toggle('hidden', visible === 0);
+  });
+}
+search.addEventListener('input', () => filter(search.value));
+document.querySelectorAll('.key').forEach(key => key.addEventListener('click', () => {
+  search.value = key.dataset.key;
+  filter(search.value);
+}));";
+
+impl FalconKeyfile {
+    /// Renders a standalone HTML page with one table per section, a search box
+    /// filtering all tables and optionally a keyboard highlighting used keys.
+    pub fn to_html(&self, options: &HtmlOptions) -> String {
+        let title = escape(options.title.as_deref().unwrap_or(&self.name));
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}\n</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+            title, STYLE, title
+        );
+        html.push_str("<input id=\"search\" type=\"search\" placeholder=\"Search callbacks, descriptions and chords\">\n");
+
+        if options.keyboard {
+            html.push_str(&self.keyboard_html(&options.layout));
+        }
+
+        for (section, callbacks) in
+            callbacks_by_section(self, options.skip_unbound, options.skip_hidden)
+        {
+            html.push_str(&format!(
+                "<section>\n<h2>{}</h2>\n<table>\n<thead><tr><th>Description</th><th>Callback</th><th>Chord</th><th>Combo</th></tr></thead>\n<tbody>\n",
+                escape(section)
+            ));
+            for callback in callbacks {
+                html.push_str(&row_html(callback));
+            }
+            html.push_str("</tbody>\n</table>\n</section>\n");
+        }
+
+        html.push_str(&format!(
+            "<script>\n{}\n</script>\n</body>\n</html>\n",
+            SCRIPT
+        ));
+        html
+    }
+
+    fn keyboard_html(&self, layout: &KeyboardLayout) -> String {
+        let mut html = String::from("<div class=\"keyboard\">\n");
+        for block in KEYBOARD_BLOCKS {
+            html.push_str("<div class=\"block\">\n");
+            for row in block {
+                html.push_str("<div class=\"row\">");
+                for key in row {
+                    let callbacks = self.callbacks_for_key(key.clone());
+                    let names: Vec<_> = callbacks
+                        .iter()
+                        .map(|c| format!("{}: {}", c.primary_combo(), c.name))
+                        .collect();
+                    html.push_str(&format!(
+                        "<div class=\"key{}\" data-key=\"{}\" title=\"{}\">{}</div>",
+                        if callbacks.is_empty() { "" } else { " used" },
+                        key_id(key),
+                        escape(&names.join("\n")),
+                        escape(&key.label(layout)),
+                    ));
+                }
+                html.push_str("</div>\n");
+            }
+            html.push_str("</div>\n");
+        }
+        html.push_str("</div>\n");
+        html
+    }
+}
+
+fn row_html(callback: &Callback) -> String {
+    let chord = if callback.is_bound() {
+        callback.primary_combo().to_string()
+    } else {
+        String::new()
+    };
+    let combo = callback
+        .combo_chord()
+        .map(|c| c.to_string())
+        .unwrap_or_default();
+    format!(
+        "<tr data-key=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+        if callback.is_bound() {
+            key_id(&callback.readable_key_code)
+        } else {
+            String::new()
+        },
+        escape(callback.description.as_deref().unwrap_or("")),
+        escape(&callback.name),
+        escape(&chord),
+        escape(&combo)
+    )
+}
+
+/// The value used to match keyboard keys and table rows in the page's script.
+fn key_id(key: &Key) -> String {
+    format!("key:{}", key.scancode())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_standalone_page() {
+        let keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let options = HtmlOptions {
+            keyboard: true,
+            ..HtmlOptions::default()
+        };
+        let html = keyfile.to_html(&options);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>basic.key</title>"));
+        assert!(html.contains("<h2>6.06 SIMULATION &amp; HARDWARE</h2>"));
+        assert!(html.contains(
+            "<td>SIM: Toggle Pilot Model</td><td>SimPilotToggle</td><td>P</td><td>LAlt+C</td>"
+        ));
+        assert!(html.contains("<div class=\"key used\" data-key=\"key:25\""));
+        for key in [Key::UpArrow, Key::Numpad8] {
+            let used = format!("<div class=\"key used\" data-key=\"{}\"", key_id(&key));
+            assert!(html.contains(&used), "{}", key);
+        }
+        assert!(html.ends_with("</html>\n"));
+
+        let html = keyfile.to_html(&HtmlOptions::default());
+        assert!(!html.contains("class=\"keyboard\""));
+    }
+}
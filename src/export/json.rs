@@ -0,0 +1,420 @@
+//! A stable JSON representation of key files for tools outside of Rust.
+//!
+//! The document looks like this, callbacks are sorted by name:
+//!
+//! ```json
+//! {
+//!   "format": "falcon-keyfile",
+//!   "version": 1,
+//!   "name": "basic.key",
+//!   "callbacks": [
+//!     {
+//!       "name": "SimPilotToggle",
+//!       "description": "SIM: Toggle Pilot Model",
+//!       "section": "6.06 SIMULATION & HARDWARE",
+//!       "visibility": "visible",
+//!       "chord": { "key": "P", "scancode": 25, "modifiers": [] },
+//!       "combo": { "key": "C", "scancode": 46, "modifiers": ["LAlt"] }
+//!     }
+//!   ],
+//!   "joystick": [
+//!     {
+//!       "callback": "SimPickle",
+//!       "input": { "button": 2 },
+//!       "shifted": false,
+//!       "description": null,
+//!       "section": "2.01 STICK"
+//!     }
+//!   ],
+//!   "sections": ["1.01 UI FUNCTIONS", "2.01 STICK"]
+//! }
+//! ```
+//!
+//! `chord` is `null` for unbound callbacks and `combo`, the chord pressed
+//! before `chord` in two-stage bindings, is `null` for single chords.
+//! `visibility` is one of `visible`, `locked`, `header` and `hidden`, or the
+//! raw number for anything else. On import the `scancode` is authoritative,
+//! `key` is informational.
+//!
+//! Joystick bindings are in file order. `input` is `{ "button": n }` with `n`
+//! counted from 0, `DX1` being 0, or `{ "pov": { "hat": h, "direction": d } }`.
+//! `sections` lists the section headers in file order. Both may be missing
+//! from older documents.
+//!
+//! `KeyfileDiff::to_json` writes a `falcon-keyfile-diff` document with the
+//! same callback objects in `added` and `removed`, and `{ "old": ..., "new":
+//! ... }` pairs in `rebound` and `modified`.
+
+use crate::combo::key_name;
+use crate::{
+    Callback, CallbackChange, FalconKeyfile, JoystickBinding, JoystickInput, Key, KeyCombo,
+    KeyfileDiff, Modifier, ModifierSet, Visibility,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The `version` written to and accepted from JSON documents.
+pub const FORMAT_VERSION: u32 = 1;
+const FORMAT: &str = "falcon-keyfile";
+const DIFF_FORMAT: &str = "falcon-keyfile-diff";
+
+#[derive(Serialize, Deserialize)]
+struct Document {
+    format: String,
+    version: u32,
+    name: String,
+    callbacks: Vec<JsonCallback>,
+    #[serde(default)]
+    joystick: Vec<JsonJoystick>,
+    #[serde(default)]
+    sections: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct DiffDocument {
+    format: String,
+    version: u32,
+    added: Vec<JsonCallback>,
+    removed: Vec<JsonCallback>,
+    rebound: Vec<JsonChange>,
+    modified: Vec<JsonChange>,
+}
+
+#[derive(Serialize)]
+struct JsonChange {
+    old: JsonCallback,
+    new: JsonCallback,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonCallback {
+    name: String,
+    description: Option<String>,
+    section: Option<String>,
+    visibility: JsonVisibility,
+    chord: Option<JsonChord>,
+    combo: Option<JsonChord>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonJoystick {
+    callback: String,
+    input: JsonInput,
+    shifted: bool,
+    description: Option<String>,
+    section: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum JsonInput {
+    Button(u16),
+    Pov { hat: u16, direction: u8 },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum JsonVisibility {
+    Named(NamedVisibility),
+    Other(i32),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum NamedVisibility {
+    Visible,
+    Locked,
+    Header,
+    Hidden,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonChord {
+    key: String,
+    scancode: u16,
+    modifiers: Vec<JsonModifier>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum JsonModifier {
+    #[serde(rename = "LShift")]
+    Shift,
+    #[serde(rename = "LCtrl")]
+    Ctrl,
+    #[serde(rename = "LAlt")]
+    Alt,
+}
+
+#[derive(Debug)]
+pub enum JsonError {
+    Syntax(serde_json::Error),
+    UnsupportedFormat { format: String, version: u32 },
+}
+
+impl std::fmt::Display for JsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonError::Syntax(e) => write!(f, "invalid key file JSON: {}", e),
+            JsonError::UnsupportedFormat { format, version } => {
+                write!(f, "unsupported document {} version {}", format, version)
+            }
+        }
+    }
+}
+
+impl std::error::Error for JsonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            JsonError::Syntax(e) => Some(e),
+            JsonError::UnsupportedFormat { .. } => None,
+        }
+    }
+}
+
+impl FalconKeyfile {
+    /// Serializes the key file to the JSON document described in the module
+    /// documentation.
+    pub fn to_json(&self) -> String {
+        let mut callbacks: Vec<_> = self.callbacks.values().map(JsonCallback::from).collect();
+        callbacks.sort_by(|a, b| a.name.cmp(&b.name));
+        let document = Document {
+            format: String::from(FORMAT),
+            version: FORMAT_VERSION,
+            name: self.name.clone(),
+            callbacks,
+            joystick: self.joystick.iter().map(JsonJoystick::from).collect(),
+            sections: self.sections.clone(),
+        };
+        serde_json::to_string_pretty(&document).expect("key file JSON is always serializable")
+    }
+
+    /// Reads a key file from a JSON document written by `to_json`.
+    pub fn from_json(json: &str) -> Result<FalconKeyfile, JsonError> {
+        let document: Document = serde_json::from_str(json).map_err(JsonError::Syntax)?;
+        if document.format != FORMAT || document.version != FORMAT_VERSION {
+            return Err(JsonError::UnsupportedFormat {
+                format: document.format,
+                version: document.version,
+            });
+        }
+
+        let callbacks: HashMap<_, _> = document
+            .callbacks
+            .into_iter()
+            .map(|c| (c.name.clone(), Callback::from(c)))
+            .collect();
+        let mut keyfile = FalconKeyfile::new(document.name, callbacks);
+        keyfile.joystick = document
+            .joystick
+            .into_iter()
+            .map(JoystickBinding::from)
+            .collect();
+        keyfile.sections = document.sections;
+        Ok(keyfile)
+    }
+}
+
+impl KeyfileDiff {
+    /// Serializes the diff to the JSON document described in the module
+    /// documentation, for checks in CI pipelines.
+    pub fn to_json(&self) -> String {
+        let changes = |changes: &[CallbackChange]| {
+            changes
+                .iter()
+                .map(|change| JsonChange {
+                    old: JsonCallback::from(&change.old),
+                    new: JsonCallback::from(&change.new),
+                })
+                .collect()
+        };
+        let document = DiffDocument {
+            format: String::from(DIFF_FORMAT),
+            version: FORMAT_VERSION,
+            added: self.added.iter().map(JsonCallback::from).collect(),
+            removed: self.removed.iter().map(JsonCallback::from).collect(),
+            rebound: changes(&self.rebound),
+            modified: changes(&self.modified),
+        };
+        serde_json::to_string_pretty(&document).expect("diff JSON is always serializable")
+    }
+}
+
+impl From<&Callback> for JsonCallback {
+    fn from(callback: &Callback) -> JsonCallback {
+        JsonCallback {
+            name: callback.name.clone(),
+            description: callback.description.clone(),
+            section: callback.section.clone(),
+            visibility: match callback.visibility {
+                Visibility::Visible => JsonVisibility::Named(NamedVisibility::Visible),
+                Visibility::Locked => JsonVisibility::Named(NamedVisibility::Locked),
+                Visibility::Header => JsonVisibility::Named(NamedVisibility::Header),
+                Visibility::Hidden => JsonVisibility::Named(NamedVisibility::Hidden),
+                Visibility::Other(value) => JsonVisibility::Other(value),
+            },
+            chord: callback
+                .is_bound()
+                .then(|| JsonChord::from(&callback.primary_combo())),
+            combo: callback.combo_chord().as_ref().map(JsonChord::from),
+        }
+    }
+}
+
+impl From<JsonCallback> for Callback {
+    fn from(callback: JsonCallback) -> Callback {
+        let chord = callback.chord.map(KeyCombo::from);
+        let combo = callback.combo.map(KeyCombo::from);
+        Callback {
+            name: callback.name,
+            key_code: chord.as_ref().map_or(0xFFFF, |c| c.key.scancode()),
+            readable_key_code: chord.as_ref().map_or(Key::Unknown, |c| c.key.clone()),
+            modifiers: chord.as_ref().map_or(ModifierSet::EMPTY, |c| c.modifiers),
+            combo_key_code: combo.as_ref().map_or(0, |c| c.key.scancode()),
+            readable_combo_key_code: combo.as_ref().map_or(Key::Unknown, |c| c.key.clone()),
+            combo_modifiers: combo.as_ref().map_or(ModifierSet::EMPTY, |c| c.modifiers),
+            visibility: match callback.visibility {
+                JsonVisibility::Named(NamedVisibility::Visible) => Visibility::Visible,
+                JsonVisibility::Named(NamedVisibility::Locked) => Visibility::Locked,
+                JsonVisibility::Named(NamedVisibility::Header) => Visibility::Header,
+                JsonVisibility::Named(NamedVisibility::Hidden) => Visibility::Hidden,
+                JsonVisibility::Other(value) => Visibility::Other(value),
+            },
+            description: callback.description,
+            section: callback.section,
+            line: None,
+            raw_line: None,
+        }
+    }
+}
+
+impl From<&JoystickBinding> for JsonJoystick {
+    fn from(binding: &JoystickBinding) -> JsonJoystick {
+        JsonJoystick {
+            callback: binding.callback.clone(),
+            input: match binding.input {
+                JoystickInput::Button(button) => JsonInput::Button(button),
+                JoystickInput::Pov { hat, direction } => JsonInput::Pov { hat, direction },
+            },
+            shifted: binding.shifted,
+            description: binding.description.clone(),
+            section: binding.section.clone(),
+        }
+    }
+}
+
+impl From<JsonJoystick> for JoystickBinding {
+    fn from(binding: JsonJoystick) -> JoystickBinding {
+        JoystickBinding {
+            callback: binding.callback,
+            input: match binding.input {
+                JsonInput::Button(button) => JoystickInput::Button(button),
+                JsonInput::Pov { hat, direction } => JoystickInput::Pov { hat, direction },
+            },
+            shifted: binding.shifted,
+            description: binding.description,
+            section: binding.section,
+            line: None,
+        }
+    }
+}
+
+impl From<&KeyCombo> for JsonChord {
+    fn from(combo: &KeyCombo) -> JsonChord {
+        JsonChord {
+            key: key_name(&combo.key),
+            scancode: combo.key.scancode(),
+            modifiers: combo
+                .modifiers
+                .iter()
+                .map(|m| match m {
+                    Modifier::LSHIFT => JsonModifier::Shift,
+                    Modifier::LCONTROL => JsonModifier::Ctrl,
+                    Modifier::LALT => JsonModifier::Alt,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<JsonChord> for KeyCombo {
+    fn from(chord: JsonChord) -> KeyCombo {
+        let modifiers = chord
+            .modifiers
+            .into_iter()
+            .map(|m| match m {
+                JsonModifier::Shift => Modifier::LSHIFT,
+                JsonModifier::Ctrl => Modifier::LCONTROL,
+                JsonModifier::Alt => Modifier::LALT,
+            })
+            .collect();
+        KeyCombo::new(Key::from_scancode(chord.scancode), modifiers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_round_trip() {
+        let keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let json = keyfile.to_json();
+        assert!(json.contains("\"format\": \"falcon-keyfile\""));
+
+        let imported = FalconKeyfile::from_json(&json).unwrap();
+        assert!(keyfile.diff(&imported).is_empty());
+
+        let callback = imported.callback("SimPilotToggle").unwrap();
+        assert_eq!(callback.secondary_combo().unwrap().to_string(), "LAlt+C");
+        assert!(callback.section.is_some());
+    }
+
+    #[test]
+    fn json_round_trip_with_joystick_and_sections() {
+        let keyfile = FalconKeyfile::from_path("test-data/T16000M-FCS-Full.key").unwrap();
+        assert!(!keyfile.joystick_bindings().is_empty());
+        let imported = FalconKeyfile::from_json(&keyfile.to_json()).unwrap();
+
+        assert!(keyfile.diff(&imported).is_empty());
+        assert_eq!(imported.sections(), keyfile.sections());
+        let without_lines = |keyfile: &FalconKeyfile| -> Vec<JoystickBinding> {
+            keyfile
+                .joystick_bindings()
+                .iter()
+                .map(|b| JoystickBinding {
+                    line: None,
+                    ..b.clone()
+                })
+                .collect()
+        };
+        assert_eq!(without_lines(&imported), without_lines(&keyfile));
+    }
+
+    #[test]
+    fn diff_as_json() {
+        let old = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let mut new = old.clone();
+        new.set_binding("AFBrakesToggle", "LCtrl+B".parse().unwrap())
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&old.diff(&new).to_json()).unwrap();
+        assert_eq!(json["format"], "falcon-keyfile-diff");
+        assert_eq!(json["added"].as_array().unwrap().len(), 0);
+        let rebound = &json["rebound"][0];
+        assert_eq!(rebound["old"]["name"], "AFBrakesToggle");
+        assert_eq!(rebound["old"]["chord"]["modifiers"], serde_json::json!([]));
+        assert_eq!(rebound["new"]["chord"]["modifiers"][0], "LCtrl");
+    }
+
+    #[test]
+    fn reject_other_documents() {
+        let json = r#"{"format": "falcon-keyfile", "version": 99, "name": "x", "callbacks": []}"#;
+        assert!(matches!(
+            FalconKeyfile::from_json(json),
+            Err(JsonError::UnsupportedFormat { version: 99, .. })
+        ));
+        assert!(matches!(
+            FalconKeyfile::from_json("{"),
+            Err(JsonError::Syntax(_))
+        ));
+    }
+}
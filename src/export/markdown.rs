@@ -0,0 +1,74 @@
+//! Markdown kneeboards with one table per section.
+
+use super::callbacks_by_section;
+use crate::FalconKeyfile;
+
+/// Controls which callbacks end up on the kneeboard.
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownOptions {
+    /// Leave out callbacks that are not bound to a key.
+    pub skip_unbound: bool,
+    /// Leave out callbacks the BMS setup UI does not show.
+    pub skip_hidden: bool,
+}
+
+impl FalconKeyfile {
+    /// Renders the key file as a Markdown document with a heading and a table
+    /// of descriptions and chords per section, in file order. Callbacks without
+    /// a section are listed last under "Other".
+    pub fn to_markdown(&self, options: &MarkdownOptions) -> String {
+        let mut markdown = format!("# {}\n", self.name);
+        for (section, callbacks) in
+            callbacks_by_section(self, options.skip_unbound, options.skip_hidden)
+        {
+            markdown.push_str(&format!("\n## {}\n\n", section));
+            markdown.push_str("| Description | Chord | Combo |\n");
+            markdown.push_str("|---|---|---|\n");
+            for callback in callbacks {
+                let chord = callback.chord().map(|c| c.to_string()).unwrap_or_default();
+                let combo = callback
+                    .combo_chord()
+                    .map(|c| c.to_string())
+                    .unwrap_or_default();
+                markdown.push_str(&format!(
+                    "| {} | {} | {} |\n",
+                    escape(callback.description.as_deref().unwrap_or(&callback.name)),
+                    escape(&chord),
+                    escape(&combo)
+                ));
+            }
+        }
+        markdown
+    }
+}
+
+fn escape(cell: &str) -> String {
+    cell.replace('|', "\\|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_sections_as_tables() {
+        let keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let markdown = keyfile.to_markdown(&MarkdownOptions::default());
+
+        assert!(markdown.starts_with("# basic.key\n"));
+        let throttle = markdown
+            .find("\n## 2.19 THROTTLE QUADRANT SYSTEM\n")
+            .unwrap();
+        let simulation = markdown.find("\n## 6.06 SIMULATION & HARDWARE\n").unwrap();
+        assert!(throttle < simulation);
+        assert!(markdown.contains("| SIM: Toggle Pilot Model | P | LAlt+C |\n"));
+
+        let options = MarkdownOptions {
+            skip_unbound: true,
+            skip_hidden: true,
+        };
+        let markdown = keyfile.to_markdown(&options);
+        assert!(!markdown.contains("| TQS: RDR CURSOR - Up Left |  |  |"));
+        assert!(!markdown.contains("Radio-next menu AWACS"));
+    }
+}
@@ -0,0 +1,87 @@
+//! Conversions of key files to and from other formats.
+
+use crate::{Callback, FalconKeyfile, Visibility};
+
+pub mod csv;
+pub mod gremlin;
+pub mod html;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod markdown;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+pub mod svg;
+#[cfg(feature = "json")]
+pub mod touch_portal;
+
+/// The filtered callbacks grouped by section, sections in file order and
+/// callbacks sorted by description.
+pub(crate) fn callbacks_by_section(
+    keyfile: &FalconKeyfile,
+    skip_unbound: bool,
+    skip_hidden: bool,
+) -> Vec<(&str, Vec<&Callback>)> {
+    let mut sections: Vec<&str> = keyfile.sections.iter().map(String::as_str).collect();
+    let mut extra: Vec<&str> = keyfile
+        .callbacks
+        .values()
+        .filter_map(|c| c.section.as_deref())
+        .filter(|s| !keyfile.sections.iter().any(|known| known == s))
+        .collect();
+    extra.sort();
+    extra.dedup();
+    sections.extend(extra);
+
+    let included = |c: &&Callback| {
+        (c.is_bound() || !skip_unbound) && (c.visibility != Visibility::Hidden || !skip_hidden)
+    };
+
+    let mut grouped: Vec<_> = sections
+        .into_iter()
+        .map(|section| {
+            let callbacks = keyfile
+                .callbacks
+                .values()
+                .filter(|c| c.section.as_deref() == Some(section))
+                .filter(included)
+                .collect();
+            (section, sorted(callbacks))
+        })
+        .collect();
+    let other = keyfile
+        .callbacks
+        .values()
+        .filter(|c| c.section.is_none())
+        .filter(included)
+        .collect();
+    grouped.push(("Other", sorted(other)));
+
+    grouped.retain(|(_, callbacks)| !callbacks.is_empty());
+    grouped
+}
+
+fn sorted(mut callbacks: Vec<&Callback>) -> Vec<&Callback> {
+    callbacks.sort_by(|a, b| (&a.description, &a.name).cmp(&(&b.description, &b.name)));
+    callbacks
+}
+
+/// `text` cut to at most `chars` characters, ending with `…` if shortened.
+pub(crate) fn truncate(text: &str, chars: usize) -> String {
+    if text.chars().count() <= chars {
+        return String::from(text);
+    }
+    let mut truncated: String = text.chars().take(chars.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_long_text() {
+        assert_eq!(truncate("SimPilotToggle", 20), "SimPilotToggle");
+        assert_eq!(truncate("SimPilotToggle", 8), "SimPilo…");
+    }
+}
@@ -0,0 +1,119 @@
+//! Kneeboard sized PDF pages (5.5 x 8.5 inches) listing the bindings per section.
+
+use super::{callbacks_by_section, truncate};
+use crate::FalconKeyfile;
+use printpdf::{
+    BuiltinFont, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference,
+};
+
+const PAGE_WIDTH: Mm = Mm(139.7);
+const PAGE_HEIGHT: Mm = Mm(215.9);
+const MARGIN: f32 = 10.0;
+const LINE_HEIGHT: f32 = 4.2;
+const CHORD_COLUMN: f32 = 92.0;
+/// Roughly what fits left of the chord column at the body font size.
+const DESCRIPTION_CHARS: usize = 48;
+
+/// Controls the content of the kneeboard.
+#[derive(Debug, Clone, Default)]
+pub struct PdfOptions {
+    /// The title on the first page, the key file's name if not set.
+    pub title: Option<String>,
+    /// Leave out callbacks that are not bound to a key.
+    pub skip_unbound: bool,
+    /// Leave out callbacks the BMS setup UI does not show.
+    pub skip_hidden: bool,
+}
+
+/// Lays out text top to bottom, starting new pages as they fill up.
+struct Writer {
+    document: PdfDocumentReference,
+    layer: PdfLayerReference,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    y: f32,
+}
+
+impl Writer {
+    fn new(title: &str) -> Result<Writer, printpdf::Error> {
+        let (document, page, layer) = PdfDocument::new(title, PAGE_WIDTH, PAGE_HEIGHT, "Bindings");
+        let regular = document.add_builtin_font(BuiltinFont::Helvetica)?;
+        let bold = document.add_builtin_font(BuiltinFont::HelveticaBold)?;
+        let layer = document.get_page(page).get_layer(layer);
+        Ok(Writer {
+            document,
+            layer,
+            regular,
+            bold,
+            y: PAGE_HEIGHT.0 - MARGIN,
+        })
+    }
+
+    /// Moves down by `height`, continuing on a new page if it does not fit.
+    fn advance(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            let (page, layer) = self.document.add_page(PAGE_WIDTH, PAGE_HEIGHT, "Bindings");
+            self.layer = self.document.get_page(page).get_layer(layer);
+            self.y = PAGE_HEIGHT.0 - MARGIN;
+        }
+        self.y -= height;
+    }
+
+    fn heading(&mut self, text: &str, size: f32) {
+        self.advance(size * 0.6);
+        self.layer
+            .use_text(text, size, Mm(MARGIN), Mm(self.y), &self.bold);
+        self.y -= 1.5;
+    }
+
+    fn binding(&mut self, description: &str, chord: &str) {
+        self.advance(LINE_HEIGHT);
+        self.layer
+            .use_text(description, 8.0, Mm(MARGIN), Mm(self.y), &self.regular);
+        self.layer
+            .use_text(chord, 8.0, Mm(CHORD_COLUMN), Mm(self.y), &self.bold);
+    }
+}
+
+impl FalconKeyfile {
+    /// Renders the bindings as a kneeboard PDF, one block per section.
+    pub fn to_pdf(&self, options: &PdfOptions) -> Result<Vec<u8>, printpdf::Error> {
+        let title = options.title.as_deref().unwrap_or(&self.name);
+        let mut writer = Writer::new(title)?;
+        writer.heading(title, 14.0);
+
+        for (section, callbacks) in
+            callbacks_by_section(self, options.skip_unbound, options.skip_hidden)
+        {
+            writer.advance(2.0);
+            writer.heading(section, 10.0);
+            for callback in callbacks {
+                let chord = callback
+                    .binding()
+                    .map(|binding| binding.to_string())
+                    .unwrap_or_default();
+                let description = callback.description.as_deref().unwrap_or(&callback.name);
+                writer.binding(&truncate(description, DESCRIPTION_CHARS), &chord);
+            }
+        }
+
+        writer.document.save_to_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_kneeboard_pages() {
+        let keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let options = PdfOptions {
+            skip_unbound: true,
+            ..PdfOptions::default()
+        };
+        let pdf = keyfile.to_pdf(&options).unwrap();
+        assert!(pdf.starts_with(b"%PDF-"));
+        assert!(pdf.windows(9).filter(|w| w == b"/MediaBox").count() > 1);
+    }
+}
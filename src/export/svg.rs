@@ -0,0 +1,389 @@
+//! Keyboard charts as SVG, every key annotated with the callbacks bound to it
+//! on each modifier layer.
+
+use super::truncate;
+use crate::combo::modifier_names;
+use crate::{FalconKeyfile, Key, KeyCombo, KeyboardLayout, ModifierSet};
+
+/// Size of one key unit in pixels.
+const UNIT: f32 = 64.0;
+const GAP: f32 = 3.0;
+const FONT_SIZE: f32 = 8.0;
+/// Approximate advance of a character at `FONT_SIZE`, used to fit text into keys.
+const CHAR_WIDTH: f32 = 4.6;
+const LINE_HEIGHT: f32 = 9.0;
+const LEGEND_HEIGHT: f32 = 40.0;
+
+/// Text colors of the eight modifier layers, indexed by modifier mask.
+const LAYER_COLORS: [&str; 8] = [
+    "#000000", "#1f5fbf", "#bf1f1f", "#7f1fbf", "#1f8f1f", "#1f8f8f", "#bf6f00", "#5f5f5f",
+];
+
+/// Controls what is written on the keys.
+#[derive(Debug, Clone, Default)]
+pub struct SvgOptions {
+    /// Annotate keys with the callback descriptions instead of their names.
+    pub use_descriptions: bool,
+    /// What to print on the keys.
+    pub layout: KeyboardLayout,
+}
+
+/// Position and size of a key in key units.
+struct KeyCap {
+    key: Key,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+}
+
+/// A full size US keyboard. Keys BMS key files have no scan code for, such as
+/// the alt keys, are left out.
+fn keyboard() -> Vec<KeyCap> {
+    let mut caps = vec![];
+    let mut row = |y: f32, x: f32, keys: &[(Key, f32)]| {
+        let mut x = x;
+        for (key, width) in keys {
+            caps.push(KeyCap {
+                key: key.clone(),
+                x,
+                y,
+                width: *width,
+                height: 1.0,
+            });
+            x += width;
+        }
+    };
+
+    row(0.0, 0.0, &[(Key::Escape, 1.0)]);
+    row(
+        0.0,
+        2.0,
+        &[
+            (Key::F1, 1.0),
+            (Key::F2, 1.0),
+            (Key::F3, 1.0),
+            (Key::F4, 1.0),
+        ],
+    );
+    row(
+        0.0,
+        6.5,
+        &[
+            (Key::F5, 1.0),
+            (Key::F6, 1.0),
+            (Key::F7, 1.0),
+            (Key::F8, 1.0),
+        ],
+    );
+    row(
+        0.0,
+        11.0,
+        &[
+            (Key::F9, 1.0),
+            (Key::F10, 1.0),
+            (Key::F11, 1.0),
+            (Key::F12, 1.0),
+        ],
+    );
+    row(0.0, 15.25, &[(Key::PrintScr, 1.0), (Key::ScrollLock, 1.0)]);
+
+    row(
+        1.5,
+        0.0,
+        &[
+            (Key::BackQuote, 1.0),
+            (Key::Num1, 1.0),
+            (Key::Num2, 1.0),
+            (Key::Num3, 1.0),
+            (Key::Num4, 1.0),
+            (Key::Num5, 1.0),
+            (Key::Num6, 1.0),
+            (Key::Num7, 1.0),
+            (Key::Num8, 1.0),
+            (Key::Num9, 1.0),
+            (Key::Num0, 1.0),
+            (Key::Minus, 1.0),
+            (Key::Equals, 1.0),
+            (Key::Backspace, 2.0),
+        ],
+    );
+    row(
+        1.5,
+        15.25,
+        &[(Key::Insert, 1.0), (Key::Home, 1.0), (Key::PageUp, 1.0)],
+    );
+    row(
+        1.5,
+        18.5,
+        &[
+            (Key::Numlock, 1.0),
+            (Key::Divide, 1.0),
+            (Key::Multiply, 1.0),
+            (Key::Subtract, 1.0),
+        ],
+    );
+
+    row(
+        2.5,
+        0.0,
+        &[
+            (Key::Tab, 1.5),
+            (Key::Q, 1.0),
+            (Key::W, 1.0),
+            (Key::E, 1.0),
+            (Key::R, 1.0),
+            (Key::T, 1.0),
+            (Key::Y, 1.0),
+            (Key::U, 1.0),
+            (Key::I, 1.0),
+            (Key::O, 1.0),
+            (Key::P, 1.0),
+            (Key::LeftBracket, 1.0),
+            (Key::RightBracket, 1.0),
+            (Key::Backslash, 1.5),
+        ],
+    );
+    row(
+        2.5,
+        15.25,
+        &[(Key::Delete, 1.0), (Key::End, 1.0), (Key::PageDown, 1.0)],
+    );
+    row(
+        2.5,
+        18.5,
+        &[
+            (Key::Numpad7, 1.0),
+            (Key::Numpad8, 1.0),
+            (Key::Numpad9, 1.0),
+        ],
+    );
+
+    row(
+        3.5,
+        0.0,
+        &[
+            (Key::CapsLock, 1.75),
+            (Key::A, 1.0),
+            (Key::S, 1.0),
+            (Key::D, 1.0),
+            (Key::F, 1.0),
+            (Key::G, 1.0),
+            (Key::H, 1.0),
+            (Key::J, 1.0),
+            (Key::K, 1.0),
+            (Key::L, 1.0),
+            (Key::Semicolon, 1.0),
+            (Key::Apostrophe, 1.0),
+            (Key::Return, 2.25),
+        ],
+    );
+    row(
+        3.5,
+        18.5,
+        &[
+            (Key::Numpad4, 1.0),
+            (Key::Numpad5, 1.0),
+            (Key::Numpad6, 1.0),
+        ],
+    );
+
+    row(
+        4.5,
+        0.0,
+        &[
+            (Key::LShift, 2.25),
+            (Key::Z, 1.0),
+            (Key::X, 1.0),
+            (Key::C, 1.0),
+            (Key::V, 1.0),
+            (Key::B, 1.0),
+            (Key::N, 1.0),
+            (Key::M, 1.0),
+            (Key::Comma, 1.0),
+            (Key::Period, 1.0),
+            (Key::Slash, 1.0),
+        ],
+    );
+    row(4.5, 16.25, &[(Key::UpArrow, 1.0)]);
+    row(
+        4.5,
+        18.5,
+        &[
+            (Key::Numpad1, 1.0),
+            (Key::Numpad2, 1.0),
+            (Key::Numpad3, 1.0),
+        ],
+    );
+
+    row(5.5, 0.0, &[(Key::LControl, 1.25), (Key::LWin, 1.25)]);
+    row(5.5, 3.75, &[(Key::Space, 6.25)]);
+    row(
+        5.5,
+        11.25,
+        &[(Key::RWin, 1.25), (Key::Apps, 1.25), (Key::RControl, 1.25)],
+    );
+    row(
+        5.5,
+        15.25,
+        &[
+            (Key::LeftArrow, 1.0),
+            (Key::DownArrow, 1.0),
+            (Key::RightArrow, 1.0),
+        ],
+    );
+    row(5.5, 18.5, &[(Key::Numpad0, 2.0), (Key::Decimal, 1.0)]);
+
+    caps.push(KeyCap {
+        key: Key::Add,
+        x: 21.5,
+        y: 2.5,
+        width: 1.0,
+        height: 2.0,
+    });
+    caps.push(KeyCap {
+        key: Key::NumpadEnter,
+        x: 21.5,
+        y: 4.5,
+        width: 1.0,
+        height: 2.0,
+    });
+    caps
+}
+
+impl FalconKeyfile {
+    /// Draws a keyboard with the callbacks bound to each key, colored by
+    /// modifier layer, and a legend of the layer colors below it. Text that does
+    /// not fit is shortened, keys with more bindings than lines end with a
+    /// `+N more` line.
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let caps = keyboard();
+        let width = caps.iter().map(|c| c.x + c.width).fold(0.0, f32::max) * UNIT;
+        let height = caps.iter().map(|c| c.y + c.height).fold(0.0, f32::max) * UNIT;
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\">\n",
+            w = width,
+            h = height + LEGEND_HEIGHT
+        );
+        for cap in &caps {
+            svg.push_str(&self.key_svg(cap, options));
+        }
+        svg.push_str(&legend_svg(height));
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    fn key_svg(&self, cap: &KeyCap, options: &SvgOptions) -> String {
+        let x = cap.x * UNIT + GAP / 2.0;
+        let y = cap.y * UNIT + GAP / 2.0;
+        let width = cap.width * UNIT - GAP;
+        let height = cap.height * UNIT - GAP;
+        let mut svg = format!(
+            "<g>\n<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"4\" fill=\"#f4f4f4\" stroke=\"#888\"/>\n",
+            x, y, width, height
+        );
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" font-size=\"10\" font-weight=\"bold\">{}</text>\n",
+            x + 3.0,
+            y + 11.0,
+            escape(&cap.key.label(&options.layout))
+        ));
+
+        let mut lines = vec![];
+        for mask in 0..=ModifierSet::ALL.to_mask() {
+            let combo = KeyCombo::new(cap.key.clone(), ModifierSet::from_mask(mask));
+            for callback in self.callbacks_for_combo(&combo) {
+                let text = match (&callback.description, options.use_descriptions) {
+                    (Some(description), true) => description.as_str(),
+                    _ => callback.name.as_str(),
+                };
+                lines.push((mask as usize, text));
+            }
+        }
+
+        let chars = ((width - 4.0) / CHAR_WIDTH) as usize;
+        let capacity = ((height - 16.0) / LINE_HEIGHT) as usize;
+        let shown = if lines.len() > capacity {
+            capacity.saturating_sub(1)
+        } else {
+            lines.len()
+        };
+        for (i, (layer, text)) in lines.iter().take(shown).enumerate() {
+            svg.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"{}\">{}</text>\n",
+                x + 3.0,
+                y + 20.0 + i as f32 * LINE_HEIGHT,
+                FONT_SIZE,
+                LAYER_COLORS[*layer],
+                escape(&truncate(text, chars))
+            ));
+        }
+        if shown < lines.len() {
+            svg.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" font-size=\"{}\" font-style=\"italic\">+{} more</text>\n",
+                x + 3.0,
+                y + 20.0 + shown as f32 * LINE_HEIGHT,
+                FONT_SIZE,
+                lines.len() - shown
+            ));
+        }
+        svg.push_str("</g>\n");
+        svg
+    }
+}
+
+fn legend_svg(top: f32) -> String {
+    let mut svg = String::new();
+    let mut x = GAP;
+    for (mask, color) in LAYER_COLORS.iter().enumerate() {
+        let names = modifier_names(ModifierSet::from_mask(mask as u16));
+        let label = if names.is_empty() {
+            String::from("No modifier")
+        } else {
+            names.join("+")
+        };
+        svg.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"12\" height=\"12\" fill=\"{}\"/>\n<text x=\"{}\" y=\"{}\" font-size=\"11\">{}</text>\n",
+            x,
+            top + 14.0,
+            color,
+            x + 16.0,
+            top + 24.0,
+            label
+        ));
+        x += 24.0 + label.len() as f32 * 6.5;
+    }
+    svg
+}
+
+/// Shortens `text` to at most `chars` characters, marking the cut with an ellipsis.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn annotate_keys_per_layer() {
+        let keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let svg = keyfile.to_svg(&SvgOptions::default());
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(svg.contains(">UpArrow</text>"));
+        // LCtrl+UpArrow is on the control layer, shortened to fit the key
+        assert!(svg.contains(&format!("fill=\"{}\">AFElevatorT…<", LAYER_COLORS[2])));
+        assert!(svg.contains(">LCtrl+LShift</text>"));
+
+        let options = SvgOptions {
+            layout: KeyboardLayout::qwertz(),
+            ..SvgOptions::default()
+        };
+        assert!(keyfile.to_svg(&options).contains(">Ö</text>"));
+    }
+}
@@ -0,0 +1,191 @@
+//! Button pages for tablet button boxes such as Touch Portal.
+//!
+//! Bound callbacks are laid out section by section on pages of a fixed grid,
+//! a section that does not fit on one page continues on the next. Each
+//! button sends its binding as one or two hotkeys:
+//!
+//! ```json
+//! {
+//!   "name": "basic.key",
+//!   "pages": [
+//!     {
+//!       "name": "6.06 SIMULATION & HARDWARE",
+//!       "columns": 6,
+//!       "rows": 4,
+//!       "buttons": [
+//!         {
+//!           "row": 0,
+//!           "column": 0,
+//!           "text": "SIM: Toggle Pilot Model",
+//!           "callback": "SimPilotToggle",
+//!           "hotkeys": [
+//!             { "keys": ["LAlt", "C"], "virtualKeys": [164, 67] },
+//!             { "keys": ["P"], "virtualKeys": [80] }
+//!           ]
+//!         }
+//!       ]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Hotkeys are sent in order, modifiers first within a hotkey. Keys without
+//! a Windows virtual-key code are left out of `virtualKeys`.
+
+use super::callbacks_by_section;
+use crate::combo::{MODIFIER_ORDER, key_name, modifier_names};
+use crate::{Callback, FalconKeyfile, KeyCombo, Modifier};
+use serde::Serialize;
+
+/// The grid of the generated pages.
+#[derive(Debug, Clone)]
+pub struct TouchPortalOptions {
+    pub columns: usize,
+    pub rows: usize,
+    /// Leave out callbacks the BMS setup UI does not show.
+    pub skip_hidden: bool,
+}
+
+impl Default for TouchPortalOptions {
+    fn default() -> TouchPortalOptions {
+        TouchPortalOptions {
+            columns: 6,
+            rows: 4,
+            skip_hidden: true,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Document<'a> {
+    name: &'a str,
+    pages: Vec<Page<'a>>,
+}
+
+#[derive(Serialize)]
+struct Page<'a> {
+    name: String,
+    columns: usize,
+    rows: usize,
+    buttons: Vec<Button<'a>>,
+}
+
+#[derive(Serialize)]
+struct Button<'a> {
+    row: usize,
+    column: usize,
+    text: &'a str,
+    callback: &'a str,
+    hotkeys: Vec<Hotkey>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Hotkey {
+    keys: Vec<String>,
+    virtual_keys: Vec<u16>,
+}
+
+impl FalconKeyfile {
+    /// Lays out the bound callbacks on button pages as described in the
+    /// module documentation.
+    pub fn to_touch_portal(&self, options: &TouchPortalOptions) -> String {
+        let per_page = (options.columns * options.rows).max(1);
+        let mut pages = Vec::new();
+        for (section, callbacks) in callbacks_by_section(self, true, options.skip_hidden) {
+            let chunks: Vec<_> = callbacks.chunks(per_page).collect();
+            for (number, chunk) in chunks.iter().enumerate() {
+                let name = if chunks.len() > 1 {
+                    format!("{} ({}/{})", section, number + 1, chunks.len())
+                } else {
+                    section.to_string()
+                };
+                let buttons = chunk
+                    .iter()
+                    .enumerate()
+                    .map(|(index, callback)| button(index, options.columns.max(1), callback))
+                    .collect();
+                pages.push(Page {
+                    name,
+                    columns: options.columns,
+                    rows: options.rows,
+                    buttons,
+                });
+            }
+        }
+
+        let document = Document {
+            name: &self.name,
+            pages,
+        };
+        serde_json::to_string_pretty(&document).expect("button pages are always serializable")
+    }
+}
+
+fn button<'a>(index: usize, columns: usize, callback: &'a Callback) -> Button<'a> {
+    let mut hotkeys = Vec::new();
+    if let Some(combo) = callback.combo_chord() {
+        hotkeys.push(hotkey(&combo));
+    }
+    hotkeys.push(hotkey(&callback.primary_combo()));
+    Button {
+        row: index / columns,
+        column: index % columns,
+        text: callback.description.as_deref().unwrap_or(&callback.name),
+        callback: &callback.name,
+        hotkeys,
+    }
+}
+
+fn hotkey(combo: &KeyCombo) -> Hotkey {
+    let mut keys: Vec<String> = modifier_names(combo.modifiers)
+        .into_iter()
+        .map(String::from)
+        .collect();
+    keys.push(key_name(&combo.key));
+    let mut virtual_keys: Vec<u16> = MODIFIER_ORDER
+        .into_iter()
+        .filter(|m| combo.modifiers.contains(*m))
+        .map(modifier_vk)
+        .collect();
+    virtual_keys.extend(combo.key.to_vk());
+    Hotkey { keys, virtual_keys }
+}
+
+/// The left hand modifier keys, `Key` has no variant for LAlt.
+fn modifier_vk(modifier: Modifier) -> u16 {
+    match modifier {
+        Modifier::LSHIFT => 0xA0,
+        Modifier::LCONTROL => 0xA2,
+        Modifier::LALT => 0xA4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lay_out_sections_on_pages() {
+        let keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let options = TouchPortalOptions::default();
+        let json: serde_json::Value =
+            serde_json::from_str(&keyfile.to_touch_portal(&options)).unwrap();
+
+        let pages = json["pages"].as_array().unwrap();
+        assert!(!pages.is_empty());
+        for page in pages {
+            assert!(page["buttons"].as_array().unwrap().len() <= 24);
+        }
+        let button = pages
+            .iter()
+            .flat_map(|page| page["buttons"].as_array().unwrap())
+            .find(|button| button["callback"] == "SimPilotToggle")
+            .unwrap();
+        assert_eq!(
+            button["hotkeys"][0]["keys"],
+            serde_json::json!(["LAlt", "C"])
+        );
+        assert_eq!(button["hotkeys"][1]["keys"], serde_json::json!(["P"]));
+    }
+}
@@ -0,0 +1,290 @@
+//! A C API for linking the parser into tools written in other languages, the
+//! declarations are in `include/falcon_keyfile.h`. See the README for how to
+//! build the shared library.
+//!
+//! A key file is opened with `fbk_keyfile_open` or `fbk_keyfile_parse` and
+//! released with `fbk_keyfile_free`. Callbacks are borrowed from the key file
+//! and stay valid until it is freed, sorted by name so that indices are
+//! stable. Functions that fail return null and leave a message for
+//! `fbk_last_error`.
+
+use crate::{Callback, FalconKeyfile, Key, KeyCombo, ModifierSet, ParseOptions, parse_str};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::ptr;
+
+/// A callback as seen from C. Strings are NUL terminated, `description` is
+/// null if the callback has none.
+#[repr(C)]
+pub struct FbkCallback {
+    pub name: *const c_char,
+    pub description: *const c_char,
+    /// The DirectInput scan code, 0 or 0xFFFF if unbound.
+    pub key_code: u16,
+    /// The modifier mask of the key, 1 shift, 2 control and 4 alt.
+    pub modifiers: u16,
+    /// The scan code of the combo pressed first, 0 if there is none.
+    pub combo_key_code: u16,
+    pub combo_modifiers: u16,
+    pub bound: bool,
+}
+
+/// An opened key file, opaque to C.
+pub struct FbkKeyfile {
+    keyfile: FalconKeyfile,
+    callbacks: Vec<FbkCallback>,
+    /// The storage behind the strings of `callbacks`.
+    _strings: Vec<CString>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).expect("NUL bytes were replaced");
+    LAST_ERROR.with(|error| *error.borrow_mut() = Some(message));
+}
+
+impl FbkKeyfile {
+    fn new(keyfile: FalconKeyfile) -> FbkKeyfile {
+        let mut sorted: Vec<&Callback> = keyfile.iter().collect();
+        sorted.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut strings = vec![];
+        let mut callbacks = vec![];
+        for callback in sorted {
+            // The heap buffer of a CString does not move when the CString does.
+            let name = c_string(&callback.name);
+            let description = callback.description.as_deref().map(c_string);
+            callbacks.push(FbkCallback {
+                name: name.as_ptr(),
+                description: description.as_ref().map_or(ptr::null(), |d| d.as_ptr()),
+                key_code: callback.key_code,
+                modifiers: callback.modifiers.to_mask(),
+                combo_key_code: callback.combo_key_code,
+                combo_modifiers: callback.combo_modifiers.to_mask(),
+                bound: callback.is_bound(),
+            });
+            strings.push(name);
+            strings.extend(description);
+        }
+
+        FbkKeyfile {
+            keyfile,
+            callbacks,
+            _strings: strings,
+        }
+    }
+
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.callbacks
+            .binary_search_by(|c| {
+                // SAFETY: every name points into `strings`.
+                unsafe { CStr::from_ptr(c.name) }
+                    .to_bytes()
+                    .cmp(name.as_bytes())
+            })
+            .ok()
+    }
+}
+
+fn c_string(text: &str) -> CString {
+    CString::new(text.replace('\0', " ")).expect("NUL bytes were replaced")
+}
+
+/// # Safety
+///
+/// `text` must be null or point to a NUL terminated string.
+unsafe fn to_str<'a>(text: *const c_char, what: &str) -> Option<&'a str> {
+    if text.is_null() {
+        set_error(format!("{} is null", what));
+        return None;
+    }
+    // SAFETY: guaranteed by the caller.
+    match unsafe { CStr::from_ptr(text) }.to_str() {
+        Ok(text) => Some(text),
+        Err(_) => {
+            set_error(format!("{} is not valid UTF-8", what));
+            None
+        }
+    }
+}
+
+/// Opens and parses the key file at `path`.
+///
+/// # Safety
+///
+/// `path` must be null or point to a NUL terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fbk_keyfile_open(path: *const c_char) -> *mut FbkKeyfile {
+    // SAFETY: guaranteed by the caller.
+    let Some(path) = (unsafe { to_str(path, "path") }) else {
+        return ptr::null_mut();
+    };
+    match FalconKeyfile::from_path(path) {
+        Ok(keyfile) => Box::into_raw(Box::new(FbkKeyfile::new(keyfile))),
+        Err(error) => {
+            set_error(error.to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Parses the text of a key file.
+///
+/// # Safety
+///
+/// `name` and `content` must be null or point to NUL terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fbk_keyfile_parse(
+    name: *const c_char,
+    content: *const c_char,
+) -> *mut FbkKeyfile {
+    // SAFETY: guaranteed by the caller.
+    let (Some(name), Some(content)) = (unsafe { to_str(name, "name") }, unsafe {
+        to_str(content, "content")
+    }) else {
+        return ptr::null_mut();
+    };
+    match parse_str(String::from(name), content, &ParseOptions::default()) {
+        Ok(report) => Box::into_raw(Box::new(FbkKeyfile::new(report.keyfile))),
+        Err(error) => {
+            set_error(error.to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Releases a key file and all callbacks borrowed from it.
+///
+/// # Safety
+///
+/// `keyfile` must be null or returned by `fbk_keyfile_open` or
+/// `fbk_keyfile_parse` and not freed before.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fbk_keyfile_free(keyfile: *mut FbkKeyfile) {
+    if !keyfile.is_null() {
+        // SAFETY: guaranteed by the caller.
+        drop(unsafe { Box::from_raw(keyfile) });
+    }
+}
+
+/// The number of callbacks in the key file.
+///
+/// # Safety
+///
+/// `keyfile` must be a live key file.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fbk_keyfile_len(keyfile: *const FbkKeyfile) -> usize {
+    // SAFETY: guaranteed by the caller.
+    unsafe { &*keyfile }.callbacks.len()
+}
+
+/// The callback at `index` in name order, null past the end.
+///
+/// # Safety
+///
+/// `keyfile` must be a live key file.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fbk_keyfile_callback(
+    keyfile: *const FbkKeyfile,
+    index: usize,
+) -> *const FbkCallback {
+    // SAFETY: guaranteed by the caller.
+    unsafe { &*keyfile }
+        .callbacks
+        .get(index)
+        .map_or(ptr::null(), |c| c as *const FbkCallback)
+}
+
+/// The callback named `name`, null if there is none.
+///
+/// # Safety
+///
+/// `keyfile` must be a live key file and `name` null or a NUL terminated
+/// string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fbk_keyfile_find(
+    keyfile: *const FbkKeyfile,
+    name: *const c_char,
+) -> *const FbkCallback {
+    // SAFETY: guaranteed by the caller.
+    let (keyfile, Some(name)) = (unsafe { &*keyfile }, unsafe { to_str(name, "name") }) else {
+        return ptr::null();
+    };
+    keyfile
+        .index_of(name)
+        .map_or(ptr::null(), |i| &keyfile.callbacks[i] as *const FbkCallback)
+}
+
+/// Stores up to `capacity` callbacks triggered by the scan code and modifier
+/// mask in `out` and returns how many there are in total.
+///
+/// # Safety
+///
+/// `keyfile` must be a live key file and `out` valid for `capacity` writes,
+/// it may be null if `capacity` is 0.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fbk_keyfile_find_by_key(
+    keyfile: *const FbkKeyfile,
+    key_code: u16,
+    modifiers: u16,
+    out: *mut *const FbkCallback,
+    capacity: usize,
+) -> usize {
+    // SAFETY: guaranteed by the caller.
+    let keyfile = unsafe { &*keyfile };
+    let combo = KeyCombo::new(
+        Key::from_scancode(key_code),
+        ModifierSet::from_mask(modifiers),
+    );
+    let found: Vec<_> = keyfile
+        .keyfile
+        .callbacks_for_combo(&combo)
+        .into_iter()
+        .filter_map(|c| keyfile.index_of(&c.name))
+        .collect();
+    for (slot, index) in found.iter().take(capacity).enumerate() {
+        // SAFETY: `slot` is below `capacity`, guaranteed by the caller.
+        unsafe { *out.add(slot) = &keyfile.callbacks[*index] };
+    }
+    found.len()
+}
+
+/// The message of the last failure on this thread, null if nothing failed
+/// yet. Valid until the next failing call on the thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn fbk_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| error.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_through_c_api() {
+        let path = c"test-data/basic.key";
+        unsafe {
+            let keyfile = fbk_keyfile_open(path.as_ptr());
+            assert!(!keyfile.is_null());
+            assert!(fbk_keyfile_len(keyfile) > 0);
+
+            let callback = fbk_keyfile_find(keyfile, c"SimPilotToggle".as_ptr());
+            assert!(!callback.is_null());
+            assert_eq!(CStr::from_ptr((*callback).name), c"SimPilotToggle");
+            assert_eq!((*callback).key_code, Key::P.scancode());
+
+            let mut found = [ptr::null(); 4];
+            let count =
+                fbk_keyfile_find_by_key(keyfile, Key::P.scancode(), 0, found.as_mut_ptr(), 4);
+            assert!(count >= 1);
+            assert!(found[..count.min(4)].contains(&callback));
+            fbk_keyfile_free(keyfile);
+
+            assert!(fbk_keyfile_open(c"does-not-exist.key".as_ptr()).is_null());
+            assert!(!fbk_last_error().is_null());
+        }
+    }
+}
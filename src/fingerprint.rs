@@ -0,0 +1,112 @@
+use crate::{FalconKeyfile, JoystickInput};
+use std::fmt;
+
+/// A hash of what a key file binds, see `FalconKeyfile::fingerprint`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fingerprint(pub u64);
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// 64 bit FNV-1a, which unlike `DefaultHasher` is the same on every platform
+/// and Rust version.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Fnv {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn write_u16(&mut self, value: u16) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_str(&mut self, text: &str) {
+        self.write(text.as_bytes());
+        // separates "ab" + "c" from "a" + "bc"
+        self.write(&[0xFF]);
+    }
+}
+
+impl FalconKeyfile {
+    /// A stable hash over the bindings only: the bound callbacks with their
+    /// chords and combos and the joystick bindings. Two files with the same
+    /// fingerprint trigger the same callbacks, no matter the order of their
+    /// lines, comments, descriptions or formatting. The value is the same
+    /// across platforms and versions of this crate.
+    pub fn fingerprint(&self) -> Fingerprint {
+        let mut hasher = Fnv::new();
+
+        let mut bound: Vec<_> = self.callbacks.values().filter(|c| c.is_bound()).collect();
+        bound.sort_by(|a, b| a.name.cmp(&b.name));
+        hasher.write_str("keyboard");
+        for callback in bound {
+            hasher.write_str(&callback.name);
+            hasher.write_u16(callback.key_code);
+            hasher.write_u16(callback.modifiers.to_mask());
+            hasher.write_u16(callback.combo_key_code);
+            hasher.write_u16(callback.combo_modifiers.to_mask());
+        }
+
+        let mut joystick: Vec<_> = self
+            .joystick
+            .iter()
+            .map(|b| {
+                let input = match b.input {
+                    JoystickInput::Button(button) => (0, button, 0),
+                    JoystickInput::Pov { hat, direction } => (1, hat, direction as u16),
+                };
+                (b.callback.as_str(), input, b.shifted)
+            })
+            .collect();
+        joystick.sort();
+        joystick.dedup();
+        hasher.write_str("joystick");
+        for (callback, (kind, number, direction), shifted) in joystick {
+            hasher.write_str(callback);
+            hasher.write_u16(kind);
+            hasher.write_u16(number);
+            hasher.write_u16(direction);
+            hasher.write(&[shifted as u8]);
+        }
+
+        Fingerprint(hasher.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Key, KeyCombo, ModifierSet};
+
+    #[test]
+    fn ignore_formatting_but_not_bindings() {
+        let keyfile = FalconKeyfile::from_path("test-data/T16000M-FCS-Full.key").unwrap();
+        let canonical = crate::parse_str(
+            String::from("other.key"),
+            &keyfile.canonicalize(),
+            &crate::ParseOptions::default(),
+        )
+        .unwrap()
+        .keyfile;
+        assert_eq!(canonical.fingerprint(), keyfile.fingerprint());
+        assert_eq!(keyfile.fingerprint().to_string().len(), 16);
+
+        let mut rebound = keyfile.clone();
+        rebound
+            .set_binding("SimPilotToggle", KeyCombo::new(Key::F12, ModifierSet::ALL))
+            .unwrap();
+        assert_ne!(rebound.fingerprint(), keyfile.fingerprint());
+    }
+}
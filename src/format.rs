@@ -0,0 +1,251 @@
+use crate::combo::{MODIFIER_ORDER, key_name};
+use crate::{Callback, Key, KeyCombo, Modifier};
+use std::fmt;
+
+/// How keys, modifiers and chords are written for people.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ChordFormat {
+    /// Compact upper case codes, such as `LC-UP`.
+    Short,
+    /// Full names, such as `Left Control + Up Arrow`.
+    #[default]
+    Long,
+    /// Keyboard symbols where there are any, such as `⌃↑`.
+    Symbols,
+}
+
+impl ChordFormat {
+    fn separator(self) -> &'static str {
+        match self {
+            ChordFormat::Short => "-",
+            ChordFormat::Long => " + ",
+            ChordFormat::Symbols => "",
+        }
+    }
+}
+
+impl Key {
+    pub fn display_name(&self, format: ChordFormat) -> String {
+        match format {
+            ChordFormat::Short => short_name(self),
+            ChordFormat::Long => long_name(self),
+            ChordFormat::Symbols => symbol(self).map_or_else(|| short_name(self), String::from),
+        }
+    }
+}
+
+impl Modifier {
+    pub fn display_name(&self, format: ChordFormat) -> &'static str {
+        match (self, format) {
+            (Modifier::LCONTROL, ChordFormat::Short) => "LC",
+            (Modifier::LSHIFT, ChordFormat::Short) => "LS",
+            (Modifier::LALT, ChordFormat::Short) => "LA",
+            (Modifier::LCONTROL, ChordFormat::Long) => "Left Control",
+            (Modifier::LSHIFT, ChordFormat::Long) => "Left Shift",
+            (Modifier::LALT, ChordFormat::Long) => "Left Alt",
+            (Modifier::LCONTROL, ChordFormat::Symbols) => "⌃",
+            (Modifier::LSHIFT, ChordFormat::Symbols) => "⇧",
+            (Modifier::LALT, ChordFormat::Symbols) => "⌥",
+        }
+    }
+}
+
+impl KeyCombo {
+    /// The combo with modifiers in the order control, shift, alt.
+    pub fn format(&self, format: ChordFormat) -> String {
+        let mut parts: Vec<String> = MODIFIER_ORDER
+            .into_iter()
+            .filter(|m| self.modifiers.contains(*m))
+            .map(|m| String::from(m.display_name(format)))
+            .collect();
+        parts.push(self.key.display_name(format));
+        parts.join(format.separator())
+    }
+}
+
+impl Callback {
+    /// The binding and name, such as `Left Control + Up Arrow — AFElevatorTrimUp`.
+    /// Two-stage bindings start with the combo pressed first.
+    pub fn format(&self, format: ChordFormat) -> String {
+        if !self.is_bound() {
+            return format!("{} (unbound)", self.name);
+        }
+        let chord = self.primary_combo().format(format);
+        match self.combo_chord() {
+            Some(combo) => format!("{}, {} — {}", combo.format(format), chord, self.name),
+            None => format!("{} — {}", chord, self.name),
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.display_name(ChordFormat::Long))
+    }
+}
+
+impl fmt::Display for Modifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.display_name(ChordFormat::Long))
+    }
+}
+
+impl fmt::Display for Callback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.format(ChordFormat::Long))
+    }
+}
+
+fn long_name(key: &Key) -> String {
+    let name = match key {
+        Key::Unknown => "Unbound",
+        Key::Num1 => "1",
+        Key::Num2 => "2",
+        Key::Num3 => "3",
+        Key::Num4 => "4",
+        Key::Num5 => "5",
+        Key::Num6 => "6",
+        Key::Num7 => "7",
+        Key::Num8 => "8",
+        Key::Num9 => "9",
+        Key::Num0 => "0",
+        Key::LControl => "Left Control",
+        Key::RControl => "Right Control",
+        Key::LShift => "Left Shift",
+        Key::LWin => "Left Windows",
+        Key::RWin => "Right Windows",
+        Key::Apps => "Menu",
+        Key::Multiply => "Numpad Multiply",
+        Key::Divide => "Numpad Divide",
+        Key::Subtract => "Numpad Minus",
+        Key::Add => "Numpad Plus",
+        Key::Decimal => "Numpad Decimal",
+        Key::Numlock => "Num Lock",
+        Key::PrintScr => "Print Screen",
+        Key::Other(number) => return format!("Key {:#04X}", number),
+        key => return split_words(&key_name(key)),
+    };
+    String::from(name)
+}
+
+/// `UpArrow` as `Up Arrow` and `Numpad7` as `Numpad 7`, but `F12` as it is.
+fn split_words(name: &str) -> String {
+    let mut words = String::new();
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if previous.is_some_and(|p| p.is_lowercase()) && (c.is_uppercase() || c.is_ascii_digit()) {
+            words.push(' ');
+        }
+        words.push(c);
+        previous = Some(c);
+    }
+    words
+}
+
+fn short_name(key: &Key) -> String {
+    let name = match key {
+        Key::Unknown => "-",
+        Key::Escape => "ESC",
+        Key::Minus => "-",
+        Key::Equals => "=",
+        Key::Backspace => "BKSP",
+        Key::Tab => "TAB",
+        Key::LeftBracket => "[",
+        Key::RightBracket => "]",
+        Key::Return => "RET",
+        Key::LControl => "LC",
+        Key::RControl => "RC",
+        Key::LShift => "LS",
+        Key::Semicolon => ";",
+        Key::Apostrophe => "'",
+        Key::BackQuote => "`",
+        Key::Backslash => "\\",
+        Key::Comma => ",",
+        Key::Period => ".",
+        Key::Slash => "/",
+        Key::Multiply => "NUM*",
+        Key::Divide => "NUM/",
+        Key::Subtract => "NUM-",
+        Key::Add => "NUM+",
+        Key::Decimal => "NUM.",
+        Key::NumpadEnter => "NUMENT",
+        Key::Space => "SPC",
+        Key::CapsLock => "CAPS",
+        Key::Numlock => "NUMLK",
+        Key::ScrollLock => "SCRLK",
+        Key::PrintScr => "PRTSC",
+        Key::UpArrow => "UP",
+        Key::DownArrow => "DN",
+        Key::LeftArrow => "LT",
+        Key::RightArrow => "RT",
+        Key::PageUp => "PGUP",
+        Key::PageDown => "PGDN",
+        Key::Insert => "INS",
+        Key::Delete => "DEL",
+        Key::Num1 => "1",
+        Key::Num2 => "2",
+        Key::Num3 => "3",
+        Key::Num4 => "4",
+        Key::Num5 => "5",
+        Key::Num6 => "6",
+        Key::Num7 => "7",
+        Key::Num8 => "8",
+        Key::Num9 => "9",
+        Key::Num0 => "0",
+        Key::Other(_) => return key_name(key),
+        key => return key_name(key).to_uppercase(),
+    };
+    String::from(name)
+}
+
+fn symbol(key: &Key) -> Option<&'static str> {
+    Some(match key {
+        Key::UpArrow => "↑",
+        Key::DownArrow => "↓",
+        Key::LeftArrow => "←",
+        Key::RightArrow => "→",
+        Key::Escape => "⎋",
+        Key::Backspace => "⌫",
+        Key::Tab => "⇥",
+        Key::Return => "⏎",
+        Key::NumpadEnter => "⌤",
+        Key::Space => "␣",
+        Key::CapsLock => "⇪",
+        Key::Delete => "⌦",
+        Key::Home => "↖",
+        Key::End => "↘",
+        Key::PageUp => "⇞",
+        Key::PageDown => "⇟",
+        Key::LShift => "⇧",
+        Key::LControl | Key::RControl => "⌃",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_chords() {
+        let combo: KeyCombo = "LCtrl+UpArrow".parse().unwrap();
+        assert_eq!(combo.format(ChordFormat::Short), "LC-UP");
+        assert_eq!(combo.format(ChordFormat::Long), "Left Control + Up Arrow");
+        assert_eq!(combo.format(ChordFormat::Symbols), "⌃↑");
+
+        assert_eq!(Key::Numpad7.to_string(), "Numpad 7");
+        assert_eq!(Key::F12.to_string(), "F12");
+        assert_eq!(Key::Num4.to_string(), "4");
+        assert_eq!(Key::Other(0x54).to_string(), "Key 0x54");
+        assert_eq!(Modifier::LALT.to_string(), "Left Alt");
+
+        let callback = Callback::builder("SimPilotToggle")
+            .chord_str("P")
+            .combo_str("LAlt+C")
+            .build()
+            .unwrap();
+        assert_eq!(callback.to_string(), "Left Alt + C, P — SimPilotToggle");
+        let callback = Callback::builder("SimHookToggle").build().unwrap();
+        assert_eq!(callback.to_string(), "SimHookToggle (unbound)");
+    }
+}
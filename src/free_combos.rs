@@ -0,0 +1,76 @@
+use crate::key::named_keys;
+use crate::{FalconKeyfile, Key, KeyCombo, ModifierSet};
+use std::collections::HashSet;
+
+/// Controls which combos `FalconKeyfile::free_combos` considers.
+#[derive(Debug, Clone, Default)]
+pub struct FreeComboOptions {
+    /// Leave out combos Windows handles itself, such as `LAlt+Tab`.
+    pub exclude_reserved: bool,
+    /// Only consider these modifier layers, all of them if empty.
+    pub layers: Vec<ModifierSet>,
+}
+
+impl FalconKeyfile {
+    /// Every combo of a named key and modifiers that no callback is bound to,
+    /// neither directly nor as the first stage of a two-stage binding. Modifier
+    /// keys themselves are never proposed.
+    pub fn free_combos(&self, options: &FreeComboOptions) -> Vec<KeyCombo> {
+        let used: HashSet<KeyCombo> = self
+            .callbacks
+            .values()
+            .flat_map(|c| [c.chord(), c.combo_chord()])
+            .flatten()
+            .collect();
+
+        let layers: Vec<ModifierSet> = if options.layers.is_empty() {
+            (0..=ModifierSet::ALL.to_mask())
+                .map(ModifierSet::from_mask)
+                .collect()
+        } else {
+            options.layers.clone()
+        };
+
+        named_keys()
+            .filter(|key| !matches!(key, Key::LShift | Key::LControl | Key::RControl))
+            .flat_map(|key| {
+                layers
+                    .iter()
+                    .map(move |modifiers| KeyCombo::new(key.clone(), *modifiers))
+            })
+            .filter(|combo| !used.contains(combo))
+            .filter(|combo| !(options.exclude_reserved && combo.is_reserved()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Modifier;
+
+    #[test]
+    fn propose_unused_combos() {
+        let keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let free = keyfile.free_combos(&FreeComboOptions::default());
+
+        assert!(!free.contains(&"LCtrl+UpArrow".parse().unwrap()));
+        assert!(!free.contains(&"LAlt+C".parse().unwrap()));
+        assert!(
+            free.iter()
+                .all(|c| keyfile.callbacks_for_combo(c).is_empty())
+        );
+        assert!(free.contains(&"LAlt+Tab".parse().unwrap()));
+
+        let options = FreeComboOptions {
+            exclude_reserved: true,
+            layers: vec![ModifierSet::from(Modifier::LALT)],
+        };
+        let free = keyfile.free_combos(&options);
+        assert!(!free.contains(&"LAlt+Tab".parse().unwrap()));
+        assert!(
+            free.iter()
+                .all(|c| c.modifiers == ModifierSet::from(Modifier::LALT))
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use crate::FalconKeyfile;
+use levenshtein::levenshtein;
+
+/// How `FalconKeyfile::fuzzy_search` compares a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FuzzyOptions {
+    pub ignore_case: bool,
+    /// Also match the query against the descriptions, taking the better of
+    /// the two scores.
+    pub descriptions: bool,
+}
+
+impl Default for FuzzyOptions {
+    fn default() -> FuzzyOptions {
+        FuzzyOptions {
+            ignore_case: true,
+            descriptions: false,
+        }
+    }
+}
+
+pub(crate) const MATCH: i32 = 16;
+/// A match at the start of a word, e.g. the `P` of `SimPilotToggle`.
+const BOUNDARY: i32 = 10;
+const CONSECUTIVE: i32 = 8;
+const GAP_START: i32 = -3;
+const GAP_EXTENSION: i32 = -1;
+
+/// Scores `text` for `query` in the style of fzf: every character of the
+/// query has to appear in order, matches at word starts and runs of
+/// consecutive matches score higher and gaps between matches lower the score.
+/// `None` if the query is not a subsequence of the text.
+pub(crate) fn fuzzy_score(query: &str, text: &str, ignore_case: bool) -> Option<u32> {
+    let query: Vec<char> = query.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let same = |a: char, b: char| a == b || (ignore_case && a.eq_ignore_ascii_case(&b));
+
+    // best[j]: the best score with the current query character at text[j]
+    let mut best: Vec<Option<i32>> = vec![None; text.len()];
+    for (i, &wanted) in query.iter().enumerate() {
+        let mut next = vec![None; text.len()];
+        for (j, &c) in text.iter().enumerate() {
+            if !same(wanted, c) {
+                continue;
+            }
+            let here = MATCH + if is_boundary(&text, j) { BOUNDARY } else { 0 };
+            next[j] = if i == 0 {
+                Some(here)
+            } else {
+                (0..j)
+                    .filter_map(|k| {
+                        let score = best[k]?;
+                        let gap = (j - k - 1) as i32;
+                        let bonus = match gap {
+                            0 => CONSECUTIVE,
+                            _ => GAP_START + GAP_EXTENSION * (gap - 1),
+                        };
+                        Some(score + here + bonus)
+                    })
+                    .max()
+            };
+        }
+        best = next;
+    }
+    best.into_iter()
+        .flatten()
+        .max()
+        .map(|score| score.max(1) as u32)
+}
+
+/// Whether `text[index]` starts a word: the first character, one after a
+/// separator or the first upper case letter or digit after a lower case one.
+fn is_boundary(text: &[char], index: usize) -> bool {
+    let Some(previous) = index.checked_sub(1).map(|i| text[i]) else {
+        return true;
+    };
+    let c = text[index];
+    !previous.is_alphanumeric()
+        || (previous.is_lowercase() && (c.is_uppercase() || c.is_numeric()))
+        || (previous.is_alphabetic() && c.is_numeric())
+}
+
+impl FalconKeyfile {
+    /// The callbacks matching `query` as `(name, score)`, best first. See
+    /// `FuzzyOptions` for matching descriptions and case.
+    pub fn fuzzy_search(&self, query: &str, options: &FuzzyOptions) -> Vec<(&str, u32)> {
+        let mut matches: Vec<_> = self
+            .callbacks
+            .values()
+            .filter_map(|c| {
+                let name = fuzzy_score(query, &c.name, options.ignore_case);
+                let description = c
+                    .description
+                    .as_deref()
+                    .filter(|_| options.descriptions)
+                    .and_then(|d| fuzzy_score(query, d, options.ignore_case));
+                Some((c.name.as_str(), name.max(description)?))
+            })
+            .collect();
+        matches.sort_by(|(a, x), (b, y)| {
+            y.cmp(x)
+                .then_with(|| a.len().cmp(&b.len()))
+                .then_with(|| a.cmp(b))
+        });
+        matches
+    }
+
+    /// Up to `count` callback names closest to `query`: fuzzy matches first,
+    /// then the rest by edit distance for queries with typos.
+    pub fn propose_callback_names(&self, query: String, count: usize) -> Vec<String> {
+        let matches = self.fuzzy_search(&query, &FuzzyOptions::default());
+        let mut rest: Vec<_> = self
+            .callbacks
+            .keys()
+            .filter(|name| !matches.iter().any(|(m, _)| m == name))
+            .collect();
+        rest.sort_by_key(|name| (levenshtein(&query, name), name.as_str()));
+
+        matches
+            .iter()
+            .map(|(name, _)| *name)
+            .chain(rest.into_iter().map(String::as_str))
+            .take(count)
+            .map(String::from)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefer_word_starts_and_runs() {
+        assert_eq!(fuzzy_score("xyz", "SimPickle", true), None);
+        assert_eq!(fuzzy_score("pick", "SimPickle", false), None);
+        assert!(fuzzy_score("pick", "SimPickle", true) > fuzzy_score("spkl", "SimPickle", true));
+        assert!(
+            fuzzy_score("hud", "SimHUDOn", true) > fuzzy_score("hud", "SimHandsUpDisplay", true)
+        );
+
+        let keyfile = FalconKeyfile::from_path("test-data/T16000M-FCS-Full.key").unwrap();
+        let matches = keyfile.fuzzy_search("pilottog", &FuzzyOptions::default());
+        assert_eq!(matches[0].0, "SimPilotToggle");
+        assert!(matches.windows(2).all(|w| w[0].1 >= w[1].1));
+        assert_eq!(
+            keyfile.propose_callback_names(String::from("SimPilotTogle"), 1),
+            vec!["SimPilotToggle"]
+        );
+    }
+}
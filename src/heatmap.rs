@@ -0,0 +1,99 @@
+use crate::{FalconKeyfile, Key, ModifierSet};
+use std::collections::HashMap;
+
+/// Number of modifier layers, one per combination of `ModifierSet::ALL`.
+const LAYERS: usize = ModifierSet::ALL.to_mask() as usize + 1;
+
+/// How many bound callbacks reference a key, per modifier layer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyUsage {
+    pub key: Key,
+    /// Counts indexed by the modifier mask of the layer.
+    pub layers: [usize; LAYERS],
+}
+
+impl KeyUsage {
+    /// References on the layer of `modifiers`.
+    pub fn count(&self, modifiers: ModifierSet) -> usize {
+        self.layers[modifiers.to_mask() as usize]
+    }
+
+    /// References on all layers together.
+    pub fn total(&self) -> usize {
+        self.layers.iter().sum()
+    }
+}
+
+/// Key usage of a keyfile, ready to be turned into a heatmap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heatmap {
+    /// Every referenced key, sorted by scan code.
+    pub keys: Vec<KeyUsage>,
+}
+
+impl Heatmap {
+    pub fn get(&self, key: &Key) -> Option<&KeyUsage> {
+        self.keys.iter().find(|usage| usage.key == *key)
+    }
+
+    /// The highest total of any key, for scaling colors.
+    pub fn max(&self) -> usize {
+        self.keys.iter().map(KeyUsage::total).max().unwrap_or(0)
+    }
+}
+
+impl FalconKeyfile {
+    /// Counts the bound callbacks per key and modifier layer. The first stage of
+    /// a two-stage binding counts as a reference to its key as well.
+    pub fn heatmap(&self) -> Heatmap {
+        let mut by_key: HashMap<Key, [usize; LAYERS]> = HashMap::new();
+        for callback in self.callbacks.values().filter(|c| c.is_bound()) {
+            for combo in [callback.chord(), callback.combo_chord()]
+                .into_iter()
+                .flatten()
+            {
+                by_key.entry(combo.key).or_insert([0; LAYERS])
+                    [combo.modifiers.to_mask() as usize] += 1;
+            }
+        }
+
+        let mut keys: Vec<_> = by_key
+            .into_iter()
+            .map(|(key, layers)| KeyUsage { key, layers })
+            .collect();
+        keys.sort_by_key(|usage| usage.key.scancode());
+        Heatmap { keys }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Key, Modifier, ModifierSet, ParseOptions, parse_str};
+
+    #[test]
+    fn count_references_per_layer() {
+        let content = "# header
+AFBrakesToggle -1 0 0x30 0 0 0 1 \"Brakes\"
+AFGearToggle -1 0 0x30 2 0 0 1 \"Gear\"
+SimHookToggle -1 0 0x30 2 0 0 1 \"Hook\"
+SimPilotToggle -1 0 0x19 0 0x2E 4 1 \"Autopilot\"
+AFFlapsToggle -1 0 0XFFFFFFFF 0 0 0 1 \"Flaps\"
+";
+        let keyfile = parse_str(String::from("inline"), content, &ParseOptions::default())
+            .unwrap()
+            .keyfile;
+        let heatmap = keyfile.heatmap();
+
+        let b = heatmap.get(&Key::B).unwrap();
+        assert_eq!(b.count(ModifierSet::EMPTY), 1);
+        assert_eq!(b.count(Modifier::LCONTROL.into()), 2);
+        assert_eq!(b.total(), 3);
+        assert_eq!(
+            heatmap.get(&Key::C).unwrap().count(Modifier::LALT.into()),
+            1
+        );
+        assert_eq!(heatmap.get(&Key::P).unwrap().total(), 1);
+        assert_eq!(heatmap.keys.len(), 3);
+        assert_eq!(heatmap.max(), 3);
+    }
+}
@@ -0,0 +1,146 @@
+//! USB HID usage IDs of the keyboard/keypad usage page (0x07).
+
+use crate::Key;
+
+/// Keys and their usage IDs.
+const USAGE_IDS: [(Key, u16); 103] = [
+    (Key::A, 0x04),
+    (Key::B, 0x05),
+    (Key::C, 0x06),
+    (Key::D, 0x07),
+    (Key::E, 0x08),
+    (Key::F, 0x09),
+    (Key::G, 0x0A),
+    (Key::H, 0x0B),
+    (Key::I, 0x0C),
+    (Key::J, 0x0D),
+    (Key::K, 0x0E),
+    (Key::L, 0x0F),
+    (Key::M, 0x10),
+    (Key::N, 0x11),
+    (Key::O, 0x12),
+    (Key::P, 0x13),
+    (Key::Q, 0x14),
+    (Key::R, 0x15),
+    (Key::S, 0x16),
+    (Key::T, 0x17),
+    (Key::U, 0x18),
+    (Key::V, 0x19),
+    (Key::W, 0x1A),
+    (Key::X, 0x1B),
+    (Key::Y, 0x1C),
+    (Key::Z, 0x1D),
+    (Key::Num1, 0x1E),
+    (Key::Num2, 0x1F),
+    (Key::Num3, 0x20),
+    (Key::Num4, 0x21),
+    (Key::Num5, 0x22),
+    (Key::Num6, 0x23),
+    (Key::Num7, 0x24),
+    (Key::Num8, 0x25),
+    (Key::Num9, 0x26),
+    (Key::Num0, 0x27),
+    (Key::Return, 0x28),
+    (Key::Escape, 0x29),
+    (Key::Backspace, 0x2A),
+    (Key::Tab, 0x2B),
+    (Key::Space, 0x2C),
+    (Key::Minus, 0x2D),
+    (Key::Equals, 0x2E),
+    (Key::LeftBracket, 0x2F),
+    (Key::RightBracket, 0x30),
+    (Key::Backslash, 0x31),
+    (Key::Semicolon, 0x33),
+    (Key::Apostrophe, 0x34),
+    (Key::BackQuote, 0x35),
+    (Key::Comma, 0x36),
+    (Key::Period, 0x37),
+    (Key::Slash, 0x38),
+    (Key::CapsLock, 0x39),
+    (Key::F1, 0x3A),
+    (Key::F2, 0x3B),
+    (Key::F3, 0x3C),
+    (Key::F4, 0x3D),
+    (Key::F5, 0x3E),
+    (Key::F6, 0x3F),
+    (Key::F7, 0x40),
+    (Key::F8, 0x41),
+    (Key::F9, 0x42),
+    (Key::F10, 0x43),
+    (Key::F11, 0x44),
+    (Key::F12, 0x45),
+    (Key::PrintScr, 0x46),
+    (Key::ScrollLock, 0x47),
+    (Key::Insert, 0x49),
+    (Key::Home, 0x4A),
+    (Key::PageUp, 0x4B),
+    (Key::Delete, 0x4C),
+    (Key::End, 0x4D),
+    (Key::PageDown, 0x4E),
+    (Key::RightArrow, 0x4F),
+    (Key::LeftArrow, 0x50),
+    (Key::DownArrow, 0x51),
+    (Key::UpArrow, 0x52),
+    (Key::Numlock, 0x53),
+    (Key::Divide, 0x54),
+    (Key::Multiply, 0x55),
+    (Key::Subtract, 0x56),
+    (Key::Add, 0x57),
+    (Key::NumpadEnter, 0x58),
+    (Key::Numpad1, 0x59),
+    (Key::Numpad2, 0x5A),
+    (Key::Numpad3, 0x5B),
+    (Key::Numpad4, 0x5C),
+    (Key::Numpad5, 0x5D),
+    (Key::Numpad6, 0x5E),
+    (Key::Numpad7, 0x5F),
+    (Key::Numpad8, 0x60),
+    (Key::Numpad9, 0x61),
+    (Key::Numpad0, 0x62),
+    (Key::Decimal, 0x63),
+    (Key::Apps, 0x65),
+    (Key::F13, 0x68),
+    (Key::F14, 0x69),
+    (Key::F15, 0x6A),
+    (Key::LControl, 0xE0),
+    (Key::LShift, 0xE1),
+    (Key::LWin, 0xE3),
+    (Key::RControl, 0xE4),
+    (Key::RWin, 0xE7),
+];
+
+impl Key {
+    /// The usage ID of the key on the keyboard/keypad page, `None` for
+    /// `Unknown` and unnamed keys.
+    pub fn to_hid_usage(&self) -> Option<u16> {
+        USAGE_IDS
+            .iter()
+            .find(|(key, _)| key == self)
+            .map(|(_, usage)| *usage)
+    }
+
+    /// The key of a usage ID on the keyboard/keypad page.
+    pub fn from_hid_usage(usage: u16) -> Option<Key> {
+        USAGE_IDS
+            .iter()
+            .find(|(_, id)| *id == usage)
+            .map(|(key, _)| key.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::named_keys;
+
+    #[test]
+    fn hid_usage_round_trip() {
+        assert_eq!(Key::A.to_hid_usage(), Some(0x04));
+        assert_eq!(Key::Return.to_hid_usage(), Some(0x28));
+        assert_eq!(Key::from_hid_usage(0x52), Some(Key::UpArrow));
+        assert_eq!(Key::from_hid_usage(0x32), None);
+        for key in named_keys() {
+            assert_eq!(key.to_hid_usage().and_then(Key::from_hid_usage), Some(key));
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Triggering callbacks by synthesizing their key presses with `enigo`.
+//!
+//! BMS reads scan codes through DirectInput, so the keys are sent as raw scan
+//! codes. That is only possible on Windows, elsewhere injecting fails with
+//! `InjectError::Unsupported`.
+
+use crate::Callback;
+use enigo::{Direction, InputError, Keyboard};
+use std::fmt;
+
+#[derive(Debug)]
+pub enum InjectError {
+    /// Sending a key event failed.
+    Input(InputError),
+    /// The platform cannot send scan codes.
+    Unsupported,
+}
+
+impl fmt::Display for InjectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InjectError::Input(error) => write!(f, "could not send key event: {}", error),
+            InjectError::Unsupported => write!(f, "scan codes can only be sent on Windows"),
+        }
+    }
+}
+
+impl std::error::Error for InjectError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InjectError::Input(error) => Some(error),
+            InjectError::Unsupported => None,
+        }
+    }
+}
+
+/// Presses and releases the keys of `callback` as BMS expects them, see
+/// `Callback::key_sequence`.
+pub fn inject(keyboard: &mut impl Keyboard, callback: &Callback) -> Result<(), InjectError> {
+    for event in callback.key_sequence() {
+        let direction = if event.pressed {
+            Direction::Press
+        } else {
+            Direction::Release
+        };
+        keyboard
+            .raw(raw_code(event.key.scancode())?, direction)
+            .map_err(InjectError::Input)?;
+    }
+    Ok(())
+}
+
+/// BMS marks extended keys with the high bit, Windows with the `EXT` bits.
+#[cfg(windows)]
+fn raw_code(scancode: u16) -> Result<u16, InjectError> {
+    if scancode & 0x80 != 0 {
+        Ok((scancode & 0x7F) | enigo::EXT)
+    } else {
+        Ok(scancode)
+    }
+}
+
+#[cfg(not(windows))]
+fn raw_code(_scancode: u16) -> Result<u16, InjectError> {
+    Err(InjectError::Unsupported)
+}
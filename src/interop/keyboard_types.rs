@@ -0,0 +1,148 @@
+use super::NoMatchingKey;
+use crate::Key;
+use keyboard_types::Code;
+
+const CODES: [(Key, Code); 103] = [
+    (Key::Escape, Code::Escape),
+    (Key::Num0, Code::Digit0),
+    (Key::Num1, Code::Digit1),
+    (Key::Num2, Code::Digit2),
+    (Key::Num3, Code::Digit3),
+    (Key::Num4, Code::Digit4),
+    (Key::Num5, Code::Digit5),
+    (Key::Num6, Code::Digit6),
+    (Key::Num7, Code::Digit7),
+    (Key::Num8, Code::Digit8),
+    (Key::Num9, Code::Digit9),
+    (Key::Minus, Code::Minus),
+    (Key::Equals, Code::Equal),
+    (Key::Backspace, Code::Backspace),
+    (Key::Tab, Code::Tab),
+    (Key::A, Code::KeyA),
+    (Key::B, Code::KeyB),
+    (Key::C, Code::KeyC),
+    (Key::D, Code::KeyD),
+    (Key::E, Code::KeyE),
+    (Key::F, Code::KeyF),
+    (Key::G, Code::KeyG),
+    (Key::H, Code::KeyH),
+    (Key::I, Code::KeyI),
+    (Key::J, Code::KeyJ),
+    (Key::K, Code::KeyK),
+    (Key::L, Code::KeyL),
+    (Key::M, Code::KeyM),
+    (Key::N, Code::KeyN),
+    (Key::O, Code::KeyO),
+    (Key::P, Code::KeyP),
+    (Key::Q, Code::KeyQ),
+    (Key::R, Code::KeyR),
+    (Key::S, Code::KeyS),
+    (Key::T, Code::KeyT),
+    (Key::U, Code::KeyU),
+    (Key::V, Code::KeyV),
+    (Key::W, Code::KeyW),
+    (Key::X, Code::KeyX),
+    (Key::Y, Code::KeyY),
+    (Key::Z, Code::KeyZ),
+    (Key::LeftBracket, Code::BracketLeft),
+    (Key::RightBracket, Code::BracketRight),
+    (Key::Return, Code::Enter),
+    (Key::LControl, Code::ControlLeft),
+    (Key::Semicolon, Code::Semicolon),
+    (Key::Apostrophe, Code::Quote),
+    (Key::BackQuote, Code::Backquote),
+    (Key::LShift, Code::ShiftLeft),
+    (Key::Backslash, Code::Backslash),
+    (Key::Comma, Code::Comma),
+    (Key::Period, Code::Period),
+    (Key::Slash, Code::Slash),
+    (Key::Multiply, Code::NumpadMultiply),
+    (Key::Space, Code::Space),
+    (Key::CapsLock, Code::CapsLock),
+    (Key::F1, Code::F1),
+    (Key::F2, Code::F2),
+    (Key::F3, Code::F3),
+    (Key::F4, Code::F4),
+    (Key::F5, Code::F5),
+    (Key::F6, Code::F6),
+    (Key::F7, Code::F7),
+    (Key::F8, Code::F8),
+    (Key::F9, Code::F9),
+    (Key::F10, Code::F10),
+    (Key::F11, Code::F11),
+    (Key::F12, Code::F12),
+    (Key::F13, Code::F13),
+    (Key::F14, Code::F14),
+    (Key::F15, Code::F15),
+    (Key::Numlock, Code::NumLock),
+    (Key::ScrollLock, Code::ScrollLock),
+    (Key::Numpad0, Code::Numpad0),
+    (Key::Numpad1, Code::Numpad1),
+    (Key::Numpad2, Code::Numpad2),
+    (Key::Numpad3, Code::Numpad3),
+    (Key::Numpad4, Code::Numpad4),
+    (Key::Numpad5, Code::Numpad5),
+    (Key::Numpad6, Code::Numpad6),
+    (Key::Numpad7, Code::Numpad7),
+    (Key::Numpad8, Code::Numpad8),
+    (Key::Numpad9, Code::Numpad9),
+    (Key::Subtract, Code::NumpadSubtract),
+    (Key::Add, Code::NumpadAdd),
+    (Key::Decimal, Code::NumpadDecimal),
+    (Key::NumpadEnter, Code::NumpadEnter),
+    (Key::RControl, Code::ControlRight),
+    (Key::Divide, Code::NumpadDivide),
+    (Key::PrintScr, Code::PrintScreen),
+    (Key::Home, Code::Home),
+    (Key::UpArrow, Code::ArrowUp),
+    (Key::PageUp, Code::PageUp),
+    (Key::LeftArrow, Code::ArrowLeft),
+    (Key::RightArrow, Code::ArrowRight),
+    (Key::End, Code::End),
+    (Key::DownArrow, Code::ArrowDown),
+    (Key::PageDown, Code::PageDown),
+    (Key::Insert, Code::Insert),
+    (Key::Delete, Code::Delete),
+    (Key::LWin, Code::MetaLeft),
+    (Key::RWin, Code::MetaRight),
+    (Key::Apps, Code::ContextMenu),
+];
+
+/// `Unknown` and unnamed keys are `Code::Unidentified`.
+impl From<Key> for Code {
+    fn from(key: Key) -> Code {
+        CODES
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map_or(Code::Unidentified, |(_, code)| *code)
+    }
+}
+
+impl TryFrom<Code> for Key {
+    type Error = NoMatchingKey;
+
+    fn try_from(code: Code) -> Result<Key, NoMatchingKey> {
+        CODES
+            .iter()
+            .find(|(_, c)| *c == code)
+            .map(|(key, _)| key.clone())
+            .ok_or(NoMatchingKey)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::named_keys;
+
+    #[test]
+    fn convert_codes() {
+        assert_eq!(Code::from(Key::UpArrow), Code::ArrowUp);
+        assert_eq!(Code::from(Key::Other(0x54)), Code::Unidentified);
+        assert_eq!(Key::try_from(Code::KeyQ), Ok(Key::Q));
+        assert_eq!(Key::try_from(Code::Fn), Err(NoMatchingKey));
+        for key in named_keys() {
+            assert_eq!(Key::try_from(Code::from(key.clone())), Ok(key));
+        }
+    }
+}
@@ -0,0 +1,20 @@
+//! Conversions between `Key` and the key codes of other crates.
+
+use std::fmt;
+
+#[cfg(feature = "keyboard-types")]
+mod keyboard_types;
+#[cfg(feature = "winit")]
+mod winit;
+
+/// The key code has no equivalent `Key`, or the `Key` no equivalent key code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoMatchingKey;
+
+impl fmt::Display for NoMatchingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no matching key")
+    }
+}
+
+impl std::error::Error for NoMatchingKey {}
@@ -0,0 +1,190 @@
+use super::NoMatchingKey;
+use crate::Key;
+use winit::keyboard::{KeyCode, NativeKeyCode, PhysicalKey};
+
+const CODES: [(Key, KeyCode); 103] = [
+    (Key::Escape, KeyCode::Escape),
+    (Key::Num0, KeyCode::Digit0),
+    (Key::Num1, KeyCode::Digit1),
+    (Key::Num2, KeyCode::Digit2),
+    (Key::Num3, KeyCode::Digit3),
+    (Key::Num4, KeyCode::Digit4),
+    (Key::Num5, KeyCode::Digit5),
+    (Key::Num6, KeyCode::Digit6),
+    (Key::Num7, KeyCode::Digit7),
+    (Key::Num8, KeyCode::Digit8),
+    (Key::Num9, KeyCode::Digit9),
+    (Key::Minus, KeyCode::Minus),
+    (Key::Equals, KeyCode::Equal),
+    (Key::Backspace, KeyCode::Backspace),
+    (Key::Tab, KeyCode::Tab),
+    (Key::A, KeyCode::KeyA),
+    (Key::B, KeyCode::KeyB),
+    (Key::C, KeyCode::KeyC),
+    (Key::D, KeyCode::KeyD),
+    (Key::E, KeyCode::KeyE),
+    (Key::F, KeyCode::KeyF),
+    (Key::G, KeyCode::KeyG),
+    (Key::H, KeyCode::KeyH),
+    (Key::I, KeyCode::KeyI),
+    (Key::J, KeyCode::KeyJ),
+    (Key::K, KeyCode::KeyK),
+    (Key::L, KeyCode::KeyL),
+    (Key::M, KeyCode::KeyM),
+    (Key::N, KeyCode::KeyN),
+    (Key::O, KeyCode::KeyO),
+    (Key::P, KeyCode::KeyP),
+    (Key::Q, KeyCode::KeyQ),
+    (Key::R, KeyCode::KeyR),
+    (Key::S, KeyCode::KeyS),
+    (Key::T, KeyCode::KeyT),
+    (Key::U, KeyCode::KeyU),
+    (Key::V, KeyCode::KeyV),
+    (Key::W, KeyCode::KeyW),
+    (Key::X, KeyCode::KeyX),
+    (Key::Y, KeyCode::KeyY),
+    (Key::Z, KeyCode::KeyZ),
+    (Key::LeftBracket, KeyCode::BracketLeft),
+    (Key::RightBracket, KeyCode::BracketRight),
+    (Key::Return, KeyCode::Enter),
+    (Key::LControl, KeyCode::ControlLeft),
+    (Key::Semicolon, KeyCode::Semicolon),
+    (Key::Apostrophe, KeyCode::Quote),
+    (Key::BackQuote, KeyCode::Backquote),
+    (Key::LShift, KeyCode::ShiftLeft),
+    (Key::Backslash, KeyCode::Backslash),
+    (Key::Comma, KeyCode::Comma),
+    (Key::Period, KeyCode::Period),
+    (Key::Slash, KeyCode::Slash),
+    (Key::Multiply, KeyCode::NumpadMultiply),
+    (Key::Space, KeyCode::Space),
+    (Key::CapsLock, KeyCode::CapsLock),
+    (Key::F1, KeyCode::F1),
+    (Key::F2, KeyCode::F2),
+    (Key::F3, KeyCode::F3),
+    (Key::F4, KeyCode::F4),
+    (Key::F5, KeyCode::F5),
+    (Key::F6, KeyCode::F6),
+    (Key::F7, KeyCode::F7),
+    (Key::F8, KeyCode::F8),
+    (Key::F9, KeyCode::F9),
+    (Key::F10, KeyCode::F10),
+    (Key::F11, KeyCode::F11),
+    (Key::F12, KeyCode::F12),
+    (Key::F13, KeyCode::F13),
+    (Key::F14, KeyCode::F14),
+    (Key::F15, KeyCode::F15),
+    (Key::Numlock, KeyCode::NumLock),
+    (Key::ScrollLock, KeyCode::ScrollLock),
+    (Key::Numpad0, KeyCode::Numpad0),
+    (Key::Numpad1, KeyCode::Numpad1),
+    (Key::Numpad2, KeyCode::Numpad2),
+    (Key::Numpad3, KeyCode::Numpad3),
+    (Key::Numpad4, KeyCode::Numpad4),
+    (Key::Numpad5, KeyCode::Numpad5),
+    (Key::Numpad6, KeyCode::Numpad6),
+    (Key::Numpad7, KeyCode::Numpad7),
+    (Key::Numpad8, KeyCode::Numpad8),
+    (Key::Numpad9, KeyCode::Numpad9),
+    (Key::Subtract, KeyCode::NumpadSubtract),
+    (Key::Add, KeyCode::NumpadAdd),
+    (Key::Decimal, KeyCode::NumpadDecimal),
+    (Key::NumpadEnter, KeyCode::NumpadEnter),
+    (Key::RControl, KeyCode::ControlRight),
+    (Key::Divide, KeyCode::NumpadDivide),
+    (Key::PrintScr, KeyCode::PrintScreen),
+    (Key::Home, KeyCode::Home),
+    (Key::UpArrow, KeyCode::ArrowUp),
+    (Key::PageUp, KeyCode::PageUp),
+    (Key::LeftArrow, KeyCode::ArrowLeft),
+    (Key::RightArrow, KeyCode::ArrowRight),
+    (Key::End, KeyCode::End),
+    (Key::DownArrow, KeyCode::ArrowDown),
+    (Key::PageDown, KeyCode::PageDown),
+    (Key::Insert, KeyCode::Insert),
+    (Key::Delete, KeyCode::Delete),
+    (Key::LWin, KeyCode::SuperLeft),
+    (Key::RWin, KeyCode::SuperRight),
+    (Key::Apps, KeyCode::ContextMenu),
+];
+
+impl TryFrom<Key> for KeyCode {
+    type Error = NoMatchingKey;
+
+    fn try_from(key: Key) -> Result<KeyCode, NoMatchingKey> {
+        CODES
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, code)| *code)
+            .ok_or(NoMatchingKey)
+    }
+}
+
+impl TryFrom<KeyCode> for Key {
+    type Error = NoMatchingKey;
+
+    fn try_from(code: KeyCode) -> Result<Key, NoMatchingKey> {
+        CODES
+            .iter()
+            .find(|(_, c)| *c == code)
+            .map(|(key, _)| key.clone())
+            .ok_or(NoMatchingKey)
+    }
+}
+
+/// Unnamed keys keep their scan code as a Windows native key code.
+impl From<Key> for PhysicalKey {
+    fn from(key: Key) -> PhysicalKey {
+        match key {
+            Key::Other(scancode) => PhysicalKey::Unidentified(NativeKeyCode::Windows(scancode)),
+            key => KeyCode::try_from(key).map_or(
+                PhysicalKey::Unidentified(NativeKeyCode::Unidentified),
+                PhysicalKey::Code,
+            ),
+        }
+    }
+}
+
+/// Windows native key codes are scan codes with an `0xE0` prefix for extended
+/// keys, which BMS writes with the high bit set instead.
+impl TryFrom<PhysicalKey> for Key {
+    type Error = NoMatchingKey;
+
+    fn try_from(key: PhysicalKey) -> Result<Key, NoMatchingKey> {
+        match key {
+            PhysicalKey::Code(code) => Key::try_from(code),
+            PhysicalKey::Unidentified(NativeKeyCode::Windows(scancode)) => {
+                let scancode = match scancode >> 8 {
+                    0 => scancode,
+                    0xE0 => (scancode & 0x7F) | 0x80,
+                    _ => return Err(NoMatchingKey),
+                };
+                Ok(Key::from_scancode(scancode))
+            }
+            PhysicalKey::Unidentified(_) => Err(NoMatchingKey),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::named_keys;
+
+    #[test]
+    fn convert_key_codes() {
+        assert_eq!(KeyCode::try_from(Key::LWin), Ok(KeyCode::SuperLeft));
+        assert_eq!(Key::try_from(KeyCode::ArrowUp), Ok(Key::UpArrow));
+        assert_eq!(
+            Key::try_from(PhysicalKey::Unidentified(NativeKeyCode::Windows(0xE048))),
+            Ok(Key::UpArrow)
+        );
+        assert_eq!(
+            PhysicalKey::from(Key::Other(0x54)),
+            PhysicalKey::Unidentified(NativeKeyCode::Windows(0x54))
+        );
+        for key in named_keys() {
+            assert_eq!(Key::try_from(PhysicalKey::from(key.clone())), Ok(key));
+        }
+    }
+}
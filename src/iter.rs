@@ -0,0 +1,101 @@
+use crate::{Callback, FalconKeyfile};
+use std::collections::hash_map::{IntoValues, Values};
+
+/// Iterator over the callbacks of a keyfile, in no particular order.
+#[derive(Debug, Clone)]
+pub struct Iter<'a>(Values<'a, String, Callback>);
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a Callback;
+
+    fn next(&mut self) -> Option<&'a Callback> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+/// Owning iterator over the callbacks of a keyfile, in no particular order.
+#[derive(Debug)]
+pub struct IntoIter(IntoValues<String, Callback>);
+
+impl Iterator for IntoIter {
+    type Item = Callback;
+
+    fn next(&mut self) -> Option<Callback> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl ExactSizeIterator for IntoIter {}
+
+impl FalconKeyfile {
+    /// The callbacks, without the `SimDoNothing` entries.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter(self.callbacks.values())
+    }
+
+    /// The number of callbacks, without the `SimDoNothing` entries.
+    pub fn len(&self) -> usize {
+        self.callbacks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.callbacks.is_empty()
+    }
+
+    pub fn contains(&self, callback_name: &str) -> bool {
+        self.callbacks.contains_key(callback_name)
+    }
+
+    /// The callback names, in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.callbacks.keys().map(String::as_str)
+    }
+}
+
+impl IntoIterator for FalconKeyfile {
+    type Item = Callback;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        IntoIter(self.callbacks.into_values())
+    }
+}
+
+impl<'a> IntoIterator for &'a FalconKeyfile {
+    type Item = &'a Callback;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FalconKeyfile;
+
+    #[test]
+    fn iterate_callbacks() {
+        let keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+
+        assert_eq!(keyfile.len(), 496);
+        assert!(!keyfile.is_empty());
+        assert!(keyfile.contains("AFElevatorTrimUp"));
+        assert!(!keyfile.contains("SimDoNothing"));
+        assert_eq!(keyfile.iter().len(), keyfile.len());
+        assert_eq!(keyfile.names().count(), keyfile.len());
+        assert!((&keyfile).into_iter().any(|c| c.name == "AFElevatorTrimUp"));
+
+        assert_eq!(keyfile.into_iter().count(), 496);
+    }
+}
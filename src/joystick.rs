@@ -0,0 +1,115 @@
+use crate::FalconKeyfile;
+use std::collections::HashMap;
+
+/// Offset BMS adds to a button number for its shifted layer.
+pub(crate) const SHIFT_OFFSET: u16 = 256;
+
+/// The DirectX input a joystick line binds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum JoystickInput {
+    /// A 0-based button, `DX1` is button 0.
+    Button(u16),
+    /// A POV hat and one of its eight directions, clockwise from 0 for up.
+    Pov { hat: u16, direction: u8 },
+}
+
+/// A callback bound to a joystick button or POV hat, such as
+/// `SimPickle 2 -1 -2 0 0x0 -1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JoystickBinding {
+    pub callback: String,
+    pub input: JoystickInput,
+    /// Buttons on the shifted layer, written as the button number plus 256.
+    pub shifted: bool,
+    pub description: Option<String>,
+    /// The header of the section the binding is listed under.
+    pub section: Option<String>,
+    /// The 1-based line the binding was parsed from.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub line: Option<usize>,
+}
+
+/// `DX3`, `shifted DX3` or `POV1 direction 2`, numbered from 1 as in the
+/// BMS setup UI.
+pub(crate) fn input_name(input: JoystickInput, shifted: bool) -> String {
+    let shifted = if shifted { "shifted " } else { "" };
+    match input {
+        JoystickInput::Button(button) => format!("{}DX{}", shifted, button + 1),
+        JoystickInput::Pov { hat, direction } => {
+            format!("{}POV{} direction {}", shifted, hat + 1, direction)
+        }
+    }
+}
+
+/// What one physical button or POV direction does on each layer, see
+/// `FalconKeyfile::joystick_layers`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputLayers<'a> {
+    pub input: JoystickInput,
+    /// Bindings in file order, usually at most one.
+    pub unshifted: Vec<&'a JoystickBinding>,
+    /// Bindings while the shift callback, such as `SimHotasPinkyShift`, is held.
+    pub shifted: Vec<&'a JoystickBinding>,
+}
+
+impl FalconKeyfile {
+    /// The joystick bindings in file order.
+    pub fn joystick_bindings(&self) -> &[JoystickBinding] {
+        &self.joystick
+    }
+
+    /// The joystick bindings grouped by physical input, buttons by number
+    /// before POV hats. `SimDoNothing` placeholders are left out,
+    /// and with them inputs that have nothing else bound.
+    pub fn joystick_layers(&self) -> Vec<InputLayers<'_>> {
+        let mut by_input: HashMap<JoystickInput, InputLayers<'_>> = HashMap::new();
+        for binding in self
+            .joystick
+            .iter()
+            .filter(|b| b.callback != "SimDoNothing")
+        {
+            let layers = by_input
+                .entry(binding.input)
+                .or_insert_with(|| InputLayers {
+                    input: binding.input,
+                    unshifted: vec![],
+                    shifted: vec![],
+                });
+            if binding.shifted {
+                layers.shifted.push(binding);
+            } else {
+                layers.unshifted.push(binding);
+            }
+        }
+        let mut layers: Vec<_> = by_input.into_values().collect();
+        layers.sort_by_key(|l| match l.input {
+            JoystickInput::Button(button) => (0, button, 0),
+            JoystickInput::Pov { hat, direction } => (1, hat, direction),
+        });
+        layers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn group_buttons_by_layer() {
+        let keyfile = FalconKeyfile::from_path("test-data/T16000M-FCS-Full.key").unwrap();
+        let layers = keyfile.joystick_layers();
+        let button = &layers[1];
+        assert_eq!(button.input, JoystickInput::Button(1));
+        assert_eq!(button.unshifted[0].callback, "SimTriggerFirstDetent");
+        assert_eq!(button.shifted[0].callback, "AFResetTrim");
+        assert!(layers[0].shifted.is_empty());
+
+        assert!(!layers.iter().any(|l| l.input
+            == JoystickInput::Pov {
+                hat: 0,
+                direction: 1
+            }));
+    }
+}
@@ -0,0 +1,504 @@
+use std::cmp::Ordering;
+
+/// Keys that are used in falcon bms key files
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Key {
+    Unknown,
+    Escape,
+    Num1,
+    Num2,
+    Num3,
+    Num4,
+    Num5,
+    Num6,
+    Num7,
+    Num8,
+    Num9,
+    Num0,
+    Minus,
+    Equals,
+    Backspace,
+    Tab,
+    Q,
+    W,
+    E,
+    R,
+    T,
+    Y,
+    U,
+    I,
+    O,
+    P,
+    LeftBracket,
+    RightBracket,
+    Return,
+    LControl,
+    A,
+    S,
+    D,
+    F,
+    G,
+    H,
+    J,
+    K,
+    L,
+    Semicolon,
+    Apostrophe,
+    BackQuote,
+    LShift,
+    Backslash,
+    Z,
+    X,
+    C,
+    V,
+    B,
+    N,
+    M,
+    Comma,
+    Period,
+    Slash,
+    Multiply,
+    Space,
+    CapsLock,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    Numlock,
+    ScrollLock,
+    Numpad7,
+    Numpad8,
+    Numpad9,
+    Subtract,
+    Numpad4,
+    Numpad5,
+    Numpad6,
+    Add,
+    Numpad1,
+    Numpad2,
+    Numpad3,
+    Numpad0,
+    Decimal,
+    F11,
+    F12,
+    F13,
+    F14,
+    F15,
+    NumpadEnter,
+    RControl,
+    Divide,
+    PrintScr,
+    Home,
+    UpArrow,
+    PageUp,
+    LeftArrow,
+    RightArrow,
+    End,
+    DownArrow,
+    PageDown,
+    Insert,
+    Delete,
+    LWin,
+    RWin,
+    Apps,
+    /// A scan code without a named key, kept so it can still be shown and written back.
+    Other(u16),
+}
+
+impl Key {
+    /// Every named key, in scan code order. `Unknown` and `Other` are not
+    /// included.
+    pub const ALL: [Key; 103] = [
+        Key::Escape,
+        Key::Num1,
+        Key::Num2,
+        Key::Num3,
+        Key::Num4,
+        Key::Num5,
+        Key::Num6,
+        Key::Num7,
+        Key::Num8,
+        Key::Num9,
+        Key::Num0,
+        Key::Minus,
+        Key::Equals,
+        Key::Backspace,
+        Key::Tab,
+        Key::Q,
+        Key::W,
+        Key::E,
+        Key::R,
+        Key::T,
+        Key::Y,
+        Key::U,
+        Key::I,
+        Key::O,
+        Key::P,
+        Key::LeftBracket,
+        Key::RightBracket,
+        Key::Return,
+        Key::LControl,
+        Key::A,
+        Key::S,
+        Key::D,
+        Key::F,
+        Key::G,
+        Key::H,
+        Key::J,
+        Key::K,
+        Key::L,
+        Key::Semicolon,
+        Key::Apostrophe,
+        Key::BackQuote,
+        Key::LShift,
+        Key::Backslash,
+        Key::Z,
+        Key::X,
+        Key::C,
+        Key::V,
+        Key::B,
+        Key::N,
+        Key::M,
+        Key::Comma,
+        Key::Period,
+        Key::Slash,
+        Key::Multiply,
+        Key::Space,
+        Key::CapsLock,
+        Key::F1,
+        Key::F2,
+        Key::F3,
+        Key::F4,
+        Key::F5,
+        Key::F6,
+        Key::F7,
+        Key::F8,
+        Key::F9,
+        Key::F10,
+        Key::Numlock,
+        Key::ScrollLock,
+        Key::Numpad7,
+        Key::Numpad8,
+        Key::Numpad9,
+        Key::Subtract,
+        Key::Numpad4,
+        Key::Numpad5,
+        Key::Numpad6,
+        Key::Add,
+        Key::Numpad1,
+        Key::Numpad2,
+        Key::Numpad3,
+        Key::Numpad0,
+        Key::Decimal,
+        Key::F11,
+        Key::F12,
+        Key::F13,
+        Key::F14,
+        Key::F15,
+        Key::NumpadEnter,
+        Key::RControl,
+        Key::Divide,
+        Key::PrintScr,
+        Key::Home,
+        Key::UpArrow,
+        Key::PageUp,
+        Key::LeftArrow,
+        Key::RightArrow,
+        Key::End,
+        Key::DownArrow,
+        Key::PageDown,
+        Key::Insert,
+        Key::Delete,
+        Key::LWin,
+        Key::RWin,
+        Key::Apps,
+    ];
+
+    /// The key for a DirectInput scan code as used in key files.
+    pub fn from_scancode(number: u16) -> Key {
+        match number {
+            // these are maric unicorns in keyfiles
+            0xFFFF => Key::Unknown,
+            0 => Key::Unknown,
+            // normal ones start here
+            1 => Key::Escape,
+            2 => Key::Num1,
+            3 => Key::Num2,
+            4 => Key::Num3,
+            5 => Key::Num4,
+            6 => Key::Num5,
+            7 => Key::Num6,
+            8 => Key::Num7,
+            9 => Key::Num8,
+            10 => Key::Num9,
+            11 => Key::Num0,
+            12 => Key::Minus,
+            13 => Key::Equals,
+            14 => Key::Backspace,
+            15 => Key::Tab,
+            16 => Key::Q,
+            17 => Key::W,
+            18 => Key::E,
+            19 => Key::R,
+            20 => Key::T,
+            21 => Key::Y,
+            22 => Key::U,
+            23 => Key::I,
+            24 => Key::O,
+            25 => Key::P,
+            26 => Key::LeftBracket,
+            27 => Key::RightBracket,
+            28 => Key::Return,
+            29 => Key::LControl,
+            30 => Key::A,
+            31 => Key::S,
+            32 => Key::D,
+            33 => Key::F,
+            34 => Key::G,
+            35 => Key::H,
+            36 => Key::J,
+            37 => Key::K,
+            38 => Key::L,
+            39 => Key::Semicolon,
+            40 => Key::Apostrophe,
+            41 => Key::BackQuote,
+            42 => Key::LShift,
+            43 => Key::Backslash,
+            44 => Key::Z,
+            45 => Key::X,
+            46 => Key::C,
+            47 => Key::V,
+            48 => Key::B,
+            49 => Key::N,
+            50 => Key::M,
+            51 => Key::Comma,
+            52 => Key::Period,
+            53 => Key::Slash,
+            55 => Key::Multiply,
+            57 => Key::Space,
+            58 => Key::CapsLock,
+            59 => Key::F1,
+            60 => Key::F2,
+            61 => Key::F3,
+            62 => Key::F4,
+            63 => Key::F5,
+            64 => Key::F6,
+            65 => Key::F7,
+            66 => Key::F8,
+            67 => Key::F9,
+            68 => Key::F10,
+            69 => Key::Numlock,
+            70 => Key::ScrollLock,
+            71 => Key::Numpad7,
+            72 => Key::Numpad8,
+            73 => Key::Numpad9,
+            74 => Key::Subtract,
+            75 => Key::Numpad4,
+            76 => Key::Numpad5,
+            77 => Key::Numpad6,
+            78 => Key::Add,
+            79 => Key::Numpad1,
+            80 => Key::Numpad2,
+            81 => Key::Numpad3,
+            82 => Key::Numpad0,
+            83 => Key::Decimal,
+            87 => Key::F11,
+            88 => Key::F12,
+            100 => Key::F13,
+            101 => Key::F14,
+            102 => Key::F15,
+            156 => Key::NumpadEnter,
+            157 => Key::RControl,
+            181 => Key::Divide,
+            183 => Key::PrintScr,
+            199 => Key::Home,
+            200 => Key::UpArrow,
+            201 => Key::PageUp,
+            203 => Key::LeftArrow,
+            205 => Key::RightArrow,
+            207 => Key::End,
+            208 => Key::DownArrow,
+            209 => Key::PageDown,
+            210 => Key::Insert,
+            211 => Key::Delete,
+            219 => Key::LWin,
+            220 => Key::RWin,
+            221 => Key::Apps,
+            e => Key::Other(e),
+        }
+    }
+
+    /// The DirectInput scan code of this key, the inverse of `from_scancode`.
+    /// `Unknown` maps to the 0xFFFF that key files use for unbound callbacks.
+    pub fn scancode(&self) -> u16 {
+        match self {
+            Key::Unknown => 0xFFFF,
+            Key::Escape => 1,
+            Key::Num1 => 2,
+            Key::Num2 => 3,
+            Key::Num3 => 4,
+            Key::Num4 => 5,
+            Key::Num5 => 6,
+            Key::Num6 => 7,
+            Key::Num7 => 8,
+            Key::Num8 => 9,
+            Key::Num9 => 10,
+            Key::Num0 => 11,
+            Key::Minus => 12,
+            Key::Equals => 13,
+            Key::Backspace => 14,
+            Key::Tab => 15,
+            Key::Q => 16,
+            Key::W => 17,
+            Key::E => 18,
+            Key::R => 19,
+            Key::T => 20,
+            Key::Y => 21,
+            Key::U => 22,
+            Key::I => 23,
+            Key::O => 24,
+            Key::P => 25,
+            Key::LeftBracket => 26,
+            Key::RightBracket => 27,
+            Key::Return => 28,
+            Key::LControl => 29,
+            Key::A => 30,
+            Key::S => 31,
+            Key::D => 32,
+            Key::F => 33,
+            Key::G => 34,
+            Key::H => 35,
+            Key::J => 36,
+            Key::K => 37,
+            Key::L => 38,
+            Key::Semicolon => 39,
+            Key::Apostrophe => 40,
+            Key::BackQuote => 41,
+            Key::LShift => 42,
+            Key::Backslash => 43,
+            Key::Z => 44,
+            Key::X => 45,
+            Key::C => 46,
+            Key::V => 47,
+            Key::B => 48,
+            Key::N => 49,
+            Key::M => 50,
+            Key::Comma => 51,
+            Key::Period => 52,
+            Key::Slash => 53,
+            Key::Multiply => 55,
+            Key::Space => 57,
+            Key::CapsLock => 58,
+            Key::F1 => 59,
+            Key::F2 => 60,
+            Key::F3 => 61,
+            Key::F4 => 62,
+            Key::F5 => 63,
+            Key::F6 => 64,
+            Key::F7 => 65,
+            Key::F8 => 66,
+            Key::F9 => 67,
+            Key::F10 => 68,
+            Key::Numlock => 69,
+            Key::ScrollLock => 70,
+            Key::Numpad7 => 71,
+            Key::Numpad8 => 72,
+            Key::Numpad9 => 73,
+            Key::Subtract => 74,
+            Key::Numpad4 => 75,
+            Key::Numpad5 => 76,
+            Key::Numpad6 => 77,
+            Key::Add => 78,
+            Key::Numpad1 => 79,
+            Key::Numpad2 => 80,
+            Key::Numpad3 => 81,
+            Key::Numpad0 => 82,
+            Key::Decimal => 83,
+            Key::F11 => 87,
+            Key::F12 => 88,
+            Key::F13 => 100,
+            Key::F14 => 101,
+            Key::F15 => 102,
+            Key::NumpadEnter => 156,
+            Key::RControl => 157,
+            Key::Divide => 181,
+            Key::PrintScr => 183,
+            Key::Home => 199,
+            Key::UpArrow => 200,
+            Key::PageUp => 201,
+            Key::LeftArrow => 203,
+            Key::RightArrow => 205,
+            Key::End => 207,
+            Key::DownArrow => 208,
+            Key::PageDown => 209,
+            Key::Insert => 210,
+            Key::Delete => 211,
+            Key::LWin => 219,
+            Key::RWin => 220,
+            Key::Apps => 221,
+            Key::Other(number) => *number,
+        }
+    }
+}
+
+/// Keys sort by scan code, `Unknown` last. An `Other` sorts after the named key
+/// with the same scan code.
+impl Ord for Key {
+    fn cmp(&self, other: &Key) -> Ordering {
+        (self.scancode(), matches!(self, Key::Other(_)))
+            .cmp(&(other.scancode(), matches!(other, Key::Other(_))))
+    }
+}
+
+impl PartialOrd for Key {
+    fn partial_cmp(&self, other: &Key) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Every named key, in scan code order.
+pub(crate) fn named_keys() -> impl Iterator<Item = Key> {
+    Key::ALL.into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scancodes_round_trip() {
+        for number in 1..=0xFF {
+            let key = Key::from_scancode(number);
+            if key != Key::Unknown {
+                assert_eq!(key.scancode(), number, "{:?}", key);
+            }
+        }
+        assert_eq!(Key::from_scancode(0xC8), Key::UpArrow);
+        assert_eq!(Key::UpArrow.scancode(), 0xC8);
+        assert_eq!(Key::from_scancode(0xFFFF).scancode(), 0xFFFF);
+    }
+
+    #[test]
+    fn list_and_order_keys() {
+        let named: Vec<_> = (1..=0xFF)
+            .map(Key::from_scancode)
+            .filter(|key| !matches!(key, Key::Unknown | Key::Other(_)))
+            .collect();
+        assert_eq!(Key::ALL.to_vec(), named);
+        assert!(Key::ALL.is_sorted());
+
+        let mut keys = vec![Key::Unknown, Key::Other(0x10), Key::B, Key::Q, Key::Escape];
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![Key::Escape, Key::Q, Key::Other(0x10), Key::B, Key::Unknown]
+        );
+    }
+}
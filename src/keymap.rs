@@ -0,0 +1,84 @@
+use crate::{Callback, FalconKeyfile, Key, KeyCombo, ModifierSet};
+use std::collections::HashMap;
+
+/// Something a key triggers when pressed with `modifiers`, see
+/// `FalconKeyfile::key_map`.
+#[derive(Debug, Clone)]
+pub struct KeyTrigger<'a> {
+    pub modifiers: ModifierSet,
+    /// The chord that has to be pressed first when the key is the second
+    /// stage of a two-stage binding.
+    pub after: Option<KeyCombo>,
+    pub callback: &'a Callback,
+}
+
+impl FalconKeyfile {
+    /// Everything each key triggers, both as a chord of its own and as the
+    /// second stage of a two-stage binding. Keys that trigger nothing are
+    /// left out. Each list is sorted by modifier mask, single chords first,
+    /// then by callback name.
+    pub fn key_map(&self) -> HashMap<Key, Vec<KeyTrigger<'_>>> {
+        let mut map: HashMap<Key, Vec<KeyTrigger<'_>>> = HashMap::new();
+        for callback in self.callbacks.values() {
+            let Some(chord) = callback.chord() else {
+                continue;
+            };
+            map.entry(chord.key).or_default().push(KeyTrigger {
+                modifiers: chord.modifiers,
+                after: callback.combo_chord(),
+                callback,
+            });
+        }
+        for triggers in map.values_mut() {
+            triggers.sort_by(|a, b| {
+                a.modifiers
+                    .to_mask()
+                    .cmp(&b.modifiers.to_mask())
+                    .then_with(|| a.after.is_some().cmp(&b.after.is_some()))
+                    .then_with(|| a.callback.name.cmp(&b.callback.name))
+            });
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Key, ModifierSet, ParseOptions, parse_str};
+
+    #[test]
+    fn map_keys_to_what_they_trigger() {
+        let content = "# header
+AFBrakesToggle -1 0 0x19 0 0 0 1 \"Brakes\"
+SimPilotToggle -1 0 0x19 0 0x2E 4 1 \"Autopilot\"
+SimHookToggle -1 0 0x2E 4 0 0 1 \"Hook\"
+AFGearToggle -1 0 0x19 2 0 0 1 \"Gear\"
+AFFlapsToggle -1 0 0XFFFFFFFF 0 0 0 1 \"Flaps\"
+";
+        let keyfile = parse_str(String::from("inline"), content, &ParseOptions::default())
+            .unwrap()
+            .keyfile;
+        let map = keyfile.key_map();
+        assert_eq!(map.len(), 2);
+
+        let p: Vec<_> = map[&Key::P]
+            .iter()
+            .map(|t| {
+                (
+                    t.callback.name.as_str(),
+                    t.after.as_ref().map(|c| c.to_string()),
+                )
+            })
+            .collect();
+        assert_eq!(
+            p,
+            vec![
+                ("AFBrakesToggle", None),
+                ("SimPilotToggle", Some(String::from("LAlt+C"))),
+                ("AFGearToggle", None),
+            ]
+        );
+        assert_eq!(map[&Key::P][2].modifiers, ModifierSet::from_mask(2));
+        assert_eq!(map[&Key::C][0].callback.name, "SimHookToggle");
+    }
+}
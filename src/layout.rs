@@ -0,0 +1,152 @@
+use crate::Key;
+use crate::combo::key_name;
+use std::collections::HashMap;
+
+/// What is printed on the keys of a physical keyboard. BMS key codes are
+/// scan codes, so `Key::Y` is the key labelled `Z` on a German keyboard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyboardLayout {
+    name: String,
+    /// Labels differing from the US layout.
+    labels: HashMap<Key, String>,
+}
+
+impl Default for KeyboardLayout {
+    fn default() -> KeyboardLayout {
+        KeyboardLayout::qwerty()
+    }
+}
+
+impl KeyboardLayout {
+    /// The US layout the `Key` names follow.
+    pub fn qwerty() -> KeyboardLayout {
+        KeyboardLayout::custom("QWERTY", [])
+    }
+
+    /// The German layout.
+    pub fn qwertz() -> KeyboardLayout {
+        KeyboardLayout::custom(
+            "QWERTZ",
+            [
+                (Key::Y, "Z"),
+                (Key::Z, "Y"),
+                (Key::Minus, "ß"),
+                (Key::Equals, "´"),
+                (Key::LeftBracket, "Ü"),
+                (Key::RightBracket, "+"),
+                (Key::Semicolon, "Ö"),
+                (Key::Apostrophe, "Ä"),
+                (Key::BackQuote, "^"),
+                (Key::Backslash, "#"),
+                (Key::Slash, "-"),
+            ],
+        )
+    }
+
+    /// The French layout, digits as they are typed without shift.
+    pub fn azerty() -> KeyboardLayout {
+        KeyboardLayout::custom(
+            "AZERTY",
+            [
+                (Key::Q, "A"),
+                (Key::A, "Q"),
+                (Key::W, "Z"),
+                (Key::Z, "W"),
+                (Key::Semicolon, "M"),
+                (Key::M, ","),
+                (Key::Comma, ";"),
+                (Key::Period, ":"),
+                (Key::Slash, "!"),
+                (Key::Num1, "&"),
+                (Key::Num2, "é"),
+                (Key::Num3, "\""),
+                (Key::Num4, "'"),
+                (Key::Num5, "("),
+                (Key::Num6, "-"),
+                (Key::Num7, "è"),
+                (Key::Num8, "_"),
+                (Key::Num9, "ç"),
+                (Key::Num0, "à"),
+                (Key::Minus, ")"),
+                (Key::LeftBracket, "^"),
+                (Key::RightBracket, "$"),
+                (Key::Apostrophe, "ù"),
+                (Key::BackQuote, "²"),
+                (Key::Backslash, "*"),
+            ],
+        )
+    }
+
+    /// A layout with `labels` for the keys that differ from the US layout.
+    pub fn custom<'a>(
+        name: &str,
+        labels: impl IntoIterator<Item = (Key, &'a str)>,
+    ) -> KeyboardLayout {
+        KeyboardLayout {
+            name: String::from(name),
+            labels: labels
+                .into_iter()
+                .map(|(key, label)| (key, String::from(label)))
+                .collect(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Key {
+    /// What is printed on the key in `layout`. Keys without a character keep
+    /// their name, such as `UpArrow`.
+    pub fn label(&self, layout: &KeyboardLayout) -> String {
+        if let Some(label) = layout.labels.get(self) {
+            return label.clone();
+        }
+        let label = match self {
+            Key::Num1 => "1",
+            Key::Num2 => "2",
+            Key::Num3 => "3",
+            Key::Num4 => "4",
+            Key::Num5 => "5",
+            Key::Num6 => "6",
+            Key::Num7 => "7",
+            Key::Num8 => "8",
+            Key::Num9 => "9",
+            Key::Num0 => "0",
+            Key::Minus => "-",
+            Key::Equals => "=",
+            Key::LeftBracket => "[",
+            Key::RightBracket => "]",
+            Key::Semicolon => ";",
+            Key::Apostrophe => "'",
+            Key::BackQuote => "`",
+            Key::Backslash => "\\",
+            Key::Comma => ",",
+            Key::Period => ".",
+            Key::Slash => "/",
+            key => return key_name(key),
+        };
+        String::from(label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn label_keys_per_layout() {
+        let qwertz = KeyboardLayout::qwertz();
+        assert_eq!(Key::Y.label(&qwertz), "Z");
+        assert_eq!(Key::Semicolon.label(&qwertz), "Ö");
+        assert_eq!(Key::Semicolon.label(&KeyboardLayout::qwerty()), ";");
+        assert_eq!(Key::Q.label(&KeyboardLayout::azerty()), "A");
+        assert_eq!(Key::UpArrow.label(&qwertz), "UpArrow");
+
+        let custom = KeyboardLayout::custom("Dvorak", [(Key::Q, "'")]);
+        assert_eq!(custom.name(), "Dvorak");
+        assert_eq!(Key::Q.label(&custom), "'");
+        assert_eq!(Key::W.label(&custom), "W");
+    }
+}
@@ -1,83 +1,369 @@
-use levenshtein::levenshtein;
-use log::*;
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 
-pub fn parse(name: String, key_file: &'_ File) -> Result<FalconKeyfile, KeyFileError<'_>> {
-    let reader = BufReader::new(key_file);
-    let mut lines = reader.lines();
+mod aliases;
+mod binding;
+mod builder;
+#[cfg(feature = "callback-db")]
+mod callback_db;
+mod canonical;
+mod category;
+mod combo;
+mod compact;
+mod config;
+mod conflicts;
+mod coverage;
+mod customization;
+mod devices;
+mod diagnostics;
+mod diff;
+mod discover;
+mod edit;
+mod editor;
+mod encoding;
+mod events;
+pub mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod fingerprint;
+mod format;
+mod free_combos;
+mod fuzzy;
+mod heatmap;
+mod hid;
+#[cfg(feature = "inject")]
+pub mod inject;
+#[cfg(any(feature = "keyboard-types", feature = "winit"))]
+mod interop;
+mod iter;
+mod joystick;
+mod key;
+mod keymap;
+mod layout;
+pub mod lint;
+mod merge;
+mod metadata;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod modifier;
+mod parser;
+mod profile;
+mod query;
+mod resolver;
+#[cfg(feature = "rhai")]
+mod script;
+mod search;
+mod stats;
+mod synonyms;
+mod systems;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+mod transaction;
+#[cfg(feature = "callback-db")]
+mod versions;
+mod vk;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "watch")]
+mod watch;
+mod workspace;
 
-    if lines.next().is_none() {
-        return Err(KeyFileError::Empty);
-    }
-
-    let mut ln = 0;
-    let mut keycodes_by_callback: HashMap<String, Callback> = HashMap::new();
-
-    for line in lines {
-        ln += 1;
-        let line = line.map_err(KeyFileError::ReadError)?;
-        if line.is_empty() {
-            continue;
-        }
-
-        if !(line.starts_with("#") || line.starts_with("SimDoNothing")) {
-            let stuff: Vec<&str> = line.split_whitespace().collect();
-            trace!("Parsing line {}, tokens: {:?}", ln, stuff);
-
-            // an actual key callback
-            let callback_name = stuff[0];
-            if callback_name != "SimDoNothing" {
-                let error = format!(
-                    "Expected key identifier to be u32 on line {} but was {}",
-                    ln, stuff[2]
-                );
-                let is_key: i64 = stuff[2].parse().expect(&error);
-                if is_key == 0 {
-                    let callback = Callback {
-                        name: String::from(callback_name),
-                        key_code: convert_number(stuff[3]),
-                        readable_key_code: parse_key_code(convert_number(stuff[3])),
-                        modifiers: parse_modifiers(convert_number(stuff[4])),
-                        combo_key_code: convert_number(stuff[5]),
-                        readable_combo_key_code: parse_key_code(convert_number(stuff[5])),
-                        combo_modifiers: parse_modifiers(convert_number(stuff[6])),
-                    };
-                    trace!("Parsed callback: {:?}", callback);
-                    keycodes_by_callback.insert(String::from(callback_name), callback);
-                }
-            }
-        }
-    }
-
-    debug!(
-        "Parsed key file with {} callbacks.",
-        keycodes_by_callback.keys().count()
-    );
-
-    Ok(FalconKeyfile::new(name, keycodes_by_callback))
-}
+pub use binding::Binding;
+pub use builder::{BuildError, CallbackBuilder, CallbackError, KeyfileBuilder};
+#[cfg(feature = "callback-db")]
+pub use callback_db::{KnownCallback, KnownCallbacks};
+pub use category::Category;
+pub use combo::{KeyCombo, ParseComboError};
+pub use compact::{CompactCallback, CompactKeyfile, Interner};
+pub use config::{BmsConfig, ConfigEntry};
+pub use conflicts::Conflict;
+pub use coverage::{CategoryCoverage, Coverage};
+pub use customization::Customizations;
+pub use devices::{BUTTONS_PER_DEVICE, Device, DeviceSorting};
+pub use diagnostics::{Diagnostic, Diagnostics, Level, LogDiagnostics};
+pub use diff::{CallbackChange, KeyfileDiff};
+pub use discover::{BmsInstallation, DiscoverError};
+#[cfg(windows)]
+pub use discover::{discover, installations};
+pub use edit::{EditError, RemapMode, RemapReport};
+pub use editor::KeyfileEditor;
+pub use events::KeyEvent;
+pub use fingerprint::Fingerprint;
+pub use format::ChordFormat;
+pub use free_combos::FreeComboOptions;
+pub use fuzzy::FuzzyOptions;
+pub use heatmap::{Heatmap, KeyUsage};
+#[cfg(any(feature = "keyboard-types", feature = "winit"))]
+pub use interop::NoMatchingKey;
+pub use iter::{IntoIter, Iter};
+pub use joystick::{InputLayers, JoystickBinding, JoystickInput};
+pub use key::Key;
+pub use keymap::KeyTrigger;
+pub use layout::KeyboardLayout;
+pub use merge::{
+    JoystickConflict, JoystickResolution, MergeConflict, MergeError, MergeOutcome, MergeResolution,
+    MergeStrategy, OverlayReport, ThreeWayConflict, ThreeWayMergeError, ThreeWayOutcome,
+};
+pub use metadata::Metadata;
+#[cfg(feature = "mmap")]
+pub use mmap::MappedKeyfile;
+pub use modifier::{Modifier, ModifierSet};
+#[cfg(feature = "tokio")]
+pub use parser::parse_async;
+pub use parser::{
+    Dialect, LineError, ParseOptions, ParseReport, ParseWarning, PartialParse, RawBinding, parse,
+    parse_partial, parse_reader, parse_str, parse_str_with, raw_bindings,
+};
+pub use profile::{EffectiveBinding, Profile};
+pub use query::Query;
+pub use resolver::{Resolution, Resolver};
+#[cfg(feature = "rhai")]
+pub use script::ScriptError;
+pub use stats::KeyfileStats;
+pub use synonyms::Synonyms;
+pub use systems::CockpitSystem;
+pub use transaction::{Edit, Transaction, TransactionError};
+#[cfg(feature = "callback-db")]
+pub use versions::{
+    BmsVersion, DeprecatedCallback, MigrationIssue, MigrationReport, ValidationIssue, VersionGuess,
+};
+#[cfg(feature = "watch")]
+pub use watch::{KeyfileWatcher, Snapshot, WatchError};
+#[cfg(feature = "rayon")]
+pub use workspace::parse_all;
+pub use workspace::{Workspace, WorkspaceScan};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "KeyfileFields"))]
 pub struct FalconKeyfile {
     name: String,
     callbacks: HashMap<String, Callback>,
+    do_nothing: Vec<Callback>,
+    joystick: Vec<JoystickBinding>,
+    /// Section headers in file order.
+    sections: Vec<String>,
+    /// Names of the bound callbacks by their primary combo, kept in sync with `callbacks`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    by_combo: HashMap<KeyCombo, Vec<String>>,
+    /// Whether `callback` falls back to `callback_ignore_case`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    ignore_case: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    metadata: Metadata,
+    /// Alternative callback names registered with `register_alias`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    aliases: HashMap<String, String>,
+}
+
+/// The serialized fields of a `FalconKeyfile`, the index is rebuilt on deserialization.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct KeyfileFields {
+    name: String,
+    callbacks: HashMap<String, Callback>,
+    do_nothing: Vec<Callback>,
+    #[serde(default)]
+    joystick: Vec<JoystickBinding>,
+    #[serde(default)]
+    sections: Vec<String>,
+}
+
+#[cfg(feature = "serde")]
+impl From<KeyfileFields> for FalconKeyfile {
+    fn from(fields: KeyfileFields) -> FalconKeyfile {
+        let mut keyfile = FalconKeyfile::new(fields.name, fields.callbacks);
+        keyfile.do_nothing = fields.do_nothing;
+        keyfile.joystick = fields.joystick;
+        keyfile.sections = fields.sections;
+        keyfile
+    }
 }
 
 impl FalconKeyfile {
+    /// Opens and parses the key file at `path` with the default options,
+    /// naming it after the file.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<FalconKeyfile, PathError> {
+        let path = path.as_ref();
+        let in_file = |error| PathError {
+            path: path.to_path_buf(),
+            error,
+        };
+
+        let file = File::open(path).map_err(|e| {
+            in_file(match e.kind() {
+                ErrorKind::NotFound => KeyFileError::NotFound,
+                ErrorKind::PermissionDenied => KeyFileError::PermissionDenied,
+                _ => KeyFileError::ReadError(e),
+            })
+        })?;
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+
+        let modified = file.metadata().and_then(|m| m.modified()).ok();
+        let mut keyfile = parse(name, &file, &ParseOptions::default())
+            .map(|report| report.keyfile)
+            .map_err(in_file)?;
+        keyfile.metadata.path = Some(path.to_path_buf());
+        keyfile.metadata.modified = modified;
+        #[cfg(feature = "callback-db")]
+        {
+            keyfile.metadata.version = keyfile.detect_version().map(|guess| guess.version);
+        }
+        Ok(keyfile)
+    }
+
     pub fn new(name: String, keycodes_by_callback: HashMap<String, Callback>) -> FalconKeyfile {
-        FalconKeyfile {
+        let mut keyfile = FalconKeyfile {
             name,
             callbacks: keycodes_by_callback,
+            do_nothing: vec![],
+            joystick: vec![],
+            sections: vec![],
+            by_combo: HashMap::new(),
+            ignore_case: false,
+            metadata: Metadata::default(),
+            aliases: HashMap::new(),
+        };
+        keyfile.reindex();
+        keyfile
+    }
+
+    fn reindex(&mut self) {
+        self.by_combo.clear();
+        for callback in self.callbacks.values().filter(|c| c.is_bound()) {
+            self.by_combo
+                .entry(callback.primary_combo())
+                .or_default()
+                .push(callback.name.clone());
+        }
+        for names in self.by_combo.values_mut() {
+            names.sort();
         }
     }
 
+    /// Callbacks whose last chord is exactly this key and modifiers, sorted by
+    /// name. This includes two-stage bindings ending with it, so `P` also
+    /// returns a callback bound to `LAlt+C, P`; tell them apart with
+    /// `secondary_combo`.
+    pub fn callbacks_for_combo(&self, combo: &KeyCombo) -> Vec<&Callback> {
+        self.by_combo
+            .get(combo)
+            .into_iter()
+            .flatten()
+            .filter_map(|name| self.callbacks.get(name))
+            .collect()
+    }
+
+    /// Callbacks whose binding involves exactly this chord, sorted by name:
+    /// single chords, the second keystroke of two-stage bindings and the first
+    /// keystroke of two-stage bindings.
+    pub fn find_by_chord(&self, chord: &KeyCombo) -> Vec<&Callback> {
+        let mut callbacks = self.callbacks_for_combo(chord);
+        callbacks.extend(
+            self.callbacks
+                .values()
+                .filter(|c| c.combo_chord().as_ref() == Some(chord)),
+        );
+        callbacks.sort_by(|a, b| a.name.cmp(&b.name));
+        callbacks.dedup_by(|a, b| a.name == b.name);
+        callbacks
+    }
+
+    /// Callbacks triggered by this key with any modifiers, sorted by name.
+    pub fn callbacks_for_key(&self, key: Key) -> Vec<&Callback> {
+        let mut callbacks: Vec<_> = (0..=ModifierSet::ALL.to_mask())
+            .map(|mask| KeyCombo::new(key.clone(), ModifierSet::from_mask(mask)))
+            .flat_map(|combo| self.callbacks_for_combo(&combo))
+            .collect();
+        callbacks.sort_by(|a, b| a.name.cmp(&b.name));
+        callbacks
+    }
+
+    /// The callback with exactly this name, or with `set_ignore_case` any
+    /// casing of it. Names the file does not have are looked up as aliases,
+    /// see `register_alias`.
     pub fn callback(&self, callback_name: &str) -> Option<Callback> {
-        self.callbacks.get(callback_name).cloned()
+        match self.callbacks.get(callback_name) {
+            Some(callback) => Some(callback.clone()),
+            None if self.ignore_case => self.callback_ignore_case(callback_name),
+            None => None,
+        }
+        .or_else(|| self.callback_by_alias(callback_name))
+    }
+
+    /// The callback whose name matches `callback_name` ignoring ASCII case
+    /// and surrounding whitespace. An exact match wins over other casings.
+    pub fn callback_ignore_case(&self, callback_name: &str) -> Option<Callback> {
+        let callback_name = callback_name.trim();
+        if let Some(callback) = self.callbacks.get(callback_name) {
+            return Some(callback.clone());
+        }
+        self.callbacks
+            .values()
+            .filter(|c| c.name.eq_ignore_ascii_case(callback_name))
+            .min_by(|a, b| a.name.cmp(&b.name))
+            .cloned()
+    }
+
+    /// Makes `callback` ignore case and surrounding whitespace, see
+    /// `ParseOptions::ignore_case`.
+    pub fn set_ignore_case(&mut self, ignore_case: bool) {
+        self.ignore_case = ignore_case;
+    }
+
+    /// The `SimDoNothing` entries of the file, only kept when parsing with
+    /// `ParseOptions::keep_do_nothing`.
+    pub fn do_nothing_callbacks(&self) -> &[Callback] {
+        &self.do_nothing
+    }
+
+    /// The section headers of the file in the order they appear.
+    pub fn sections(&self) -> &[String] {
+        &self.sections
+    }
+
+    /// Callbacks that are present in the file but not bound to a key, sorted
+    /// by name. Includes the `SimDoNothing` entries if they were kept.
+    pub fn unbound_callbacks(&self) -> Vec<&Callback> {
+        let mut unbound: Vec<_> = self
+            .callbacks
+            .values()
+            .chain(self.do_nothing.iter())
+            .filter(|c| !c.is_bound())
+            .collect();
+        unbound.sort_by(|a, b| a.name.cmp(&b.name));
+        unbound
     }
 
+    /// Callbacks the BMS setup UI shows, that is all but the hidden ones,
+    /// sorted by name.
+    pub fn visible_callbacks(&self) -> Vec<&Callback> {
+        let mut visible: Vec<_> = self
+            .callbacks
+            .values()
+            .filter(|c| c.visibility != Visibility::Hidden)
+            .collect();
+        visible.sort_by(|a, b| a.name.cmp(&b.name));
+        visible
+    }
+
+    /// Callbacks the BMS setup UI does not show, sorted by name.
+    pub fn hidden_callbacks(&self) -> Vec<&Callback> {
+        let mut hidden: Vec<_> = self
+            .callbacks
+            .values()
+            .filter(|c| c.visibility == Visibility::Hidden)
+            .collect();
+        hidden.sort_by(|a, b| a.name.cmp(&b.name));
+        hidden
+    }
+
+    /// A one line summary, see `stats` for the details.
     pub fn describe(&self) -> String {
         format!(
             "{} with {} callbacks.",
@@ -85,294 +371,189 @@ impl FalconKeyfile {
             self.callbacks.keys().len()
         )
     }
-
-    pub fn propose_callback_names(&self, query: String, count: usize) -> Vec<String> {
-        let mut names: Vec<_> = self.callbacks.keys().cloned().collect();
-        names.sort_by_key(|a| levenshtein(&query, a));
-
-        names.iter().take(count).map(String::from).collect()
-    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Callback {
     pub name: String,
     pub key_code: u16,
     pub readable_key_code: Key,
-    pub modifiers: Vec<Modifier>,
+    pub modifiers: ModifierSet,
     pub combo_key_code: u16,
     pub readable_combo_key_code: Key,
-    pub combo_modifiers: Vec<Modifier>,
+    pub combo_modifiers: ModifierSet,
+    pub visibility: Visibility,
+    pub description: Option<String>,
+    /// The header of the section the callback is listed under.
+    pub section: Option<String>,
+    /// The 1-based line the callback was parsed from, `None` if it was not
+    /// read from a key file.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub line: Option<usize>,
+    /// The text of that line, kept with `ParseOptions::keep_raw_lines`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub raw_line: Option<String>,
+}
+
+impl Callback {
+    /// The 1-based line of the key file the callback was parsed from.
+    pub fn source_line(&self) -> Option<usize> {
+        self.line
+    }
+
+    /// Whether pressing a key triggers this callback. `SimDoNothing` entries and
+    /// callbacks without a key code (0 or 0xFFFF) are unbound.
+    pub fn is_bound(&self) -> bool {
+        self.name != "SimDoNothing" && self.key_code != 0 && self.key_code != 0xFFFF
+    }
+
+    /// The key and modifiers that trigger this callback.
+    pub fn primary_combo(&self) -> KeyCombo {
+        KeyCombo::new(self.readable_key_code.clone(), self.modifiers)
+    }
+
+    /// The combo that has to be pressed before the primary one, for two-stage
+    /// bindings such as `LAlt+C` followed by `P`.
+    pub fn secondary_combo(&self) -> Option<KeyCombo> {
+        match self.readable_combo_key_code {
+            Key::Unknown => None,
+            ref key => Some(KeyCombo::new(key.clone(), self.combo_modifiers)),
+        }
+    }
+
+    /// Whether a combo has to be pressed first. Unbound callbacks have none,
+    /// whatever their combo columns say.
+    pub fn has_combo(&self) -> bool {
+        self.is_bound() && self.combo_key_code != 0 && self.combo_key_code != 0xFFFF
+    }
+
+    /// The chord that triggers this callback, `None` if it is unbound.
+    pub fn chord(&self) -> Option<KeyCombo> {
+        self.is_bound().then(|| self.primary_combo())
+    }
+
+    /// The chord pressed before `chord`, `None` if there is none or the
+    /// callback is unbound.
+    pub fn combo_chord(&self) -> Option<KeyCombo> {
+        if !self.has_combo() {
+            return None;
+        }
+        Some(KeyCombo::new(
+            self.readable_combo_key_code.clone(),
+            self.combo_modifiers,
+        ))
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum Modifier {
-    LSHIFT,
-    LCONTROL,
-    LALT,
+/// How an entry is shown in the BMS setup UI, from the column after the
+/// modifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Visibility {
+    /// `1`: shown and can be rebound.
+    Visible,
+    /// `-0`: shown, but cannot be rebound.
+    Locked,
+    /// `-1`: a section header.
+    Header,
+    /// `-2`: not shown at all.
+    Hidden,
+    Other(i32),
+}
+
+impl Visibility {
+    fn from_column(column: &str) -> Visibility {
+        match column {
+            "1" => Visibility::Visible,
+            "-0" => Visibility::Locked,
+            "-1" => Visibility::Header,
+            "-2" => Visibility::Hidden,
+            other => Visibility::Other(other.parse().unwrap_or_default()),
+        }
+    }
 }
 
 #[derive(Debug)]
-pub enum KeyFileError<'a> {
+pub enum KeyFileError {
     Empty,
+    NotFound,
+    PermissionDenied,
     ReadError(std::io::Error),
-    ParseError(&'a str),
+    ParseError {
+        line: usize,
+        message: String,
+    },
+    TooManyErrors(usize),
+    /// A callback defined twice, see `ParseOptions::deny_duplicates`.
+    DuplicateCallback {
+        name: String,
+        previous_line: usize,
+        line: usize,
+    },
 }
 
-fn convert_number(number: &str) -> u16 {
-    let number = number.to_lowercase();
-    if number.starts_with("0x") {
-        let without_prefix = number.trim_start_matches("0x");
-        return u32::from_str_radix(without_prefix, 16).expect("Expected hex key code to be an u16")
-            as u16;
+impl std::fmt::Display for KeyFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyFileError::Empty => write!(f, "key file is empty"),
+            KeyFileError::NotFound => write!(f, "file not found"),
+            KeyFileError::PermissionDenied => write!(f, "permission denied"),
+            KeyFileError::ReadError(e) => write!(f, "read error: {}", e),
+            KeyFileError::ParseError { line, message } => {
+                write!(f, "parse error at line {}: {}", line, message)
+            }
+            KeyFileError::TooManyErrors(count) => {
+                write!(f, "giving up after {} malformed lines", count)
+            }
+            KeyFileError::DuplicateCallback {
+                name,
+                previous_line,
+                line,
+            } => write!(
+                f,
+                "callback {} defined on line {} and again on line {}",
+                name, previous_line, line
+            ),
+        }
     }
-    number.parse().unwrap_or_else(|_| panic!("Expected key code number to be u32 but was '{}'",
-        number))
 }
 
-fn parse_modifiers(number: u16) -> Vec<Modifier> {
-    let mut result = vec![];
-    if number & 1 == 1 {
-        result.push(Modifier::LSHIFT);
-    }
-    if number & 2 == 2 {
-        result.push(Modifier::LCONTROL);
+impl std::error::Error for KeyFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            KeyFileError::ReadError(e) => Some(e),
+            _ => None,
+        }
     }
-    if number & 4 == 4 {
-        result.push(Modifier::LALT);
+}
+
+/// A `KeyFileError` together with the path of the file it occurred in.
+#[derive(Debug)]
+pub struct PathError {
+    pub path: PathBuf,
+    pub error: KeyFileError,
+}
+
+impl std::fmt::Display for PathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.error)
     }
-    result
 }
 
-fn parse_key_code(number: u16) -> Key {
-    match number {
-        // these are maric unicorns in keyfiles
-        0xFFFF => Key::Unknown,
-        0 => Key::Unknown,
-        // normal ones start here
-        1 => Key::Escape,
-        2 => Key::Num1,
-        3 => Key::Num2,
-        4 => Key::Num3,
-        5 => Key::Num4,
-        6 => Key::Num5,
-        7 => Key::Num6,
-        8 => Key::Num7,
-        9 => Key::Num8,
-        10 => Key::Num9,
-        11 => Key::Num0,
-        12 => Key::Minus,
-        13 => Key::Equals,
-        14 => Key::Backspace,
-        15 => Key::Tab,
-        16 => Key::Q,
-        17 => Key::W,
-        18 => Key::E,
-        19 => Key::R,
-        20 => Key::T,
-        21 => Key::Y,
-        22 => Key::U,
-        23 => Key::I,
-        24 => Key::O,
-        25 => Key::P,
-        26 => Key::LeftBracket,
-        27 => Key::RightBracket,
-        28 => Key::Return,
-        29 => Key::LControl,
-        30 => Key::A,
-        31 => Key::S,
-        32 => Key::D,
-        33 => Key::F,
-        34 => Key::G,
-        35 => Key::H,
-        36 => Key::J,
-        37 => Key::K,
-        38 => Key::L,
-        39 => Key::Semicolon,
-        40 => Key::Apostrophe,
-        41 => Key::BackQuote,
-        42 => Key::LShift,
-        43 => Key::Backslash,
-        44 => Key::Z,
-        45 => Key::X,
-        46 => Key::C,
-        47 => Key::V,
-        48 => Key::B,
-        49 => Key::N,
-        50 => Key::M,
-        51 => Key::Comma,
-        52 => Key::Period,
-        53 => Key::Slash,
-        55 => Key::Multiply,
-        57 => Key::Space,
-        58 => Key::CapsLock,
-        59 => Key::F1,
-        60 => Key::F2,
-        61 => Key::F3,
-        62 => Key::F4,
-        63 => Key::F5,
-        64 => Key::F6,
-        65 => Key::F7,
-        66 => Key::F8,
-        67 => Key::F9,
-        68 => Key::F10,
-        69 => Key::Numlock,
-        70 => Key::ScrollLock,
-        71 => Key::Numpad7,
-        72 => Key::Numpad8,
-        73 => Key::Numpad9,
-        74 => Key::Subtract,
-        75 => Key::Numpad4,
-        76 => Key::Numpad5,
-        77 => Key::Numpad6,
-        78 => Key::Add,
-        79 => Key::Numpad1,
-        80 => Key::Numpad2,
-        81 => Key::Numpad3,
-        82 => Key::Numpad0,
-        83 => Key::Decimal,
-        87 => Key::F11,
-        88 => Key::F12,
-        100 => Key::F13,
-        101 => Key::F14,
-        102 => Key::F15,
-        156 => Key::NumpadEnter,
-        157 => Key::RControl,
-        181 => Key::Divide,
-        183 => Key::PrintScr,
-        199 => Key::Home,
-        200 => Key::UpArrow,
-        201 => Key::PageUp,
-        203 => Key::LeftArrow,
-        205 => Key::RightArrow,
-        207 => Key::End,
-        208 => Key::DownArrow,
-        209 => Key::PageDown,
-        210 => Key::Insert,
-        211 => Key::Delete,
-        219 => Key::LWin,
-        220 => Key::RWin,
-        221 => Key::Apps,
-        e => {
-            error!("Unmatched keycode in keyfile: {}", e);
-            Key::Unknown
-        }
+impl std::error::Error for PathError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
     }
 }
 
-/// Keys that are used in falcon bms key files
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Key {
-    Unknown,
-    Escape,
-    Num1,
-    Num2,
-    Num3,
-    Num4,
-    Num5,
-    Num6,
-    Num7,
-    Num8,
-    Num9,
-    Num0,
-    Minus,
-    Equals,
-    Backspace,
-    Tab,
-    Q,
-    W,
-    E,
-    R,
-    T,
-    Y,
-    U,
-    I,
-    O,
-    P,
-    LeftBracket,
-    RightBracket,
-    Return,
-    LControl,
-    A,
-    S,
-    D,
-    F,
-    G,
-    H,
-    J,
-    K,
-    L,
-    Semicolon,
-    Apostrophe,
-    BackQuote,
-    LShift,
-    Backslash,
-    Z,
-    X,
-    C,
-    V,
-    B,
-    N,
-    M,
-    Comma,
-    Period,
-    Slash,
-    Multiply,
-    Space,
-    CapsLock,
-    F1,
-    F2,
-    F3,
-    F4,
-    F5,
-    F6,
-    F7,
-    F8,
-    F9,
-    F10,
-    Numlock,
-    ScrollLock,
-    Numpad7,
-    Numpad8,
-    Numpad9,
-    Subtract,
-    Numpad4,
-    Numpad5,
-    Numpad6,
-    Add,
-    Numpad1,
-    Numpad2,
-    Numpad3,
-    Numpad0,
-    Decimal,
-    F11,
-    F12,
-    F13,
-    F14,
-    F15,
-    NumpadEnter,
-    RControl,
-    Divide,
-    PrintScr,
-    Home,
-    UpArrow,
-    PageUp,
-    LeftArrow,
-    RightArrow,
-    End,
-    DownArrow,
-    PageDown,
-    Insert,
-    Delete,
-    LWin,
-    RWin,
-    Apps,
+fn is_unmatched_key_code(number: u16) -> bool {
+    matches!(Key::from_scancode(number), Key::Other(_))
 }
 
 #[cfg(test)]
 mod falcon_key_file {
     use super::*;
-    use env_logger;
     use env_logger::Env;
     use std::path::Path;
 
@@ -388,22 +569,22 @@ mod falcon_key_file {
     #[test]
     fn parse_basic_key_file() {
         let path = Path::new("test-data/basic.key");
-        let file = File::open(&path).unwrap();
-        let result = parse(String::from("basic.key"), &file);
+        let file = File::open(path).unwrap();
+        let result = parse(String::from("basic.key"), &file, &ParseOptions::default());
         assert!(result.is_ok());
 
-        let result = result.unwrap();
+        let result = result.unwrap().keyfile;
 
         let callback = result.callback("AFElevatorTrimUp");
         assert!(callback.is_some());
         let callback = callback.unwrap();
         println!("{:?}", callback);
         assert_eq!(callback.readable_key_code, Key::UpArrow);
-        assert_eq!(callback.modifiers, vec![Modifier::LCONTROL]);
+        assert_eq!(callback.modifiers.to_vec(), vec![Modifier::LCONTROL]);
 
         let callback = result.callback("AFBrakesToggle").unwrap();
         assert_eq!(callback.readable_key_code, Key::B);
-        assert_eq!(callback.modifiers, vec![]);
+        assert_eq!(callback.modifiers.to_vec(), vec![]);
 
         let callback = result.callback("OTWBalanceIVCvsAIUp").unwrap();
         assert_eq!(callback.readable_key_code, Key::RightBracket);
@@ -415,7 +596,7 @@ mod falcon_key_file {
         let callback = result.callback("AFElevatorUp").unwrap();
         assert_eq!(callback.readable_key_code, Key::UpArrow);
         assert_eq!(
-            callback.modifiers,
+            callback.modifiers.to_vec(),
             vec![Modifier::LSHIFT, Modifier::LCONTROL]
         );
 
@@ -425,7 +606,7 @@ mod falcon_key_file {
         assert!(callback.modifiers.is_empty());
 
         assert_eq!(callback.readable_combo_key_code, Key::C);
-        assert_eq!(callback.combo_modifiers, vec![Modifier::LALT]);
+        assert_eq!(callback.combo_modifiers.to_vec(), vec![Modifier::LALT]);
 
         // let's find another combo key
         let callback = result.callback("OTWToggleFrameRate").unwrap();
@@ -433,7 +614,338 @@ mod falcon_key_file {
         assert!(callback.modifiers.is_empty());
 
         assert_eq!(callback.readable_combo_key_code, Key::C);
-        assert_eq!(callback.combo_modifiers, vec![Modifier::LALT]);
+        assert_eq!(callback.combo_modifiers.to_vec(), vec![Modifier::LALT]);
+    }
+
+    #[test]
+    fn display_primary_and_secondary_combos() {
+        let keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let callback = keyfile.callback("SimPilotToggle").unwrap();
+        assert_eq!(callback.primary_combo().to_string(), "P");
+        assert_eq!(callback.secondary_combo().unwrap().to_string(), "LAlt+C");
+        let callback = keyfile.callback("AFBrakesToggle").unwrap();
+        assert_eq!(callback.primary_combo().to_string(), "B");
+        assert_eq!(callback.secondary_combo(), None);
+    }
+
+    #[test]
+    fn assign_callbacks_to_sections() {
+        let keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let section = keyfile.callback("AFBrakesToggle").unwrap().section.clone();
+        assert_eq!(section.as_deref(), Some("2.19 THROTTLE QUADRANT SYSTEM"));
+        assert!(keyfile.sections().contains(&section.unwrap()));
+    }
+
+    #[test]
+    fn collect_warnings_for_duplicate_callbacks() {
+        let path = Path::new("test-data/basic.key");
+        let file = File::open(path).unwrap();
+        let report = parse(String::from("basic.key"), &file, &ParseOptions::default()).unwrap();
+
+        assert!(report.warnings.contains(&ParseWarning::DuplicateCallback {
+            line: 658,
+            previous_line: 657,
+            name: String::from("OTWRadioMenuStep"),
+        }));
+        assert!(
+            report
+                .warnings
+                .iter()
+                .all(|w| matches!(w, ParseWarning::DuplicateCallback { .. }))
+        );
+
+        let file = File::open(path).unwrap();
+        let options = ParseOptions {
+            deny_duplicates: true,
+            ..ParseOptions::default()
+        };
+        let result = parse(String::from("basic.key"), &file, &options);
+        assert!(matches!(
+            result,
+            Err(KeyFileError::DuplicateCallback {
+                previous_line: 563,
+                line: 613,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn skip_malformed_lines_when_lenient() {
+        let file = File::open("test-data/malformed.key").unwrap();
+        let report = parse(
+            String::from("malformed.key"),
+            &file,
+            &ParseOptions::lenient(),
+        )
+        .unwrap();
+
+        assert!(report.keyfile.callback("AFBrakesToggle").is_some());
+        assert!(report.keyfile.callback("SimPilotToggle").is_some());
+        assert!(report.keyfile.callback("AFGearToggle").is_none());
+        assert!(report.keyfile.callback("AFElevatorTrimUp").is_none());
+
+        let skipped: Vec<_> = report
+            .warnings
+            .iter()
+            .filter(|w| matches!(w, ParseWarning::MalformedLine { .. }))
+            .map(ParseWarning::line)
+            .collect();
+        assert_eq!(skipped, vec![7, 8]);
+    }
+
+    #[test]
+    fn fail_on_malformed_lines_when_strict() {
+        let file = File::open("test-data/malformed.key").unwrap();
+        let result = parse(
+            String::from("malformed.key"),
+            &file,
+            &ParseOptions::strict(),
+        );
+        assert!(matches!(
+            result,
+            Err(KeyFileError::ParseError { line: 7, .. })
+        ));
+
+        let file = File::open("test-data/malformed.key").unwrap();
+        let options = ParseOptions {
+            max_errors: Some(1),
+            ..ParseOptions::default()
+        };
+        let result = parse(String::from("malformed.key"), &file, &options);
+        assert!(matches!(result, Err(KeyFileError::TooManyErrors(2))));
+    }
+
+    #[test]
+    fn look_up_callbacks_ignoring_case() {
+        let file = File::open("test-data/basic.key").unwrap();
+        let mut keyfile = parse(String::from("basic.key"), &file, &ParseOptions::default())
+            .unwrap()
+            .keyfile;
+        assert!(keyfile.callback("afbrakestoggle").is_none());
+        assert_eq!(
+            keyfile
+                .callback_ignore_case(" afbrakestoggle ")
+                .unwrap()
+                .name,
+            "AFBrakesToggle"
+        );
+        keyfile.set_ignore_case(true);
+        assert!(keyfile.callback("AFBRAKESTOGGLE").is_some());
+
+        let file = File::open("test-data/basic.key").unwrap();
+        let options = ParseOptions {
+            ignore_case: true,
+            ..ParseOptions::default()
+        };
+        let keyfile = parse(String::from("basic.key"), &file, &options)
+            .unwrap()
+            .keyfile;
+        assert!(keyfile.callback("afbrakestoggle").is_some());
+    }
+
+    #[test]
+    fn interpret_sentinel_codes() {
+        let content = "# sentinels
+SimPilotToggle -1 0 0x19 0 0x2E 4 1
+AFBrakesToggle -1 0 0x30 0 0 0 1
+AFGearToggle -1 0 0XFFFFFFFF 0 0x2E 4 1
+SimHookToggle -1 0 0 0 0XFFFFFFFF 0 1
+";
+        let keyfile = parse_str(String::from("inline"), content, &ParseOptions::default())
+            .unwrap()
+            .keyfile;
+        let pilot = keyfile.callback("SimPilotToggle").unwrap();
+        assert!(pilot.has_combo());
+        assert_eq!(pilot.chord().unwrap().to_string(), "P");
+        assert_eq!(pilot.combo_chord().unwrap().to_string(), "LAlt+C");
+
+        let brakes = keyfile.callback("AFBrakesToggle").unwrap();
+        assert!(brakes.is_bound() && !brakes.has_combo());
+        assert_eq!(brakes.combo_chord(), None);
+
+        for name in ["AFGearToggle", "SimHookToggle"] {
+            let callback = keyfile.callback(name).unwrap();
+            assert!(!callback.is_bound() && !callback.has_combo());
+            assert_eq!((callback.chord(), callback.combo_chord()), (None, None));
+        }
+        // exports show no combo for unbound callbacks either
+        assert!(keyfile.to_csv().contains("\nAFGearToggle,,,,,\n"));
+        assert_eq!(
+            keyfile.callback("AFGearToggle").unwrap().key_sequence(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn report_unbound_callbacks() {
+        let file = File::open("test-data/basic.key").unwrap();
+        let keyfile = parse(String::from("basic.key"), &file, &ParseOptions::default())
+            .unwrap()
+            .keyfile;
+        let unbound = keyfile.unbound_callbacks();
+        assert!(unbound.iter().all(|c| c.name != "SimDoNothing"));
+        assert!(unbound.iter().any(|c| c.name == "SimCursorUpLeft"));
+        assert!(!keyfile.callback("SimCursorUpLeft").unwrap().is_bound());
+        assert!(keyfile.callback("AFBrakesToggle").unwrap().is_bound());
+
+        let file = File::open("test-data/basic.key").unwrap();
+        let options = ParseOptions {
+            keep_do_nothing: true,
+            ..ParseOptions::default()
+        };
+        let keyfile = parse(String::from("basic.key"), &file, &options)
+            .unwrap()
+            .keyfile;
+        assert!(!keyfile.do_nothing_callbacks().is_empty());
+        assert_eq!(
+            keyfile.unbound_callbacks().len(),
+            unbound.len() + keyfile.do_nothing_callbacks().len()
+        );
+    }
+
+    #[test]
+    fn split_by_visibility() {
+        let keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let hidden = keyfile.hidden_callbacks();
+        assert!(hidden.iter().any(|c| c.name == "OTWRadioMenuStep"));
+        assert!(hidden.iter().all(|c| c.visibility == Visibility::Hidden));
+        assert!(
+            keyfile
+                .visible_callbacks()
+                .iter()
+                .any(|c| c.name == "AFBrakesToggle")
+        );
+        assert_eq!(
+            keyfile.visible_callbacks().len() + hidden.len(),
+            keyfile.len()
+        );
+    }
+
+    #[test]
+    fn parse_from_str_and_reader() {
+        let content = "# header\nAFBrakesToggle -1 0 0x30 0 0 0 1 \"Brakes\"\n";
+        let keyfile = parse_str(String::from("inline"), content, &ParseOptions::default())
+            .unwrap()
+            .keyfile;
+        assert_eq!(
+            keyfile
+                .callback("AFBrakesToggle")
+                .unwrap()
+                .readable_key_code,
+            Key::B
+        );
+
+        let reader = std::io::Cursor::new(content.as_bytes());
+        let keyfile = parse_reader(String::from("cursor"), reader, &ParseOptions::default())
+            .unwrap()
+            .keyfile;
+        assert!(keyfile.callback("AFBrakesToggle").is_some());
+
+        let result = parse_str(String::from("empty"), "", &ParseOptions::default());
+        assert!(matches!(result, Err(KeyFileError::Empty)));
+    }
+
+    #[test]
+    fn parse_from_path() {
+        let keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        assert_eq!(keyfile.describe(), "basic.key with 496 callbacks.");
+
+        let error = FalconKeyfile::from_path("test-data/missing.key").unwrap_err();
+        assert!(matches!(error.error, KeyFileError::NotFound));
+        assert_eq!(error.to_string(), "test-data/missing.key: file not found");
+    }
+
+    #[test]
+    fn keep_unmatched_key_codes() {
+        let content = "# header\nSimHookToggle -1 0 0x54 0 0 0 1 \"Hook\"\n";
+        let report = parse_str(String::from("inline"), content, &ParseOptions::default()).unwrap();
+        let callback = report.keyfile.callback("SimHookToggle").unwrap();
+        assert_eq!(callback.readable_key_code, Key::Other(0x54));
+        assert_eq!(callback.readable_combo_key_code, Key::Unknown);
+        assert_eq!(
+            report.warnings,
+            vec![ParseWarning::UnknownKeyCode {
+                line: 2,
+                key_code: 0x54
+            }]
+        );
+    }
+
+    #[test]
+    fn look_up_callbacks_by_key_and_combo() {
+        let keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+
+        let combo: KeyCombo = "LCtrl+UpArrow".parse().unwrap();
+        let names: Vec<_> = keyfile
+            .callbacks_for_combo(&combo)
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["AFElevatorTrimUp"]);
+        let pilot = keyfile.callbacks_for_combo(&"P".parse().unwrap());
+        assert!(
+            pilot
+                .iter()
+                .any(|c| c.name == "SimPilotToggle" && c.secondary_combo().is_some())
+        );
+
+        let callbacks = keyfile.callbacks_for_key(Key::UpArrow);
+        assert!(callbacks.iter().any(|c| c.name == "AFElevatorTrimUp"));
+        assert!(callbacks.iter().any(|c| c.name == "AFElevatorUp"));
+        assert!(
+            callbacks
+                .iter()
+                .all(|c| c.readable_key_code == Key::UpArrow)
+        );
+    }
+
+    #[test]
+    fn find_callbacks_by_either_chord() {
+        let keyfile = parse_str(
+            String::from("inline"),
+            "# chords
+SimPilotToggle -1 0 0x19 0 0x2E 4 1
+OTWToggleFrameRate -1 0 0x21 0 0x2E 4 1
+SimHookToggle -1 0 0x2E 4 0 0 1
+AFBrakesToggle -1 0 0x19 0 0 0 1
+AFGearToggle -1 0 0XFFFFFFFF 0 0x2E 4 1
+",
+            &ParseOptions::default(),
+        )
+        .unwrap()
+        .keyfile;
+        let names = |chord: &str| -> Vec<String> {
+            keyfile
+                .find_by_chord(&chord.parse().unwrap())
+                .iter()
+                .map(|c| c.name.clone())
+                .collect()
+        };
+        assert_eq!(
+            names("LAlt+C"),
+            vec!["OTWToggleFrameRate", "SimHookToggle", "SimPilotToggle"]
+        );
+        assert_eq!(names("P"), vec!["AFBrakesToggle", "SimPilotToggle"]);
+        assert!(names("F").contains(&String::from("OTWToggleFrameRate")));
+        assert!(names("LCtrl+P").is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let json = serde_json::to_string(&keyfile).unwrap();
+        let keyfile: FalconKeyfile = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(keyfile.describe(), "basic.key with 496 callbacks.");
+        let callback = keyfile.callback("AFElevatorUp").unwrap();
+        assert_eq!(
+            callback.modifiers.to_vec(),
+            vec![Modifier::LSHIFT, Modifier::LCONTROL]
+        );
+        let combo: KeyCombo = "LCtrl+UpArrow".parse().unwrap();
+        assert_eq!(keyfile.callbacks_for_combo(&combo).len(), 1);
     }
 
     #[test]
@@ -442,11 +954,15 @@ mod falcon_key_file {
         env_logger::init_from_env(env);
 
         let path = Path::new("test-data/T16000M-FCS-Full.key");
-        let file = File::open(&path).unwrap();
-        let result = parse(String::from("T16000M-FCS-Full.key"), &file);
+        let file = File::open(path).unwrap();
+        let result = parse(
+            String::from("T16000M-FCS-Full.key"),
+            &file,
+            &ParseOptions::default(),
+        );
         assert!(result.is_ok());
 
-        let result = result.unwrap();
+        let result = result.unwrap().keyfile;
 
         // find one callback with SLASH
         let callback = result.callback("SimMissileStep");
@@ -454,13 +970,63 @@ mod falcon_key_file {
         let callback = callback.unwrap();
         println!("{:?}", callback);
         assert_eq!(callback.readable_key_code, Key::Slash);
-        assert_eq!(callback.modifiers, vec![Modifier::LSHIFT]);
+        assert_eq!(callback.modifiers.to_vec(), vec![Modifier::LSHIFT]);
 
         // let's find the problematic new ones
         let callback = result.callback("SimMIDSLVTInc");
         assert!(callback.is_some());
         let callback = callback.unwrap();
         assert_eq!(callback.readable_key_code, Key::Slash);
-        assert_eq!(callback.modifiers, vec![Modifier::LSHIFT, Modifier::LALT]);
+        assert_eq!(
+            callback.modifiers.to_vec(),
+            vec![Modifier::LSHIFT, Modifier::LALT]
+        );
+    }
+
+    #[test]
+    fn parse_joystick_bindings() {
+        let keyfile = FalconKeyfile::from_path("test-data/T16000M-FCS-Full.key").unwrap();
+        let bindings = keyfile.joystick_bindings();
+        let find = |callback: &str| bindings.iter().find(|b| b.callback == callback).unwrap();
+
+        assert_eq!(find("SimPickle").input, JoystickInput::Button(2));
+        assert!(!find("SimPickle").shifted);
+        assert_eq!(find("AFResetTrim").input, JoystickInput::Button(1));
+        assert!(find("AFResetTrim").shifted);
+        let binding = find("SimSelectSRMOverride");
+        assert_eq!(
+            binding.input,
+            JoystickInput::Pov {
+                hat: 3,
+                direction: 2
+            }
+        );
+        assert_eq!(
+            binding.description.as_deref(),
+            Some("TQS: DOGFIGHT Switch - DF Override")
+        );
+    }
+
+    #[test]
+    fn record_source_lines() {
+        let path = Path::new("test-data/basic.key");
+        let file = File::open(path).unwrap();
+        let options = ParseOptions {
+            keep_raw_lines: true,
+            ..ParseOptions::default()
+        };
+        let keyfile = parse(String::from("basic.key"), &file, &options)
+            .unwrap()
+            .keyfile;
+
+        let callback = keyfile.callback("AFElevatorTrimUp").unwrap();
+        assert_eq!(callback.source_line(), Some(451));
+        assert_eq!(
+            callback.raw_line.as_deref(),
+            Some("AFElevatorTrimUp -1 0 0xC8 2 0 0 1 \"STICK: TRIM Up - Nose Down\"")
+        );
+
+        let keyfile = FalconKeyfile::from_path(path).unwrap();
+        assert_eq!(keyfile.callback("AFElevatorTrimUp").unwrap().raw_line, None);
     }
 }
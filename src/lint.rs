@@ -0,0 +1,401 @@
+//! Checks for common mistakes in key files.
+//!
+//! A `Linter` runs a list of `Rule`s over a key file and collects their
+//! `Finding`s. `Linter::default()` has all built-in rules, custom rules are
+//! added with `Linter::with_rule`:
+//!
+//! ```ignore
+//! let findings = Linter::default().with_rule(Box::new(MyRule)).run(&keyfile);
+//! ```
+
+use crate::{FalconKeyfile, Key, Visibility};
+use std::fmt;
+
+/// Callbacks every setup should have bound, to the keyboard or a joystick.
+pub(crate) const ESSENTIAL: [&str; 20] = [
+    "SimTriggerFirstDetent",
+    "SimTriggerSecondDetent",
+    "SimPickle",
+    "SimMissileStep",
+    "AFGearToggle",
+    "SimWheelBrakes",
+    "AFElevatorTrimUp",
+    "AFElevatorTrimDown",
+    "AFAileronTrimLeft",
+    "AFAileronTrimRight",
+    "SimCursorUp",
+    "SimCursorDown",
+    "SimCursorLeft",
+    "SimCursorRight",
+    "SimCursorEnable",
+    "SimDMSUp",
+    "SimDMSDown",
+    "SimTMSUp",
+    "SimCommsSwitchLeft",
+    "SimCommsSwitchRight",
+];
+
+/// How bad a finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A problem a rule found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// The name of the rule, such as `chord-conflict`.
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    pub callback: Option<String>,
+    /// The 1-based line of the key file, if the finding has one.
+    pub line: Option<usize>,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        write!(f, "{} [{}]: {}", self.severity, self.rule, self.message)
+    }
+}
+
+/// A check over a whole key file.
+pub trait Rule {
+    /// A short kebab-case name, used in findings.
+    fn name(&self) -> &'static str;
+
+    fn check(&self, keyfile: &FalconKeyfile) -> Vec<Finding>;
+}
+
+/// Essential callbacks bound neither to a key nor to a joystick input.
+#[derive(Debug, Clone)]
+pub struct EssentialUnbound {
+    pub callbacks: Vec<String>,
+}
+
+impl Default for EssentialUnbound {
+    fn default() -> EssentialUnbound {
+        EssentialUnbound {
+            callbacks: ESSENTIAL.iter().map(|name| String::from(*name)).collect(),
+        }
+    }
+}
+
+impl Rule for EssentialUnbound {
+    fn name(&self) -> &'static str {
+        "essential-unbound"
+    }
+
+    fn check(&self, keyfile: &FalconKeyfile) -> Vec<Finding> {
+        self.callbacks
+            .iter()
+            .filter(|name| {
+                !keyfile.callbacks.get(*name).is_some_and(|c| c.is_bound())
+                    && !keyfile.joystick.iter().any(|b| &b.callback == *name)
+            })
+            .map(|name| Finding {
+                rule: self.name(),
+                severity: Severity::Warning,
+                message: format!("{} is not bound", name),
+                callback: Some(name.clone()),
+                line: keyfile.callbacks.get(name).and_then(|c| c.line),
+            })
+            .collect()
+    }
+}
+
+/// Several callbacks on the same chord, only one of which fires.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChordConflicts;
+
+impl Rule for ChordConflicts {
+    fn name(&self) -> &'static str {
+        "chord-conflict"
+    }
+
+    fn check(&self, keyfile: &FalconKeyfile) -> Vec<Finding> {
+        keyfile
+            .conflicts()
+            .into_iter()
+            .map(|conflict| {
+                let line = conflict
+                    .callbacks
+                    .iter()
+                    .filter_map(|name| keyfile.callbacks.get(name)?.line)
+                    .max();
+                Finding {
+                    rule: self.name(),
+                    severity: Severity::Error,
+                    message: format!(
+                        "{} is bound to {}",
+                        conflict.binding(),
+                        conflict.callbacks.join(", ")
+                    ),
+                    callback: conflict.callbacks.first().cloned(),
+                    line,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Bound callbacks the BMS setup UI does not show, so the binding cannot be
+/// seen or changed there.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HiddenButBound;
+
+impl Rule for HiddenButBound {
+    fn name(&self) -> &'static str {
+        "hidden-but-bound"
+    }
+
+    fn check(&self, keyfile: &FalconKeyfile) -> Vec<Finding> {
+        keyfile
+            .callbacks
+            .values()
+            .filter(|c| c.is_bound() && c.visibility == Visibility::Hidden)
+            .map(|c| Finding {
+                rule: self.name(),
+                severity: Severity::Info,
+                message: format!("{} is bound but hidden in the setup UI", c.name),
+                callback: Some(c.name.clone()),
+                line: c.line,
+            })
+            .collect()
+    }
+}
+
+/// Chords Windows handles itself, see `KeyCombo::is_reserved`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReservedCombo;
+
+impl Rule for ReservedCombo {
+    fn name(&self) -> &'static str {
+        "reserved-combo"
+    }
+
+    fn check(&self, keyfile: &FalconKeyfile) -> Vec<Finding> {
+        keyfile
+            .callbacks
+            .values()
+            .filter(|c| c.is_bound())
+            .filter_map(|c| {
+                let combo = c
+                    .chord()
+                    .into_iter()
+                    .chain(c.combo_chord())
+                    .find(|combo| combo.is_reserved())?;
+                Some(Finding {
+                    rule: self.name(),
+                    severity: Severity::Warning,
+                    message: format!("{} is bound to {}, which Windows reserves", c.name, combo),
+                    callback: Some(c.name.clone()),
+                    line: c.line,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Modifiers that cannot mean what they say: modifiers without a key, or a
+/// modifier key held together with itself.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SuspiciousModifiers;
+
+impl Rule for SuspiciousModifiers {
+    fn name(&self) -> &'static str {
+        "suspicious-modifiers"
+    }
+
+    fn check(&self, keyfile: &FalconKeyfile) -> Vec<Finding> {
+        let problem = |key: &Key, code: u16, modifiers: crate::ModifierSet| {
+            let unbound = code == 0 || code == 0xFFFF;
+            if unbound && !modifiers.is_empty() {
+                return Some("modifiers without a key");
+            }
+            let itself = match key {
+                Key::LShift => Some(crate::Modifier::LSHIFT),
+                Key::LControl => Some(crate::Modifier::LCONTROL),
+                Key::Other(crate::events::LALT_SCANCODE) => Some(crate::Modifier::LALT),
+                _ => None,
+            };
+            itself
+                .is_some_and(|m| modifiers.contains(m))
+                .then_some("a modifier key held with itself")
+        };
+        keyfile
+            .callbacks
+            .values()
+            .filter_map(|c| {
+                let message =
+                    problem(&c.readable_key_code, c.key_code, c.modifiers).or_else(|| {
+                        problem(
+                            &c.readable_combo_key_code,
+                            c.combo_key_code,
+                            c.combo_modifiers,
+                        )
+                    })?;
+                Some(Finding {
+                    rule: self.name(),
+                    severity: Severity::Warning,
+                    message: format!("{} has {}", c.name, message),
+                    callback: Some(c.name.clone()),
+                    line: c.line,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Runs rules over key files.
+pub struct Linter {
+    rules: Vec<Box<dyn Rule>>,
+}
+
+impl Linter {
+    /// A linter without any rules.
+    pub fn new() -> Linter {
+        Linter { rules: vec![] }
+    }
+
+    pub fn with_rule(mut self, rule: Box<dyn Rule>) -> Linter {
+        self.rules.push(rule);
+        self
+    }
+
+    /// The names of the rules, in the order they run.
+    pub fn rules(&self) -> Vec<&'static str> {
+        self.rules.iter().map(|rule| rule.name()).collect()
+    }
+
+    /// The findings of all rules, those with a line first and sorted by
+    /// line, then by rule.
+    pub fn run(&self, keyfile: &FalconKeyfile) -> Vec<Finding> {
+        let mut findings: Vec<_> = self
+            .rules
+            .iter()
+            .flat_map(|rule| rule.check(keyfile))
+            .collect();
+        findings.sort_by(|a, b| {
+            (a.line.is_none(), a.line, a.rule, &a.callback).cmp(&(
+                b.line.is_none(),
+                b.line,
+                b.rule,
+                &b.callback,
+            ))
+        });
+        findings
+    }
+}
+
+/// All built-in rules.
+impl Default for Linter {
+    fn default() -> Linter {
+        Linter::new()
+            .with_rule(Box::new(EssentialUnbound::default()))
+            .with_rule(Box::new(ChordConflicts))
+            .with_rule(Box::new(HiddenButBound))
+            .with_rule(Box::new(ReservedCombo))
+            .with_rule(Box::new(SuspiciousModifiers))
+    }
+}
+
+impl FalconKeyfile {
+    /// The findings of the built-in lint rules, see `Linter`.
+    pub fn lint(&self) -> Vec<Finding> {
+        Linter::default().run(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParseOptions, parse_str};
+
+    struct NoToggles;
+
+    impl Rule for NoToggles {
+        fn name(&self) -> &'static str {
+            "no-toggles"
+        }
+
+        fn check(&self, keyfile: &FalconKeyfile) -> Vec<Finding> {
+            keyfile
+                .find("*Toggle")
+                .into_iter()
+                .map(|c| Finding {
+                    rule: self.name(),
+                    severity: Severity::Info,
+                    message: String::new(),
+                    callback: Some(c.name.clone()),
+                    line: c.line,
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn run_builtin_and_custom_rules() {
+        let keyfile = parse_str(
+            String::from("lint.key"),
+            "# lint
+AFBrakesToggle -1 0 0x30 0 0 0 1
+AFGearToggle -1 0 0x30 0 0 0 1
+SimHookToggle -1 0 0x0F 4 0 0 -2
+SimPilotToggle -1 0 0XFFFFFFFF 2 0 0 1
+SimPickle 2 -1 -2 0 0x0 -1
+",
+            &ParseOptions::default(),
+        )
+        .unwrap()
+        .keyfile;
+
+        let findings = keyfile.lint();
+        let rules: Vec<_> = findings
+            .iter()
+            .filter(|f| f.line.is_some())
+            .map(|f| (f.line.unwrap(), f.rule))
+            .collect();
+        assert_eq!(
+            rules,
+            vec![
+                (3, "chord-conflict"),
+                (4, "hidden-but-bound"),
+                (4, "reserved-combo"),
+                (5, "suspicious-modifiers"),
+            ]
+        );
+        assert!(
+            findings
+                .iter()
+                .any(|f| f.rule == "essential-unbound" && f.line.is_none())
+        );
+        assert!(
+            !findings
+                .iter()
+                .any(|f| f.callback.as_deref() == Some("SimPickle"))
+        );
+        assert_eq!(
+            findings[0].to_string(),
+            "line 3: error [chord-conflict]: B is bound to AFBrakesToggle, AFGearToggle"
+        );
+
+        let custom = Linter::new().with_rule(Box::new(NoToggles)).run(&keyfile);
+        assert_eq!(custom.len(), 4);
+    }
+}
@@ -0,0 +1,904 @@
+use crate::edit::copy_binding;
+use crate::joystick::input_name;
+use crate::{Callback, FalconKeyfile, JoystickBinding, JoystickInput};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+/// Which side wins when both key files bind a callback differently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    PreferSelf,
+    PreferOther,
+    FailOnConflict,
+}
+
+/// How the resolver of `FalconKeyfile::merge_with` settles a conflict.
+#[derive(Debug, Clone)]
+pub enum MergeResolution {
+    /// Keep the binding of this key file.
+    Ours,
+    /// Take the binding of the other key file.
+    Theirs,
+    /// Use this callback instead of either side, it keeps the conflict's name.
+    Custom(Callback),
+    /// Stop merging, `merge_with` fails with the conflicts so far.
+    Abort,
+}
+
+/// How the joystick resolver of `FalconKeyfile::merge_with` settles a
+/// `JoystickConflict`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JoystickResolution {
+    /// Keep the callbacks this key file assigns to the input.
+    Ours,
+    /// Take the callbacks the other key file assigns to the input.
+    Theirs,
+    /// Assign the input to this callback instead.
+    Custom(String),
+    /// Stop merging, `merge_with` fails with the conflicts so far.
+    Abort,
+}
+
+/// A callback that both key files bind, but to different chords.
+#[derive(Debug, Clone)]
+pub struct MergeConflict {
+    pub ours: Callback,
+    pub theirs: Callback,
+}
+
+impl MergeConflict {
+    pub fn name(&self) -> &str {
+        &self.ours.name
+    }
+}
+
+/// A joystick input that both key files assign, but to different callbacks.
+/// Either side is empty if that side does not assign the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JoystickConflict {
+    pub input: JoystickInput,
+    pub shifted: bool,
+    pub ours: Vec<JoystickBinding>,
+    pub theirs: Vec<JoystickBinding>,
+}
+
+/// The input, such as `shifted DX3`.
+impl fmt::Display for JoystickConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&input_name(self.input, self.shifted))
+    }
+}
+
+/// The merged key file together with the conflicts that were resolved by the
+/// strategy.
+#[derive(Debug, Clone)]
+pub struct MergeOutcome {
+    pub keyfile: FalconKeyfile,
+    pub conflicts: Vec<MergeConflict>,
+    pub joystick_conflicts: Vec<JoystickConflict>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MergeError {
+    pub conflicts: Vec<MergeConflict>,
+    pub joystick_conflicts: Vec<JoystickConflict>,
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<_> = self
+            .conflicts
+            .iter()
+            .map(|c| String::from(c.name()))
+            .chain(self.joystick_conflicts.iter().map(|c| c.to_string()))
+            .collect();
+        write!(f, "conflicting bindings for {}", names.join(", "))
+    }
+}
+
+impl std::error::Error for MergeError {}
+
+/// A callback that both sides changed differently relative to the common
+/// ancestor. `None` means the callback is absent on that side.
+#[derive(Debug, Clone)]
+pub struct ThreeWayConflict {
+    pub name: String,
+    pub base: Option<Callback>,
+    pub ours: Option<Callback>,
+    pub theirs: Option<Callback>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ThreeWayOutcome {
+    pub keyfile: FalconKeyfile,
+    pub conflicts: Vec<ThreeWayConflict>,
+    /// Joystick inputs both sides assigned differently.
+    pub joystick_conflicts: Vec<JoystickConflict>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ThreeWayMergeError {
+    pub conflicts: Vec<ThreeWayConflict>,
+    pub joystick_conflicts: Vec<JoystickConflict>,
+}
+
+impl fmt::Display for ThreeWayMergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<_> = self
+            .conflicts
+            .iter()
+            .map(|c| c.name.clone())
+            .chain(self.joystick_conflicts.iter().map(|c| c.to_string()))
+            .collect();
+        write!(f, "both sides changed {}", names.join(", "))
+    }
+}
+
+impl std::error::Error for ThreeWayMergeError {}
+
+/// What `FalconKeyfile::apply_overlay` changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OverlayReport {
+    /// Callbacks that got the chord of the overlay, sorted by name.
+    pub rebound: Vec<String>,
+    /// Callbacks of the overlay the base does not have, sorted by name.
+    pub unknown: Vec<String>,
+    /// Joystick inputs the overlay assigned.
+    pub joystick: usize,
+}
+
+/// Whether two optional entries are the same, ignoring descriptions.
+fn same_entry(a: Option<&Callback>, b: Option<&Callback>) -> bool {
+    match (a, b) {
+        (None, None) => true,
+        (Some(a), Some(b)) => a.same_binding(b),
+        _ => false,
+    }
+}
+
+/// A joystick input on one of the layers.
+type Slot = (JoystickInput, bool);
+
+/// The joystick bindings by input and layer, each in file order.
+fn slots(bindings: &[JoystickBinding]) -> HashMap<Slot, Vec<&JoystickBinding>> {
+    let mut slots: HashMap<Slot, Vec<&JoystickBinding>> = HashMap::new();
+    for binding in bindings {
+        slots
+            .entry((binding.input, binding.shifted))
+            .or_default()
+            .push(binding);
+    }
+    slots
+}
+
+/// Whether an input triggers the same callbacks on both sides, `None` when
+/// a side does not assign it.
+fn same_slot(a: Option<&Vec<&JoystickBinding>>, b: Option<&Vec<&JoystickBinding>>) -> bool {
+    let names = |slot: Option<&Vec<&JoystickBinding>>| -> Vec<String> {
+        slot.map_or_else(Vec::new, |bindings| {
+            bindings.iter().map(|b| b.callback.clone()).collect()
+        })
+    };
+    names(a) == names(b)
+}
+
+fn owned(slot: Option<&Vec<&JoystickBinding>>) -> Vec<JoystickBinding> {
+    slot.map_or_else(Vec::new, |bindings| {
+        bindings.iter().map(|b| (*b).clone()).collect()
+    })
+}
+
+/// Merges the joystick bindings of `theirs` into `ours` input by input. Inputs
+/// only one side assigns are taken as they are, inputs both sides assign
+/// differently are recorded in `conflicts` and get the bindings `resolve`
+/// picks. `None` from `resolve` stops the merge.
+fn merge_joystick(
+    ours: &[JoystickBinding],
+    theirs: &[JoystickBinding],
+    conflicts: &mut Vec<JoystickConflict>,
+    mut resolve: impl FnMut(&JoystickConflict) -> Option<Vec<JoystickBinding>>,
+) -> Option<Vec<JoystickBinding>> {
+    let our_slots = slots(ours);
+    let their_slots = slots(theirs);
+    let mut merged = ours.to_vec();
+    let mut seen = HashSet::new();
+    for binding in theirs {
+        let slot = (binding.input, binding.shifted);
+        if !seen.insert(slot) {
+            continue;
+        }
+        let t = their_slots.get(&slot);
+        match our_slots.get(&slot) {
+            None => merged.extend(owned(t)),
+            Some(o) if same_slot(Some(o), t) => {}
+            Some(o) => {
+                let conflict = JoystickConflict {
+                    input: binding.input,
+                    shifted: binding.shifted,
+                    ours: owned(Some(o)),
+                    theirs: owned(t),
+                };
+                let chosen = resolve(&conflict);
+                conflicts.push(conflict);
+                let chosen = chosen?;
+                if chosen
+                    .iter()
+                    .map(|b| &b.callback)
+                    .ne(o.iter().map(|b| &b.callback))
+                {
+                    merged.retain(|b| (b.input, b.shifted) != slot);
+                    merged.extend(chosen);
+                }
+            }
+        }
+    }
+    Some(merged)
+}
+
+impl FalconKeyfile {
+    /// Merges the callbacks of `other` into a copy of this key file. Callbacks
+    /// only present on one side are taken as they are, differing bindings are
+    /// resolved by `strategy`. Joystick inputs are merged the same way, by
+    /// input and shift layer.
+    pub fn merge(
+        &self,
+        other: &FalconKeyfile,
+        strategy: MergeStrategy,
+    ) -> Result<MergeOutcome, MergeError> {
+        let mut merged = self.callbacks.clone();
+        let mut conflicts = vec![];
+
+        for (name, theirs) in &other.callbacks {
+            match self.callbacks.get(name) {
+                None => {
+                    merged.insert(name.clone(), theirs.clone());
+                }
+                Some(ours) if !ours.same_binding(theirs) => {
+                    if strategy == MergeStrategy::PreferOther {
+                        merged.insert(name.clone(), theirs.clone());
+                    }
+                    conflicts.push(MergeConflict {
+                        ours: ours.clone(),
+                        theirs: theirs.clone(),
+                    });
+                }
+                Some(_) if strategy == MergeStrategy::PreferOther => {
+                    merged.insert(name.clone(), theirs.clone());
+                }
+                Some(_) => {}
+            }
+        }
+        conflicts.sort_by(|a, b| a.name().cmp(b.name()));
+
+        let mut joystick_conflicts = vec![];
+        let joystick = merge_joystick(
+            &self.joystick,
+            &other.joystick,
+            &mut joystick_conflicts,
+            |conflict| match strategy {
+                MergeStrategy::PreferOther => Some(conflict.theirs.clone()),
+                _ => Some(conflict.ours.clone()),
+            },
+        )
+        .unwrap_or_default();
+
+        if strategy == MergeStrategy::FailOnConflict
+            && !(conflicts.is_empty() && joystick_conflicts.is_empty())
+        {
+            return Err(MergeError {
+                conflicts,
+                joystick_conflicts,
+            });
+        }
+
+        let keyfile = self.merged(merged, joystick);
+        Ok(MergeOutcome {
+            keyfile,
+            conflicts,
+            joystick_conflicts,
+        })
+    }
+
+    /// A key file with these callbacks and joystick bindings and everything
+    /// else taken from this one.
+    fn merged(
+        &self,
+        callbacks: HashMap<String, Callback>,
+        joystick: Vec<JoystickBinding>,
+    ) -> FalconKeyfile {
+        let mut keyfile = FalconKeyfile::new(self.name.clone(), callbacks);
+        keyfile.do_nothing = self.do_nothing.clone();
+        keyfile.joystick = joystick;
+        keyfile.sections = self.sections.clone();
+        keyfile
+    }
+
+    /// Like `merge`, but every conflict is settled by `resolve`, called with
+    /// our and their callback in the order of the callback names. Callbacks
+    /// both sides bind the same way keep our description.
+    ///
+    /// Joystick inputs both sides assign to different callbacks are settled
+    /// afterwards by `resolve_joystick`, in the order of their bindings in
+    /// `other`.
+    pub fn merge_with(
+        &self,
+        other: &FalconKeyfile,
+        mut resolve: impl FnMut(&Callback, &Callback) -> MergeResolution,
+        mut resolve_joystick: impl FnMut(&JoystickConflict) -> JoystickResolution,
+    ) -> Result<MergeOutcome, MergeError> {
+        let mut merged = self.callbacks.clone();
+        let mut conflicts = vec![];
+
+        let mut names: Vec<_> = other.callbacks.keys().collect();
+        names.sort();
+        for name in names {
+            let theirs = &other.callbacks[name];
+            let Some(ours) = self.callbacks.get(name) else {
+                merged.insert(name.clone(), theirs.clone());
+                continue;
+            };
+            if ours.same_binding(theirs) {
+                continue;
+            }
+            conflicts.push(MergeConflict {
+                ours: ours.clone(),
+                theirs: theirs.clone(),
+            });
+            match resolve(ours, theirs) {
+                MergeResolution::Ours => {}
+                MergeResolution::Theirs => {
+                    merged.insert(name.clone(), theirs.clone());
+                }
+                MergeResolution::Custom(callback) => {
+                    merged.insert(
+                        name.clone(),
+                        Callback {
+                            name: name.clone(),
+                            ..callback
+                        },
+                    );
+                }
+                MergeResolution::Abort => {
+                    return Err(MergeError {
+                        conflicts,
+                        joystick_conflicts: vec![],
+                    });
+                }
+            }
+        }
+
+        let mut joystick_conflicts = vec![];
+        let joystick = merge_joystick(
+            &self.joystick,
+            &other.joystick,
+            &mut joystick_conflicts,
+            |conflict| match resolve_joystick(conflict) {
+                JoystickResolution::Ours => Some(conflict.ours.clone()),
+                JoystickResolution::Theirs => Some(conflict.theirs.clone()),
+                JoystickResolution::Custom(callback) => Some(vec![JoystickBinding {
+                    callback,
+                    input: conflict.input,
+                    shifted: conflict.shifted,
+                    description: None,
+                    section: conflict.ours.first().and_then(|b| b.section.clone()),
+                    line: None,
+                }]),
+                JoystickResolution::Abort => None,
+            },
+        );
+        let Some(joystick) = joystick else {
+            return Err(MergeError {
+                conflicts,
+                joystick_conflicts,
+            });
+        };
+
+        let keyfile = self.merged(merged, joystick);
+        Ok(MergeOutcome {
+            keyfile,
+            conflicts,
+            joystick_conflicts,
+        })
+    }
+
+    /// Merges `theirs` into this key file relative to their common ancestor
+    /// `base`, e.g. old defaults, new defaults and a customized file. A change
+    /// on only one side is taken over, including removals. Only callbacks both
+    /// sides changed differently are conflicts, resolved by `strategy`. The
+    /// same goes for joystick inputs, and the joystick bindings of callbacks
+    /// the merge removes are dropped.
+    pub fn merge_three_way(
+        &self,
+        base: &FalconKeyfile,
+        theirs: &FalconKeyfile,
+        strategy: MergeStrategy,
+    ) -> Result<ThreeWayOutcome, ThreeWayMergeError> {
+        let names: BTreeSet<&String> = base
+            .callbacks
+            .keys()
+            .chain(self.callbacks.keys())
+            .chain(theirs.callbacks.keys())
+            .collect();
+
+        let mut merged = HashMap::new();
+        let mut conflicts = vec![];
+        for name in names {
+            let b = base.callbacks.get(name);
+            let o = self.callbacks.get(name);
+            let t = theirs.callbacks.get(name);
+
+            let winner = if same_entry(o, t) || same_entry(b, t) {
+                o
+            } else if same_entry(b, o) {
+                t
+            } else {
+                conflicts.push(ThreeWayConflict {
+                    name: name.clone(),
+                    base: b.cloned(),
+                    ours: o.cloned(),
+                    theirs: t.cloned(),
+                });
+                match strategy {
+                    MergeStrategy::PreferOther => t,
+                    _ => o,
+                }
+            };
+            if let Some(callback) = winner {
+                merged.insert(name.clone(), callback.clone());
+            }
+        }
+
+        let (b, o, t) = (
+            slots(&base.joystick),
+            slots(&self.joystick),
+            slots(&theirs.joystick),
+        );
+        let mut inputs: Vec<Slot> = b.keys().chain(o.keys()).chain(t.keys()).copied().collect();
+        inputs.sort_by_key(|(input, shifted)| match *input {
+            JoystickInput::Button(button) => (*shifted, 0, button, 0),
+            JoystickInput::Pov { hat, direction } => (*shifted, 1, hat, direction),
+        });
+        inputs.dedup();
+        let mut joystick_conflicts = vec![];
+        let mut take_theirs = HashSet::new();
+        for slot in inputs {
+            let (b, o, t) = (b.get(&slot), o.get(&slot), t.get(&slot));
+            if same_slot(o, t) || same_slot(b, t) {
+                continue;
+            }
+            if !same_slot(b, o) {
+                joystick_conflicts.push(JoystickConflict {
+                    input: slot.0,
+                    shifted: slot.1,
+                    ours: owned(o),
+                    theirs: owned(t),
+                });
+                if strategy != MergeStrategy::PreferOther {
+                    continue;
+                }
+            }
+            take_theirs.insert(slot);
+        }
+        let known = |name: &String| {
+            base.callbacks.contains_key(name)
+                || self.callbacks.contains_key(name)
+                || theirs.callbacks.contains_key(name)
+        };
+        let joystick = self
+            .joystick
+            .iter()
+            .filter(|b| !take_theirs.contains(&(b.input, b.shifted)))
+            .chain(
+                theirs
+                    .joystick
+                    .iter()
+                    .filter(|b| take_theirs.contains(&(b.input, b.shifted))),
+            )
+            // removed callbacks take their joystick bindings with them
+            .filter(|b| merged.contains_key(&b.callback) || !known(&b.callback))
+            .cloned()
+            .collect();
+
+        if strategy == MergeStrategy::FailOnConflict
+            && !(conflicts.is_empty() && joystick_conflicts.is_empty())
+        {
+            return Err(ThreeWayMergeError {
+                conflicts,
+                joystick_conflicts,
+            });
+        }
+
+        let keyfile = self.merged(merged, joystick);
+        Ok(ThreeWayOutcome {
+            keyfile,
+            conflicts,
+            joystick_conflicts,
+        })
+    }
+
+    /// Rebinds the callbacks listed in `overlay`, a partial key file with
+    /// just the bindings to override. An unbound callback in the overlay is
+    /// unbound here too. Descriptions, visibility and sections stay those of
+    /// this file. A joystick input of the overlay replaces whatever this file
+    /// has on the same input. Callbacks this file does not have are skipped
+    /// and reported.
+    pub fn apply_overlay(&mut self, overlay: &FalconKeyfile) -> OverlayReport {
+        let mut report = OverlayReport::default();
+        for theirs in overlay.callbacks.values() {
+            match self.callbacks.get_mut(&theirs.name) {
+                Some(ours) => {
+                    if !ours.same_binding(theirs) {
+                        copy_binding(ours, theirs);
+                        report.rebound.push(theirs.name.clone());
+                    }
+                }
+                None => report.unknown.push(theirs.name.clone()),
+            }
+        }
+
+        for binding in &overlay.joystick {
+            if !self.callbacks.contains_key(&binding.callback) {
+                if !report.unknown.contains(&binding.callback) {
+                    report.unknown.push(binding.callback.clone());
+                }
+                continue;
+            }
+            self.joystick
+                .retain(|b| b.input != binding.input || b.shifted != binding.shifted);
+            let section = self.callbacks[&binding.callback].section.clone();
+            self.joystick.push(JoystickBinding {
+                section,
+                ..binding.clone()
+            });
+            report.joystick += 1;
+        }
+
+        report.rebound.sort();
+        report.unknown.sort();
+        self.reindex();
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Key, ParseOptions, parse_str};
+
+    fn keyfile(content: &str) -> FalconKeyfile {
+        parse_str(String::from("inline"), content, &ParseOptions::default())
+            .unwrap()
+            .keyfile
+    }
+
+    const MINE: &str = "# mine
+AFBrakesToggle -1 0 0x30 2 0 0 1 \"Brakes\"
+SimHookToggle -1 0 0x23 0 0 0 1 \"Hook\"
+";
+    const DEFAULTS: &str = "# new defaults
+AFBrakesToggle -1 0 0x30 0 0 0 1 \"Brakes\"
+SimHookToggle -1 0 0x23 0 0 0 1 \"Hook\"
+SimPilotToggle -1 0 0x19 0 0x2E 4 1 \"Autopilot\"
+";
+
+    #[test]
+    fn merge_with_strategies() {
+        let mine = keyfile(MINE);
+        let defaults = keyfile(DEFAULTS);
+
+        let outcome = defaults.merge(&mine, MergeStrategy::PreferOther).unwrap();
+        assert_eq!(outcome.conflicts.len(), 1);
+        assert_eq!(outcome.conflicts[0].name(), "AFBrakesToggle");
+        let brakes = outcome.keyfile.callback("AFBrakesToggle").unwrap();
+        assert_eq!(brakes.primary_combo().to_string(), "LCtrl+B");
+        assert_eq!(
+            outcome
+                .keyfile
+                .callback("SimPilotToggle")
+                .unwrap()
+                .readable_key_code,
+            Key::P
+        );
+
+        let outcome = defaults.merge(&mine, MergeStrategy::PreferSelf).unwrap();
+        let brakes = outcome.keyfile.callback("AFBrakesToggle").unwrap();
+        assert_eq!(brakes.primary_combo().to_string(), "B");
+
+        let error = defaults
+            .merge(&mine, MergeStrategy::FailOnConflict)
+            .unwrap_err();
+        assert_eq!(error.to_string(), "conflicting bindings for AFBrakesToggle");
+    }
+
+    #[test]
+    fn merge_joystick_bindings() {
+        let mine = keyfile(&format!(
+            "{}SimHookToggle 3 -1 -2 0 0x0 -1\nAFBrakesToggle 4 -1 -2 0 0x0 -1\n",
+            MINE
+        ));
+        let defaults = keyfile(&format!(
+            "{}SimPilotToggle 5 -1 -2 0 0x0 -1\nSimHookToggle 4 -1 -2 0 0x0 -1\n",
+            DEFAULTS
+        ));
+        let inputs = |keyfile: &FalconKeyfile| -> Vec<(String, JoystickInput)> {
+            keyfile
+                .joystick_bindings()
+                .iter()
+                .map(|b| (b.callback.clone(), b.input))
+                .collect()
+        };
+
+        let outcome = mine.merge(&defaults, MergeStrategy::PreferSelf).unwrap();
+        assert_eq!(
+            inputs(&outcome.keyfile),
+            vec![
+                (String::from("SimHookToggle"), JoystickInput::Button(3)),
+                (String::from("AFBrakesToggle"), JoystickInput::Button(4)),
+                (String::from("SimPilotToggle"), JoystickInput::Button(5)),
+            ]
+        );
+        assert_eq!(outcome.joystick_conflicts.len(), 1);
+        assert_eq!(outcome.joystick_conflicts[0].to_string(), "DX5");
+
+        let outcome = mine.merge(&defaults, MergeStrategy::PreferOther).unwrap();
+        assert_eq!(
+            inputs(&outcome.keyfile)[1],
+            (String::from("SimPilotToggle"), JoystickInput::Button(5))
+        );
+        assert_eq!(
+            inputs(&outcome.keyfile)[2],
+            (String::from("SimHookToggle"), JoystickInput::Button(4))
+        );
+        let error = mine
+            .merge(&defaults, MergeStrategy::FailOnConflict)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "conflicting bindings for AFBrakesToggle, DX5"
+        );
+    }
+
+    #[test]
+    fn merge_with_a_resolver() {
+        let mine = keyfile(MINE);
+        let defaults = keyfile(
+            "# new defaults
+AFBrakesToggle -1 0 0x30 0 0 0 1 \"Brakes\"
+SimHookToggle -1 0 0x23 4 0 0 1 \"Hook\"
+SimPilotToggle -1 0 0x19 0 0x2E 4 1 \"Autopilot\"
+",
+        );
+
+        let mut asked = vec![];
+        let outcome = mine
+            .merge_with(
+                &defaults,
+                |ours, theirs| {
+                    asked.push(ours.name.clone());
+                    match ours.name.as_str() {
+                        "AFBrakesToggle" => MergeResolution::Theirs,
+                        _ => {
+                            let mut custom = theirs.clone();
+                            custom.modifiers = crate::ModifierSet::from_mask(1);
+                            MergeResolution::Custom(custom)
+                        }
+                    }
+                },
+                |_| JoystickResolution::Ours,
+            )
+            .unwrap();
+        assert_eq!(asked, vec!["AFBrakesToggle", "SimHookToggle"]);
+        assert_eq!(outcome.conflicts.len(), 2);
+        let chord = |name| {
+            outcome
+                .keyfile
+                .callback(name)
+                .unwrap()
+                .chord()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(chord("AFBrakesToggle"), "B");
+        assert_eq!(chord("SimHookToggle"), "LShift+H");
+        assert!(outcome.keyfile.contains("SimPilotToggle"));
+
+        let error = mine
+            .merge_with(
+                &defaults,
+                |_, _| MergeResolution::Abort,
+                |_| JoystickResolution::Ours,
+            )
+            .unwrap_err();
+        assert_eq!(error.conflicts.len(), 1);
+    }
+
+    #[test]
+    fn merge_joystick_bindings_with_a_resolver() {
+        let mine = keyfile(&format!(
+            "{}SimHookToggle 3 -1 -2 0 0x0 -1\nAFBrakesToggle 4 -1 -2 0 0x0 -1\n",
+            MINE
+        ));
+        let defaults = keyfile(&format!(
+            "{}SimPilotToggle 4 -1 -2 0 0x0 -1\nSimHookToggle 5 -1 -2 0 0x0 -1\n",
+            DEFAULTS
+        ));
+        let callback_on = |keyfile: &FalconKeyfile, button| {
+            keyfile
+                .joystick_bindings()
+                .iter()
+                .find(|b| b.input == JoystickInput::Button(button))
+                .map(|b| b.callback.clone())
+        };
+
+        let mut asked = vec![];
+        let outcome = mine
+            .merge_with(
+                &defaults,
+                |ours, _| MergeResolution::Custom(ours.clone()),
+                |conflict| {
+                    asked.push(conflict.clone());
+                    JoystickResolution::Theirs
+                },
+            )
+            .unwrap();
+        assert_eq!(asked, outcome.joystick_conflicts);
+        assert_eq!(asked.len(), 1);
+        assert_eq!(asked[0].to_string(), "DX5");
+        assert_eq!(asked[0].ours[0].callback, "AFBrakesToggle");
+        assert_eq!(asked[0].theirs[0].callback, "SimPilotToggle");
+        assert_eq!(
+            callback_on(&outcome.keyfile, 4).as_deref(),
+            Some("SimPilotToggle")
+        );
+        assert_eq!(
+            callback_on(&outcome.keyfile, 5).as_deref(),
+            Some("SimHookToggle")
+        );
+
+        let outcome = mine
+            .merge_with(
+                &defaults,
+                |ours, _| MergeResolution::Custom(ours.clone()),
+                |_| JoystickResolution::Custom(String::from("SimHookToggle")),
+            )
+            .unwrap();
+        assert_eq!(
+            callback_on(&outcome.keyfile, 4).as_deref(),
+            Some("SimHookToggle")
+        );
+        let error = mine
+            .merge_with(
+                &defaults,
+                |_, _| MergeResolution::Ours,
+                |_| JoystickResolution::Abort,
+            )
+            .unwrap_err();
+        assert_eq!(error.joystick_conflicts.len(), 1);
+    }
+
+    #[test]
+    fn apply_an_overlay() {
+        let mut base = keyfile(DEFAULTS);
+        let overlay = keyfile(
+            "# squadron overrides
+AFBrakesToggle -1 0 0x30 2 0 0 1
+SimHookToggle 3 -1 -2 0 0x0 -1
+SimPickle 4 -1 -2 0 0x0 -1
+SimNoSuchCallback -1 0 0x10 0 0 0 1
+",
+        );
+        let report = base.apply_overlay(&overlay);
+        assert_eq!(report.rebound, vec!["AFBrakesToggle"]);
+        assert_eq!(report.unknown, vec!["SimNoSuchCallback", "SimPickle"]);
+        assert_eq!(report.joystick, 1);
+        assert_eq!(base.joystick_bindings()[0].callback, "SimHookToggle");
+
+        let brakes = base.callback("AFBrakesToggle").unwrap();
+        assert_eq!(brakes.primary_combo().to_string(), "LCtrl+B");
+        assert_eq!(brakes.description.as_deref(), Some("Brakes"));
+        assert_eq!(base.callbacks_for_combo(&brakes.primary_combo()).len(), 1);
+        assert!(base.apply_overlay(&overlay).rebound.is_empty());
+    }
+
+    #[test]
+    fn three_way_merge_only_reports_true_conflicts() {
+        let old_defaults = keyfile(
+            "# old defaults
+AFBrakesToggle -1 0 0x30 0 0 0 1 \"Brakes\"
+SimHookToggle -1 0 0x23 0 0 0 1 \"Hook\"
+AFGearToggle -1 0 0x22 0 0 0 1 \"Gear\"
+AFFlapsToggle -1 0 0x21 0 0 0 1 \"Flaps\"
+",
+        );
+        let new_defaults = keyfile(
+            "# new defaults
+AFBrakesToggle -1 0 0x30 0 0 0 1 \"Brakes\"
+SimHookToggle -1 0 0x23 4 0 0 1 \"Hook\"
+AFGearToggle -1 0 0x22 4 0 0 1 \"Gear\"
+SimPilotToggle -1 0 0x19 0 0x2E 4 1 \"Autopilot\"
+",
+        );
+        let mine = keyfile(
+            "# mine
+AFBrakesToggle -1 0 0x30 2 0 0 1 \"Brakes\"
+SimHookToggle -1 0 0x23 0 0 0 1 \"Hook\"
+AFGearToggle -1 0 0x22 2 0 0 1 \"Gear\"
+AFFlapsToggle -1 0 0x21 0 0 0 1 \"Flaps\"
+",
+        );
+
+        let outcome = mine
+            .merge_three_way(&old_defaults, &new_defaults, MergeStrategy::PreferSelf)
+            .unwrap();
+        let combo = |name| {
+            outcome
+                .keyfile
+                .callback(name)
+                .map(|c| c.primary_combo().to_string())
+        };
+        // only I changed it
+        assert_eq!(combo("AFBrakesToggle").as_deref(), Some("LCtrl+B"));
+        // only the defaults changed it
+        assert_eq!(combo("SimHookToggle").as_deref(), Some("LAlt+H"));
+        assert_eq!(combo("SimPilotToggle").as_deref(), Some("P"));
+        assert_eq!(combo("AFFlapsToggle"), None);
+        // both changed it
+        assert_eq!(combo("AFGearToggle").as_deref(), Some("LCtrl+G"));
+        assert_eq!(outcome.conflicts.len(), 1);
+        assert_eq!(outcome.conflicts[0].name, "AFGearToggle");
+
+        assert!(
+            mine.merge_three_way(&old_defaults, &new_defaults, MergeStrategy::FailOnConflict)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn three_way_merge_of_joystick_bindings() {
+        let old_defaults = keyfile(
+            "# old defaults
+AFBrakesToggle -1 0 0x30 0 0 0 1 \"Brakes\"
+AFFlapsToggle -1 0 0x21 0 0 0 1 \"Flaps\"
+AFBrakesToggle 0 -1 -2 0 0x0 -1
+AFFlapsToggle 1 -1 -2 0 0x0 -1
+",
+        );
+        let new_defaults = keyfile(
+            "# new defaults
+AFBrakesToggle -1 0 0x30 0 0 0 1 \"Brakes\"
+SimHookToggle -1 0 0x23 0 0 0 1 \"Hook\"
+AFBrakesToggle 0 -1 -2 0 0x0 -1
+SimHookToggle 2 -1 -2 0 0x0 -1
+",
+        );
+        let mine = keyfile(
+            "# mine
+AFBrakesToggle -1 0 0x30 0 0 0 1 \"Brakes\"
+AFFlapsToggle -1 0 0x21 0 0 0 1 \"Flaps\"
+AFFlapsToggle 0 -1 -2 0 0x0 -1
+AFBrakesToggle 256 -1 -2 0 0x0 -1
+AFFlapsToggle 1 -1 -2 0 0x0 -1
+",
+        );
+
+        let outcome = mine
+            .merge_three_way(&old_defaults, &new_defaults, MergeStrategy::PreferSelf)
+            .unwrap();
+        let bindings: Vec<_> = outcome
+            .keyfile
+            .joystick_bindings()
+            .iter()
+            .map(|b| (b.callback.as_str(), b.input, b.shifted))
+            .collect();
+        // the flaps were removed upstream and take my DX1 with them
+        assert_eq!(
+            bindings,
+            vec![
+                ("AFBrakesToggle", JoystickInput::Button(0), true),
+                ("SimHookToggle", JoystickInput::Button(2), false),
+            ]
+        );
+        assert!(!outcome.keyfile.contains("AFFlapsToggle"));
+        assert!(outcome.joystick_conflicts.is_empty());
+    }
+}
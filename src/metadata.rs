@@ -0,0 +1,84 @@
+#[cfg(feature = "callback-db")]
+use crate::BmsVersion;
+use crate::FalconKeyfile;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Where a key file was read from, see `FalconKeyfile::metadata`. Only
+/// `FalconKeyfile::from_path` knows the path, the timestamp and the version,
+/// the other parse entry points fill in the line count.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metadata {
+    pub(crate) path: Option<PathBuf>,
+    pub(crate) modified: Option<SystemTime>,
+    pub(crate) line_count: usize,
+    #[cfg(feature = "callback-db")]
+    pub(crate) version: Option<BmsVersion>,
+}
+
+impl Metadata {
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// When the file was last modified, if the file system tells.
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    /// The number of lines parsed, including comments and blank lines.
+    pub fn line_count(&self) -> usize {
+        self.line_count
+    }
+
+    /// The BMS version the file was most likely made for.
+    #[cfg(feature = "callback-db")]
+    pub fn version(&self) -> Option<BmsVersion> {
+        self.version
+    }
+}
+
+impl FalconKeyfile {
+    /// The name given when parsing, the file name for `from_path`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_in_metadata_from_path() {
+        let keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let metadata = keyfile.metadata();
+        assert_eq!(keyfile.name(), "basic.key");
+        assert_eq!(metadata.path(), Some(Path::new("test-data/basic.key")));
+        assert!(metadata.modified().is_some());
+        let lines = std::fs::read_to_string("test-data/basic.key")
+            .unwrap()
+            .lines()
+            .count();
+        assert_eq!(metadata.line_count(), lines);
+        #[cfg(feature = "callback-db")]
+        assert_eq!(
+            metadata.version(),
+            keyfile.detect_version().map(|guess| guess.version)
+        );
+
+        let parsed = crate::parse_str(
+            String::from("inline"),
+            "# inline\nAFBrakesToggle -1 0 0x30 0 0 0 1\n",
+            &crate::ParseOptions::default(),
+        )
+        .unwrap()
+        .keyfile;
+        assert_eq!(parsed.metadata().path(), None);
+        assert_eq!(parsed.metadata().line_count(), 2);
+    }
+}
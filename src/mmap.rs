@@ -0,0 +1,89 @@
+//! Parsing key files straight from a memory map instead of reading them into
+//! a buffer first.
+//!
+//! `MappedKeyfile::text` borrows from the mapping for UTF-8 and ASCII files,
+//! so scanning it with `raw_bindings` copies nothing. Files in other
+//! encodings are decoded into a `String` once.
+
+use crate::encoding::decode;
+use crate::{KeyFileError, ParseOptions, ParseReport, PathError, parse_str};
+use memmap2::Mmap;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// A key file mapped into memory.
+#[derive(Debug)]
+pub struct MappedKeyfile {
+    path: PathBuf,
+    map: Mmap,
+}
+
+impl MappedKeyfile {
+    /// Maps the file at `path`. The file must not be changed while it is
+    /// mapped, BMS only ever replaces key files as a whole.
+    pub fn open(path: impl AsRef<Path>) -> Result<MappedKeyfile, PathError> {
+        let path = path.as_ref().to_path_buf();
+        let in_file = |error| PathError {
+            path: path.clone(),
+            error,
+        };
+        let read_error = |e: std::io::Error| match e.kind() {
+            ErrorKind::NotFound => KeyFileError::NotFound,
+            ErrorKind::PermissionDenied => KeyFileError::PermissionDenied,
+            _ => KeyFileError::ReadError(e),
+        };
+
+        let file = File::open(&path).map_err(|e| in_file(read_error(e)))?;
+        // SAFETY: the mapping is read only, see the documentation of `open`.
+        let map = unsafe { Mmap::map(&file) }.map_err(|e| in_file(read_error(e)))?;
+        Ok(MappedKeyfile { path, map })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The decoded text, borrowed from the mapping where possible.
+    pub fn text(&self) -> Cow<'_, str> {
+        decode(&self.map)
+    }
+
+    /// Parses the mapped file, naming the key file after it.
+    pub fn parse(&self, options: &ParseOptions) -> Result<ParseReport, PathError> {
+        let name = self
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.path.display().to_string());
+        parse_str(name, &self.text(), options).map_err(|error| PathError {
+            path: self.path.clone(),
+            error,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raw_bindings;
+
+    #[test]
+    fn scan_mapped_file() {
+        let mapped = MappedKeyfile::open("test-data/basic.key").unwrap();
+        let text = mapped.text();
+        assert!(matches!(text, Cow::Borrowed(_)));
+
+        let toggle = raw_bindings(&text)
+            .find(|b| b.callback == "SimPilotToggle")
+            .unwrap();
+        assert_eq!(toggle.key_code, 0x19);
+        assert_eq!(toggle.combo_modifiers, 4);
+        assert_eq!(toggle.description, Some("SIM: Toggle Pilot Model"));
+
+        let report = mapped.parse(&ParseOptions::default()).unwrap();
+        let names: std::collections::HashSet<_> = raw_bindings(&text).map(|b| b.callback).collect();
+        assert_eq!(names.len(), report.keyfile.len());
+    }
+}
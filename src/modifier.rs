@@ -0,0 +1,183 @@
+use std::ops::{BitAnd, BitOr, Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Modifier {
+    LSHIFT,
+    LCONTROL,
+    LALT,
+}
+
+impl Modifier {
+    /// All modifiers in the order of their bits in a modifier mask.
+    pub const ALL: [Modifier; 3] = [Modifier::LSHIFT, Modifier::LCONTROL, Modifier::LALT];
+
+    /// The bit of this modifier in a key file's modifier mask.
+    pub fn mask(&self) -> u16 {
+        match self {
+            Modifier::LSHIFT => 1,
+            Modifier::LCONTROL => 2,
+            Modifier::LALT => 4,
+        }
+    }
+}
+
+/// A set of modifiers, stored like the modifier mask of a key file. Unlike a
+/// `Vec<Modifier>` equality does not depend on order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(from = "Vec<Modifier>", into = "Vec<Modifier>")
+)]
+pub struct ModifierSet(u16);
+
+impl ModifierSet {
+    pub const EMPTY: ModifierSet = ModifierSet(0);
+    pub const ALL: ModifierSet = ModifierSet(1 | 2 | 4);
+
+    /// Builds a set from a modifier mask, ignoring bits that are not a `Modifier`.
+    pub fn from_mask(mask: u16) -> ModifierSet {
+        ModifierSet(mask & ModifierSet::ALL.0)
+    }
+
+    pub const fn to_mask(self) -> u16 {
+        self.0
+    }
+
+    pub fn contains(self, modifier: Modifier) -> bool {
+        self.0 & modifier.mask() != 0
+    }
+
+    pub fn insert(&mut self, modifier: Modifier) {
+        self.0 |= modifier.mask();
+    }
+
+    pub fn remove(&mut self, modifier: Modifier) {
+        self.0 &= !modifier.mask();
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn union(self, other: ModifierSet) -> ModifierSet {
+        ModifierSet(self.0 | other.0)
+    }
+
+    pub fn intersection(self, other: ModifierSet) -> ModifierSet {
+        ModifierSet(self.0 & other.0)
+    }
+
+    pub fn difference(self, other: ModifierSet) -> ModifierSet {
+        ModifierSet(self.0 & !other.0)
+    }
+
+    pub fn is_subset(self, other: ModifierSet) -> bool {
+        self.difference(other).is_empty()
+    }
+
+    /// The modifiers in the set, in mask order.
+    pub fn iter(self) -> impl Iterator<Item = Modifier> {
+        Modifier::ALL.into_iter().filter(move |m| self.contains(*m))
+    }
+
+    pub fn to_vec(self) -> Vec<Modifier> {
+        self.iter().collect()
+    }
+}
+
+impl From<Modifier> for ModifierSet {
+    fn from(modifier: Modifier) -> ModifierSet {
+        ModifierSet(modifier.mask())
+    }
+}
+
+impl From<ModifierSet> for Vec<Modifier> {
+    fn from(set: ModifierSet) -> Vec<Modifier> {
+        set.to_vec()
+    }
+}
+
+impl From<Vec<Modifier>> for ModifierSet {
+    fn from(modifiers: Vec<Modifier>) -> ModifierSet {
+        modifiers.into_iter().collect()
+    }
+}
+
+impl FromIterator<Modifier> for ModifierSet {
+    fn from_iter<I: IntoIterator<Item = Modifier>>(iter: I) -> ModifierSet {
+        let mut set = ModifierSet::EMPTY;
+        for modifier in iter {
+            set.insert(modifier);
+        }
+        set
+    }
+}
+
+impl BitOr for ModifierSet {
+    type Output = ModifierSet;
+
+    fn bitor(self, other: ModifierSet) -> ModifierSet {
+        self.union(other)
+    }
+}
+
+impl BitOr<Modifier> for ModifierSet {
+    type Output = ModifierSet;
+
+    fn bitor(self, other: Modifier) -> ModifierSet {
+        self.union(other.into())
+    }
+}
+
+impl BitAnd for ModifierSet {
+    type Output = ModifierSet;
+
+    fn bitand(self, other: ModifierSet) -> ModifierSet {
+        self.intersection(other)
+    }
+}
+
+impl Sub for ModifierSet {
+    type Output = ModifierSet;
+
+    fn sub(self, other: ModifierSet) -> ModifierSet {
+        self.difference(other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_round_trip() {
+        for mask in 0..8 {
+            assert_eq!(ModifierSet::from_mask(mask).to_mask(), mask);
+        }
+        assert_eq!(ModifierSet::from_mask(9).to_mask(), 1);
+    }
+
+    #[test]
+    fn order_insensitive_set_operations() {
+        let ctrl_shift: ModifierSet = [Modifier::LCONTROL, Modifier::LSHIFT].into_iter().collect();
+        let shift_ctrl: ModifierSet = [Modifier::LSHIFT, Modifier::LCONTROL].into_iter().collect();
+        assert_eq!(ctrl_shift, shift_ctrl);
+        assert_eq!(
+            ctrl_shift.to_vec(),
+            vec![Modifier::LSHIFT, Modifier::LCONTROL]
+        );
+
+        let alt = ModifierSet::from(Modifier::LALT);
+        assert_eq!((ctrl_shift | alt), ModifierSet::ALL);
+        assert_eq!((ModifierSet::ALL - alt), ctrl_shift);
+        assert_eq!((ctrl_shift & alt), ModifierSet::EMPTY);
+        assert!(alt.is_subset(ModifierSet::ALL));
+        assert_eq!(ModifierSet::ALL.len(), 3);
+    }
+}
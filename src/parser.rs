@@ -0,0 +1,833 @@
+use crate::diagnostics::{Diagnostics, Level, LogDiagnostics, report};
+use crate::encoding::decode;
+use crate::joystick::SHIFT_OFFSET;
+use crate::{
+    Callback, FalconKeyfile, JoystickBinding, JoystickInput, Key, KeyFileError, ModifierSet,
+    Visibility, is_unmatched_key_code,
+};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+
+pub fn parse(
+    name: String,
+    key_file: &File,
+    options: &ParseOptions,
+) -> Result<ParseReport, KeyFileError> {
+    parse_reader(name, BufReader::new(key_file), options)
+}
+
+/// Parses a key file that is already held in memory.
+pub fn parse_str(
+    name: String,
+    content: &str,
+    options: &ParseOptions,
+) -> Result<ParseReport, KeyFileError> {
+    parse_str_with(name, content, options, &mut LogDiagnostics)
+}
+
+/// Like `parse_str`, but reports to `diagnostics` instead of the `log`
+/// crate.
+pub fn parse_str_with(
+    name: String,
+    content: &str,
+    options: &ParseOptions,
+    diagnostics: &mut dyn Diagnostics,
+) -> Result<ParseReport, KeyFileError> {
+    let content = content.strip_prefix('\u{FEFF}').unwrap_or(content);
+    let mut parser = Parser::new(options, diagnostics);
+    for line in content.lines() {
+        parser.feed(line)?;
+    }
+    parser.finish(name)
+}
+
+/// Parses as much of a key file as possible. Lines that cannot be parsed are
+/// skipped and returned with their text, however many there are. Only an
+/// empty file fails.
+pub fn parse_partial(name: String, content: &str) -> Result<PartialParse, KeyFileError> {
+    let report = parse_str(name, content, &ParseOptions::lenient())?;
+    let lines: Vec<_> = content.lines().collect();
+    let failures = report
+        .warnings
+        .into_iter()
+        .filter_map(|warning| match warning {
+            ParseWarning::MalformedLine { line, message } => Some(LineError {
+                line,
+                text: String::from(lines[line - 1]),
+                message,
+            }),
+            _ => None,
+        })
+        .collect();
+    Ok(PartialParse {
+        keyfile: report.keyfile,
+        failures,
+    })
+}
+
+/// Parses a key file from any buffered reader. UTF-8, UTF-16 and
+/// Windows-1252 encoded files are detected and decoded transparently.
+pub fn parse_reader(
+    name: String,
+    mut reader: impl BufRead,
+    options: &ParseOptions,
+) -> Result<ParseReport, KeyFileError> {
+    let mut bytes = vec![];
+    reader
+        .read_to_end(&mut bytes)
+        .map_err(KeyFileError::ReadError)?;
+    parse_str(name, &decode(&bytes), options)
+}
+
+/// Parses a key file from an async reader, sharing the line parser with the
+/// blocking entry points. The encoding is detected from the start of the file
+/// and lines are parsed as they arrive.
+#[cfg(feature = "tokio")]
+pub async fn parse_async(
+    name: String,
+    mut reader: impl tokio::io::AsyncBufRead + Unpin,
+    options: &ParseOptions,
+) -> Result<ParseReport, KeyFileError> {
+    use tokio::io::AsyncBufReadExt;
+
+    let head = reader.fill_buf().await.map_err(KeyFileError::ReadError)?;
+    let (encoding, bom) = crate::encoding::sniff_start(head);
+    reader.consume(bom);
+
+    let mut diagnostics = LogDiagnostics;
+    let mut parser = Parser::new(options, &mut diagnostics);
+    let mut bytes = vec![];
+    loop {
+        let read = reader
+            .read_until(b'\n', &mut bytes)
+            .await
+            .map_err(KeyFileError::ReadError)?;
+        let eof = read == 0 || bytes.last() != Some(&b'\n');
+        // a UTF-16 newline is followed by its other byte
+        if !eof && bytes.len() % encoding.unit() != 0 {
+            let next = reader
+                .fill_buf()
+                .await
+                .map_err(KeyFileError::ReadError)?
+                .first()
+                .copied();
+            if let Some(byte) = next {
+                bytes.push(byte);
+                reader.consume(1);
+            }
+        }
+        if !eof && !encoding.ends_line(&bytes) {
+            continue;
+        }
+        if !bytes.is_empty() {
+            parser.feed(&encoding.decode_line(&bytes))?;
+            bytes.clear();
+        }
+        if eof {
+            break;
+        }
+    }
+    parser.finish(name)
+}
+
+/// Line by line parsing state, independent of where the lines come from.
+pub(crate) struct Parser<'a> {
+    options: &'a ParseOptions,
+    diagnostics: &'a mut dyn Diagnostics,
+    ln: usize,
+    keycodes_by_callback: HashMap<String, Callback>,
+    do_nothing: Vec<Callback>,
+    joystick: Vec<JoystickBinding>,
+    warnings: Vec<ParseWarning>,
+    errors: usize,
+    section: Option<String>,
+    sections: Vec<String>,
+}
+
+impl<'a> Parser<'a> {
+    pub(crate) fn new(
+        options: &'a ParseOptions,
+        diagnostics: &'a mut dyn Diagnostics,
+    ) -> Parser<'a> {
+        Parser {
+            options,
+            diagnostics,
+            ln: 0,
+            keycodes_by_callback: HashMap::new(),
+            do_nothing: vec![],
+            joystick: vec![],
+            warnings: vec![],
+            errors: 0,
+            section: None,
+            sections: vec![],
+        }
+    }
+
+    /// Feeds the next line of the file, failing only if parsing has to stop.
+    pub(crate) fn feed(&mut self, line: &str) -> Result<(), KeyFileError> {
+        self.ln += 1;
+        let ln = self.ln;
+        let options = self.options;
+
+        // the first line is the file's banner
+        if ln == 1 || line.trim().is_empty() || line.starts_with("#") {
+            return Ok(());
+        }
+        if line.starts_with("SimDoNothing") {
+            if let Some(section) = parse_section(line) {
+                if !self.sections.contains(&section) {
+                    self.sections.push(section.clone());
+                }
+                self.section = Some(section);
+            }
+            if !options.keep_do_nothing {
+                return Ok(());
+            }
+        }
+
+        let callback = match parse_line(ln, line, options, &mut self.warnings, self.diagnostics) {
+            Ok(callback) => callback,
+            Err(message) if options.strict => {
+                return Err(KeyFileError::ParseError { line: ln, message });
+            }
+            Err(message) => {
+                self.errors += 1;
+                if options.max_errors.is_some_and(|max| self.errors > max) {
+                    return Err(KeyFileError::TooManyErrors(self.errors));
+                }
+                self.warnings
+                    .push(ParseWarning::MalformedLine { line: ln, message });
+                return Ok(());
+            }
+        };
+
+        let mut callback = match callback {
+            Some(Line::Key(callback)) => callback,
+            Some(Line::Joystick(mut binding)) => {
+                binding.section = self.section.clone();
+                self.joystick.push(binding);
+                return Ok(());
+            }
+            None => return Ok(()),
+        };
+        callback.section = self.section.clone();
+        if options.keep_raw_lines {
+            callback.raw_line = Some(String::from(line));
+        }
+        if options.skip_unknown_keycodes
+            && (is_unmatched_key_code(callback.key_code)
+                || is_unmatched_key_code(callback.combo_key_code))
+        {
+            report(self.diagnostics, Level::Debug, Some(ln), || {
+                format!(
+                    "Skipping {} with unknown keycode on line {}",
+                    callback.name, ln
+                )
+            });
+            return Ok(());
+        }
+
+        if callback.name == "SimDoNothing" {
+            self.do_nothing.push(callback);
+            return Ok(());
+        }
+        if let Some(previous) = self.keycodes_by_callback.get(&callback.name) {
+            let previous_line = previous.line.unwrap_or_default();
+            if options.deny_duplicates {
+                return Err(KeyFileError::DuplicateCallback {
+                    name: callback.name,
+                    previous_line,
+                    line: ln,
+                });
+            }
+            self.warnings.push(ParseWarning::DuplicateCallback {
+                line: ln,
+                previous_line,
+                name: callback.name.clone(),
+            });
+        }
+        self.keycodes_by_callback
+            .insert(callback.name.clone(), callback);
+        Ok(())
+    }
+
+    pub(crate) fn finish(self, name: String) -> Result<ParseReport, KeyFileError> {
+        if self.ln == 0 {
+            return Err(KeyFileError::Empty);
+        }
+
+        for warning in &self.warnings {
+            report(
+                self.diagnostics,
+                Level::Warning,
+                Some(warning.line()),
+                || warning.to_string(),
+            );
+        }
+        report(self.diagnostics, Level::Debug, None, || {
+            format!(
+                "Parsed key file with {} callbacks.",
+                self.keycodes_by_callback.len()
+            )
+        });
+
+        let mut keyfile = FalconKeyfile::new(name, self.keycodes_by_callback);
+        keyfile.do_nothing = self.do_nothing;
+        keyfile.joystick = self.joystick;
+        keyfile.sections = self.sections;
+        keyfile.ignore_case = self.options.ignore_case;
+        keyfile.metadata.line_count = self.ln;
+        Ok(ParseReport {
+            keyfile,
+            warnings: self.warnings,
+        })
+    }
+}
+
+/// A binding read from a single line.
+enum Line {
+    Key(Callback),
+    Joystick(JoystickBinding),
+}
+
+/// The leading whitespace separated columns of a line, borrowed from it.
+/// The description, if any, starts somewhere after them.
+struct Columns<'a> {
+    columns: [&'a str; Columns::MAX],
+    len: usize,
+}
+
+impl<'a> Columns<'a> {
+    /// Callback, four key or DX columns, the combo columns and visibility.
+    const MAX: usize = 8;
+
+    fn new(line: &'a str) -> Columns<'a> {
+        let mut columns = [""; Columns::MAX];
+        let mut len = 0;
+        for (column, token) in columns.iter_mut().zip(line.split_whitespace()) {
+            *column = token;
+            len += 1;
+        }
+        Columns { columns, len }
+    }
+
+    /// The columns of a legacy line, with the missing key or DX column
+    /// filled in as `0` so that it reads like a BMS keyboard line.
+    fn legacy(line: &'a str) -> Columns<'a> {
+        let mut columns = [""; Columns::MAX];
+        let mut len = 0;
+        let tokens = line.split_whitespace();
+        let key = tokens.clone().nth(2).map(|_| "0");
+        let tokens = tokens.clone().take(2).chain(key).chain(tokens.skip(2));
+        for (column, token) in columns.iter_mut().zip(tokens) {
+            *column = token;
+            len += 1;
+        }
+        Columns { columns, len }
+    }
+
+    fn get(&self, index: usize) -> Option<&'a str> {
+        self.columns[..self.len].get(index).copied()
+    }
+}
+
+/// Columns past the end of the line are empty rather than out of bounds, so
+/// a short line fails to parse as a number instead of panicking.
+impl<'a> std::ops::Index<usize> for Columns<'a> {
+    type Output = &'a str;
+
+    fn index(&self, index: usize) -> &&'a str {
+        self.columns.get(index).unwrap_or(&"")
+    }
+}
+
+/// Parses a single non-comment line, returning `None` for lines that do not
+/// describe a binding and an error message for malformed lines. When
+/// strict, modifier masks with bits other than shift, control and alt are
+/// malformed too.
+fn parse_line(
+    ln: usize,
+    line: &str,
+    options: &ParseOptions,
+    warnings: &mut Vec<ParseWarning>,
+    diagnostics: &mut dyn Diagnostics,
+) -> Result<Option<Line>, String> {
+    let strict = options.strict;
+    let stuff = match options.dialect {
+        Dialect::Bms => Columns::new(line),
+        Dialect::Legacy => Columns::legacy(line),
+    };
+    report(diagnostics, Level::Trace, Some(ln), || {
+        format!(
+            "Parsing line {}, tokens: {:?}",
+            ln,
+            &stuff.columns[..stuff.len]
+        )
+    });
+
+    if stuff.len < 7 {
+        return Err(format!(
+            "Expected at least 7 columns but found {}",
+            stuff.len
+        ));
+    }
+
+    let is_key: i64 = stuff[2].parse().map_err(|_| {
+        format!(
+            "Expected key identifier to be a number but was '{}'",
+            stuff[2]
+        )
+    })?;
+    if is_key != 0 {
+        return parse_joystick_line(ln, &stuff, line);
+    }
+
+    let raw = parse_raw_binding(ln, &stuff, line)?;
+    for code in [raw.key_code, raw.combo_key_code] {
+        if is_unmatched_key_code(code) {
+            warnings.push(ParseWarning::UnknownKeyCode {
+                line: ln,
+                key_code: code,
+            });
+        }
+    }
+    for mask in [raw.modifiers, raw.combo_modifiers] {
+        if mask & !ModifierSet::ALL.to_mask() != 0 {
+            if strict {
+                return Err(format!("Unexpected modifier bits {:#x}", mask));
+            }
+            warnings.push(ParseWarning::UnexpectedModifierBits {
+                line: ln,
+                modifiers: mask,
+            });
+        }
+    }
+
+    let callback = Callback {
+        name: String::from(raw.callback),
+        key_code: raw.key_code,
+        readable_key_code: Key::from_scancode(raw.key_code),
+        modifiers: ModifierSet::from_mask(raw.modifiers),
+        combo_key_code: raw.combo_key_code,
+        readable_combo_key_code: Key::from_scancode(raw.combo_key_code),
+        combo_modifiers: ModifierSet::from_mask(raw.combo_modifiers),
+        visibility: raw
+            .visibility
+            .map_or(Visibility::Visible, Visibility::from_column),
+        description: raw.description.map(String::from),
+        section: None,
+        line: Some(ln),
+        raw_line: None,
+    };
+    report(diagnostics, Level::Trace, Some(ln), || {
+        format!("Parsed callback: {:?}", callback)
+    });
+    Ok(Some(Line::Key(callback)))
+}
+
+/// A keyboard line as written in the key file, borrowing from its text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawBinding<'a> {
+    /// The 1-based line number.
+    pub line: usize,
+    pub callback: &'a str,
+    pub key_code: u16,
+    pub modifiers: u16,
+    pub combo_key_code: u16,
+    pub combo_modifiers: u16,
+    /// The visibility column, `None` if the line ends before it.
+    pub visibility: Option<&'a str>,
+    pub description: Option<&'a str>,
+}
+
+/// The columns of a keyboard line, whose third column is 0.
+fn parse_raw_binding<'a>(
+    ln: usize,
+    stuff: &Columns<'a>,
+    line: &'a str,
+) -> Result<RawBinding<'a>, String> {
+    Ok(RawBinding {
+        line: ln,
+        callback: stuff[0],
+        key_code: convert_number(stuff[3])?,
+        modifiers: convert_number(stuff[4])?,
+        combo_key_code: convert_number(stuff[5])?,
+        combo_modifiers: convert_number(stuff[6])?,
+        visibility: stuff.get(7),
+        description: description(line),
+    })
+}
+
+/// The keyboard lines of a key file's text without copying any of it.
+/// Joystick lines, `SimDoNothing` entries and malformed lines are skipped.
+pub fn raw_bindings(content: &str) -> impl Iterator<Item = RawBinding<'_>> {
+    let content = content.strip_prefix('\u{FEFF}').unwrap_or(content);
+    content
+        .lines()
+        .enumerate()
+        // the first line is the file's banner
+        .skip(1)
+        .filter(|(_, line)| !line.starts_with('#') && !line.starts_with("SimDoNothing"))
+        .filter_map(|(index, line)| {
+            let stuff = Columns::new(line);
+            if stuff.len < 7 || stuff[2].parse::<i64>() != Ok(0) {
+                return None;
+            }
+            parse_raw_binding(index + 1, &stuff, line).ok()
+        })
+}
+
+/// Parses a DX line such as `SimPickle 2 -1 -2 0 0x0 -1`, where the fourth
+/// column is `-2` for buttons and `-3` for POV hats.
+fn parse_joystick_line(ln: usize, stuff: &Columns, line: &str) -> Result<Option<Line>, String> {
+    let number = convert_number(stuff[1])?;
+    let input = match stuff[3] {
+        "-2" => JoystickInput::Button(number % SHIFT_OFFSET),
+        "-3" => {
+            let direction = stuff[4]
+                .parse()
+                .ok()
+                .filter(|direction| *direction < 8)
+                .ok_or_else(|| format!("Expected a POV direction but was '{}'", stuff[4]))?;
+            JoystickInput::Pov {
+                hat: number,
+                direction,
+            }
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(Line::Joystick(JoystickBinding {
+        callback: String::from(stuff[0]),
+        input,
+        shifted: matches!(input, JoystickInput::Button(_)) && number >= SHIFT_OFFSET,
+        description: parse_description(line),
+        section: None,
+        line: Some(ln),
+    })))
+}
+
+/// The name of the section a header line starts, such as `1.01 UI FUNCTIONS`
+/// for `SimDoNothing -1 0 0XFFFFFFFF 0 0 0 -1 "======== 1.01     UI FUNCTIONS ========"`.
+fn parse_section(line: &str) -> Option<String> {
+    if line.split_whitespace().nth(7) != Some("-1") {
+        return None;
+    }
+    let description = parse_description(line)?;
+    let words: Vec<_> = description
+        .trim_matches(|c: char| c == '=' || c.is_whitespace())
+        .split_whitespace()
+        .collect();
+    Some(words.join(" "))
+}
+
+/// The quoted text at the end of a line, if any.
+fn parse_description(line: &str) -> Option<String> {
+    description(line).map(String::from)
+}
+
+fn description(line: &str) -> Option<&str> {
+    let start = line.find('"')?;
+    let description = line[start + 1..].trim_end();
+    Some(description.strip_suffix('"').unwrap_or(description))
+}
+
+/// Controls how forgiving `parse` is about problems in a key file.
+///
+/// The default is lenient: malformed lines are skipped and reported as
+/// warnings.
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Fail on the first malformed line instead of skipping it. Modifier
+    /// masks with unexpected bits count as malformed rather than producing
+    /// a warning.
+    pub strict: bool,
+    /// Leave out callbacks bound to key codes that do not map to a `Key`.
+    pub skip_unknown_keycodes: bool,
+    /// Keep `SimDoNothing` entries instead of dropping them.
+    pub keep_do_nothing: bool,
+    /// Give up once more than this many lines were malformed.
+    pub max_errors: Option<usize>,
+    /// Keep the text of the line every callback was parsed from.
+    pub keep_raw_lines: bool,
+    /// Look up callbacks ignoring case and surrounding whitespace, see
+    /// `FalconKeyfile::set_ignore_case`.
+    pub ignore_case: bool,
+    /// Fail on a callback defined twice instead of letting the later
+    /// definition win.
+    pub deny_duplicates: bool,
+    /// The flavor of the format, BMS unless set.
+    pub dialect: Dialect,
+}
+
+/// The flavor of the key file format to read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Dialect {
+    /// Falcon BMS key files.
+    #[default]
+    Bms,
+    /// Key files of Falcon 4.0, Allied Force and FreeFalcon, whose keyboard
+    /// lines lack the key or DX column after the sound:
+    /// `Callback SoundID KeyCode Modifiers ComboKey ComboModifiers
+    /// [Visibility] ["Description"]`. They have no joystick lines. Parsed
+    /// files are written in BMS format by `FalconKeyfile::canonicalize`,
+    /// callbacks BMS renamed are updated by `FalconKeyfile::migrate`.
+    Legacy,
+}
+
+impl ParseOptions {
+    pub fn strict() -> ParseOptions {
+        ParseOptions {
+            strict: true,
+            ..ParseOptions::default()
+        }
+    }
+
+    pub fn lenient() -> ParseOptions {
+        ParseOptions::default()
+    }
+}
+
+/// The outcome of a successful parse: the keyfile and every recoverable
+/// problem that was encountered on the way.
+#[derive(Debug, Clone)]
+pub struct ParseReport {
+    pub keyfile: FalconKeyfile,
+    pub warnings: Vec<ParseWarning>,
+}
+
+/// The outcome of `parse_partial`: everything that could be read and the
+/// lines that could not.
+#[derive(Debug, Clone)]
+pub struct PartialParse {
+    pub keyfile: FalconKeyfile,
+    /// The skipped lines in file order.
+    pub failures: Vec<LineError>,
+}
+
+/// A line that could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineError {
+    /// The 1-based line number.
+    pub line: usize,
+    pub text: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for LineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}: {}", self.line, self.message, self.text)
+    }
+}
+
+/// A problem in a key file that does not prevent parsing it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseWarning {
+    /// A key code that does not map to any known `Key`.
+    UnknownKeyCode { line: usize, key_code: u16 },
+    /// A modifier mask with bits set other than shift, control and alt.
+    UnexpectedModifierBits { line: usize, modifiers: u16 },
+    /// A callback that was already defined on `previous_line`, the later
+    /// definition wins.
+    DuplicateCallback {
+        line: usize,
+        previous_line: usize,
+        name: String,
+    },
+    /// A line that could not be parsed and was skipped.
+    MalformedLine { line: usize, message: String },
+}
+
+impl ParseWarning {
+    /// The line of the key file the warning refers to.
+    pub fn line(&self) -> usize {
+        match self {
+            ParseWarning::UnknownKeyCode { line, .. } => *line,
+            ParseWarning::UnexpectedModifierBits { line, .. } => *line,
+            ParseWarning::DuplicateCallback { line, .. } => *line,
+            ParseWarning::MalformedLine { line, .. } => *line,
+        }
+    }
+}
+
+impl std::fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseWarning::UnknownKeyCode { line, key_code } => {
+                write!(f, "Unmatched keycode {:#x} on line {}", key_code, line)
+            }
+            ParseWarning::UnexpectedModifierBits { line, modifiers } => {
+                write!(
+                    f,
+                    "Unexpected modifier bits {:#x} on line {}",
+                    modifiers, line
+                )
+            }
+            ParseWarning::DuplicateCallback {
+                line,
+                previous_line,
+                name,
+            } => {
+                write!(
+                    f,
+                    "Callback {} redefined on line {}, first defined on line {}",
+                    name, line, previous_line
+                )
+            }
+            ParseWarning::MalformedLine { line, message } => {
+                write!(f, "Skipped line {}: {}", line, message)
+            }
+        }
+    }
+}
+
+fn convert_number(number: &str) -> Result<u16, String> {
+    let hex = number
+        .strip_prefix("0x")
+        .or_else(|| number.strip_prefix("0X"));
+    if let Some(without_prefix) = hex {
+        return u32::from_str_radix(without_prefix, 16)
+            .map(|n| n as u16)
+            .map_err(|_| {
+                format!(
+                    "Expected hex key code to be an u32 but was '{}'",
+                    number.to_lowercase()
+                )
+            });
+    }
+    number
+        .parse()
+        .map_err(|_| format!("Expected key code number to be u16 but was '{}'", number))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn survive_fuzz_corpus() {
+        for entry in std::fs::read_dir("fuzz/corpus/parse").unwrap() {
+            let content = std::fs::read(entry.unwrap().path()).unwrap();
+            for options in [ParseOptions::lenient(), ParseOptions::strict()] {
+                let _ = parse_reader(String::from("fuzz.key"), content.as_slice(), &options);
+            }
+        }
+    }
+
+    #[test]
+    fn reject_unexpected_modifier_bits_when_strict() {
+        let content = "# modifiers\nAFBrakesToggle -1 0 0x30 9 0 0 1 \"Brakes\"\n";
+        let report = parse_str(String::from("m.key"), content, &ParseOptions::lenient()).unwrap();
+        assert_eq!(
+            report.warnings,
+            vec![ParseWarning::UnexpectedModifierBits {
+                line: 2,
+                modifiers: 9
+            }]
+        );
+        let callback = report.keyfile.callback("AFBrakesToggle").unwrap();
+        assert_eq!(callback.modifiers, ModifierSet::from_mask(1));
+
+        let result = parse_str(String::from("m.key"), content, &ParseOptions::strict());
+        assert!(matches!(
+            result,
+            Err(KeyFileError::ParseError { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn skip_and_record_broken_lines() {
+        let content = std::fs::read_to_string("test-data/malformed.key").unwrap();
+        let partial = parse_partial(String::from("malformed.key"), &content).unwrap();
+        assert_eq!(partial.keyfile.len(), 3);
+        let lines: Vec<_> = partial.failures.iter().map(|f| f.line).collect();
+        assert_eq!(lines, vec![7, 8]);
+        assert_eq!(partial.failures[1].text, "AFElevatorTrimUp -1 0 0xC8");
+        assert!(
+            partial.failures[1]
+                .to_string()
+                .starts_with("line 8: Expected at least 7 columns")
+        );
+        assert!(matches!(
+            parse_partial(String::from("empty.key"), ""),
+            Err(KeyFileError::Empty)
+        ));
+    }
+
+    #[test]
+    fn read_legacy_lines() {
+        let content = "# Falcon 4.0
+AFBrakesToggle -1 0x30 0 0 0
+SimPilotToggle -1 0x19 0 0x2E 4 1 \"Pilot\"
+SimPickle
+";
+        let options = ParseOptions {
+            dialect: Dialect::Legacy,
+            ..ParseOptions::default()
+        };
+        let report = parse_str(String::from("legacy.key"), content, &options).unwrap();
+        assert_eq!(report.warnings.len(), 1);
+        assert_eq!(report.warnings[0].line(), 4);
+
+        let keyfile = report.keyfile;
+        let brakes = keyfile.callback("AFBrakesToggle").unwrap();
+        assert_eq!(brakes.readable_key_code, Key::B);
+        assert_eq!(brakes.visibility, Visibility::Visible);
+        let pilot = keyfile.callback("SimPilotToggle").unwrap();
+        assert_eq!(pilot.combo_chord().unwrap().to_string(), "LAlt+C");
+        assert_eq!(pilot.description.as_deref(), Some("Pilot"));
+
+        let bms = parse_str(
+            String::from("bms.key"),
+            &keyfile.canonicalize(),
+            &ParseOptions::strict(),
+        )
+        .unwrap()
+        .keyfile;
+        assert!(bms.diff(&keyfile).is_empty());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn parse_from_async_reader() {
+        let content = std::fs::read("test-data/basic.key").unwrap();
+        let report = parse_async(
+            String::from("basic.key"),
+            content.as_slice(),
+            &ParseOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let callback = report.keyfile.callback("AFBrakesToggle").unwrap();
+        assert_eq!(callback.readable_key_code, crate::Key::B);
+        assert!(!report.warnings.is_empty());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn parse_utf16_from_async_reader() {
+        let content = std::fs::read_to_string("test-data/basic.key").unwrap();
+        let expected = parse_str(
+            String::from("basic.key"),
+            &content,
+            &ParseOptions::default(),
+        )
+        .unwrap()
+        .keyfile;
+        let mut le = vec![0xFF, 0xFE];
+        le.extend(content.encode_utf16().flat_map(u16::to_le_bytes));
+        let be: Vec<u8> = content.encode_utf16().flat_map(u16::to_be_bytes).collect();
+
+        for bytes in [le, be] {
+            // a small buffer splits lines across reads
+            let reader = tokio::io::BufReader::with_capacity(7, bytes.as_slice());
+            let report = parse_async(String::from("basic.key"), reader, &ParseOptions::default())
+                .await
+                .unwrap();
+            assert_eq!(report.keyfile.len(), expected.len());
+            assert!(report.keyfile.diff(&expected).is_empty());
+        }
+    }
+}
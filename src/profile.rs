@@ -0,0 +1,168 @@
+use crate::{Callback, FalconKeyfile};
+use std::collections::BTreeMap;
+
+/// An ordered stack of key files, such as a squadron base, an aircraft
+/// specific file and personal tweaks. Every layer overrides the ones before
+/// it the way `FalconKeyfile::apply_overlay` does, so a layer only needs the
+/// bindings it changes.
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    layers: Vec<FalconKeyfile>,
+}
+
+/// The binding a `Profile` ends up with for one callback.
+#[derive(Debug, Clone, Copy)]
+pub struct EffectiveBinding<'a> {
+    pub callback: &'a Callback,
+    /// The index of the layer the binding comes from, 0 for the base.
+    pub layer: usize,
+    /// The name of that layer.
+    pub layer_name: &'a str,
+}
+
+impl Profile {
+    /// A profile with `base` as its only layer.
+    pub fn new(base: FalconKeyfile) -> Profile {
+        Profile { layers: vec![base] }
+    }
+
+    /// Adds a layer on top of the others.
+    pub fn push(&mut self, layer: FalconKeyfile) {
+        self.layers.push(layer);
+    }
+
+    pub fn with_layer(mut self, layer: FalconKeyfile) -> Profile {
+        self.push(layer);
+        self
+    }
+
+    /// The layers from the base to the top.
+    pub fn layers(&self) -> &[FalconKeyfile] {
+        &self.layers
+    }
+
+    /// The binding of `name` in the topmost layer that has the callback.
+    pub fn resolve(&self, name: &str) -> Option<EffectiveBinding<'_>> {
+        self.layers
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(layer, keyfile)| {
+                Some(EffectiveBinding {
+                    callback: keyfile.callbacks.get(name)?,
+                    layer,
+                    layer_name: &keyfile.name,
+                })
+            })
+    }
+
+    /// The effective binding of every callback of any layer, sorted by name.
+    pub fn effective_bindings(&self) -> Vec<EffectiveBinding<'_>> {
+        let mut bindings = BTreeMap::new();
+        for (layer, keyfile) in self.layers.iter().enumerate() {
+            for callback in keyfile.callbacks.values() {
+                bindings.insert(
+                    callback.name.as_str(),
+                    EffectiveBinding {
+                        callback,
+                        layer,
+                        layer_name: &keyfile.name,
+                    },
+                );
+            }
+        }
+        bindings.into_values().collect()
+    }
+
+    /// The layers flattened into a single key file named after the base.
+    /// Callbacks only an upper layer has are added with their section.
+    pub fn to_keyfile(&self) -> FalconKeyfile {
+        let mut layers = self.layers.iter();
+        let mut keyfile = match layers.next() {
+            Some(base) => base.clone(),
+            None => return FalconKeyfile::new(String::new(), Default::default()),
+        };
+        for layer in layers {
+            for callback in layer.callbacks.values() {
+                if !keyfile.callbacks.contains_key(&callback.name) {
+                    keyfile
+                        .callbacks
+                        .insert(callback.name.clone(), callback.clone());
+                }
+            }
+            keyfile.apply_overlay(layer);
+        }
+        keyfile
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParseOptions, parse_str};
+
+    fn keyfile(name: &str, content: &str) -> FalconKeyfile {
+        parse_str(String::from(name), content, &ParseOptions::default())
+            .unwrap()
+            .keyfile
+    }
+
+    #[test]
+    fn resolve_bindings_through_layers() {
+        let base = keyfile(
+            "squadron.key",
+            "# squadron
+AFBrakesToggle -1 0 0x30 0 0 0 1 \"Brakes\"
+SimHookToggle -1 0 0x23 0 0 0 1 \"Hook\"
+SimPilotToggle -1 0 0x19 0 0x2E 4 1 \"Autopilot\"
+",
+        );
+        let aircraft = keyfile(
+            "f16.key",
+            "# aircraft
+AFBrakesToggle -1 0 0x30 2 0 0 1
+SimHookToggle -1 0 0XFFFFFFFF 0 0 0 1
+",
+        );
+        let personal = keyfile(
+            "mine.key",
+            "# personal
+AFBrakesToggle -1 0 0x30 4 0 0 1
+SimPickle -1 0 0x39 0 0 0 1 \"Pickle\"
+SimPickle 2 -1 -2 0 0x0 -1
+",
+        );
+        let profile = Profile::new(base).with_layer(aircraft).with_layer(personal);
+
+        let brakes = profile.resolve("AFBrakesToggle").unwrap();
+        assert_eq!((brakes.layer, brakes.layer_name), (2, "mine.key"));
+        assert_eq!(brakes.callback.chord().unwrap().to_string(), "LAlt+B");
+        assert_eq!(profile.resolve("SimHookToggle").unwrap().layer, 1);
+        assert_eq!(profile.resolve("SimPilotToggle").unwrap().layer, 0);
+        assert!(profile.resolve("SimGearToggle").is_none());
+
+        let layers: Vec<_> = profile
+            .effective_bindings()
+            .iter()
+            .map(|b| (b.callback.name.as_str(), b.layer))
+            .collect();
+        assert_eq!(
+            layers,
+            vec![
+                ("AFBrakesToggle", 2),
+                ("SimHookToggle", 1),
+                ("SimPickle", 2),
+                ("SimPilotToggle", 0)
+            ]
+        );
+
+        let flat = profile.to_keyfile();
+        assert_eq!(flat.name(), "squadron.key");
+        assert_eq!(flat.len(), 4);
+        let brakes = flat.callback("AFBrakesToggle").unwrap();
+        assert_eq!(brakes.description.as_deref(), Some("Brakes"));
+        assert_eq!(brakes.chord().unwrap().to_string(), "LAlt+B");
+        assert!(!flat.callback("SimHookToggle").unwrap().is_bound());
+        assert_eq!(flat.joystick_bindings()[0].callback, "SimPickle");
+    }
+}
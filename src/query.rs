@@ -0,0 +1,156 @@
+use crate::{Callback, FalconKeyfile, Key, Modifier, ModifierSet, Visibility};
+
+/// Filters over the callbacks of a key file, see `FalconKeyfile::query`.
+/// Every filter narrows the result further.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct Query<'a> {
+    keyfile: &'a FalconKeyfile,
+    key: Option<Key>,
+    modifiers: ModifierSet,
+    exact_modifiers: Option<ModifierSet>,
+    section: Option<&'a str>,
+    visibility: Option<Visibility>,
+    bound: Option<bool>,
+    description: Option<String>,
+}
+
+impl<'a> Query<'a> {
+    /// Callbacks whose chord uses `key`.
+    pub fn key(mut self, key: Key) -> Query<'a> {
+        self.key = Some(key);
+        self
+    }
+
+    /// Callbacks whose chord includes `modifier`, among others.
+    pub fn modifier(mut self, modifier: Modifier) -> Query<'a> {
+        self.modifiers.insert(modifier);
+        self
+    }
+
+    /// Callbacks whose chord has exactly these modifiers.
+    pub fn modifiers(mut self, modifiers: ModifierSet) -> Query<'a> {
+        self.exact_modifiers = Some(modifiers);
+        self
+    }
+
+    pub fn section(mut self, section: &'a str) -> Query<'a> {
+        self.section = Some(section);
+        self
+    }
+
+    pub fn visibility(mut self, visibility: Visibility) -> Query<'a> {
+        self.visibility = Some(visibility);
+        self
+    }
+
+    /// Only bound or only unbound callbacks.
+    pub fn bound(mut self, bound: bool) -> Query<'a> {
+        self.bound = Some(bound);
+        self
+    }
+
+    /// Callbacks whose description contains `text`, ignoring case.
+    pub fn description(mut self, text: &str) -> Query<'a> {
+        self.description = Some(text.to_lowercase());
+        self
+    }
+
+    fn matches(&self, callback: &Callback) -> bool {
+        self.key
+            .as_ref()
+            .is_none_or(|key| callback.is_bound() && callback.readable_key_code == *key)
+            && (self.modifiers.is_empty()
+                || (callback.is_bound() && self.modifiers.is_subset(callback.modifiers)))
+            && self
+                .exact_modifiers
+                .is_none_or(|m| callback.is_bound() && callback.modifiers == m)
+            && self
+                .section
+                .is_none_or(|s| callback.section.as_deref() == Some(s))
+            && self.visibility.is_none_or(|v| callback.visibility == v)
+            && self.bound.is_none_or(|b| callback.is_bound() == b)
+            && self.description.as_ref().is_none_or(|text| {
+                callback
+                    .description
+                    .as_ref()
+                    .is_some_and(|d| d.to_lowercase().contains(text))
+            })
+    }
+
+    /// The matching callbacks, sorted by name.
+    pub fn collect(self) -> Vec<&'a Callback> {
+        let mut callbacks: Vec<_> = self
+            .keyfile
+            .callbacks
+            .values()
+            .filter(|c| self.matches(c))
+            .collect();
+        callbacks.sort_by(|a, b| a.name.cmp(&b.name));
+        callbacks
+    }
+
+    pub fn count(self) -> usize {
+        self.keyfile
+            .callbacks
+            .values()
+            .filter(|c| self.matches(c))
+            .count()
+    }
+}
+
+impl FalconKeyfile {
+    /// A query over all callbacks, e.g.
+    ///
+    /// ```ignore
+    /// keyfile.query().section("Radio").modifier(Modifier::LALT).bound(true).collect()
+    /// ```
+    pub fn query(&self) -> Query<'_> {
+        Query {
+            keyfile: self,
+            key: None,
+            modifiers: ModifierSet::EMPTY,
+            exact_modifiers: None,
+            section: None,
+            visibility: None,
+            bound: None,
+            description: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combine_filters() {
+        let keyfile = FalconKeyfile::from_path("test-data/T16000M-FCS-Full.key").unwrap();
+        assert_eq!(keyfile.query().count(), keyfile.len());
+
+        let section = keyfile.callback("SimHookToggle").unwrap().section.unwrap();
+        let found = keyfile
+            .query()
+            .section(&section)
+            .modifier(Modifier::LCONTROL)
+            .bound(true)
+            .collect();
+        assert!(found.iter().any(|c| c.name == "SimHookToggle"));
+        assert!(found.iter().all(|c| c.section.as_deref() == Some(&*section)
+            && c.is_bound()
+            && c.modifiers.contains(Modifier::LCONTROL)));
+
+        let hook = keyfile
+            .query()
+            .key(Key::from_scancode(0x25))
+            .modifiers(ModifierSet::from_mask(2))
+            .description("hook switch")
+            .collect();
+        assert_eq!(hook.len(), 1);
+        assert_eq!(hook[0].name, "SimHookToggle");
+        assert_eq!(
+            keyfile.query().bound(false).count(),
+            keyfile.unbound_callbacks().len()
+        );
+    }
+}
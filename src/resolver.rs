@@ -0,0 +1,165 @@
+use crate::events::LALT_SCANCODE;
+use crate::{Callback, FalconKeyfile, Key, KeyCombo, KeyEvent, Modifier, ModifierSet};
+
+/// What a key event does in BMS, see `Resolver::feed`.
+#[derive(Debug, Clone)]
+pub enum Resolution<'a> {
+    /// The callbacks bound to the chord, more than one if they conflict.
+    Fired(Vec<&'a Callback>),
+    /// The chord starts two-stage bindings, the next chord decides.
+    Pending(KeyCombo),
+    /// Nothing is bound to the chord.
+    Unbound(KeyCombo),
+    /// Modifier changes and released keys.
+    Ignored,
+}
+
+/// Follows key events the way BMS does and tells which callbacks fire.
+#[derive(Debug, Clone)]
+pub struct Resolver<'a> {
+    keyfile: &'a FalconKeyfile,
+    modifiers: ModifierSet,
+    pending: Option<KeyCombo>,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(keyfile: &'a FalconKeyfile) -> Resolver<'a> {
+        Resolver {
+            keyfile,
+            modifiers: ModifierSet::EMPTY,
+            pending: None,
+        }
+    }
+
+    /// The modifiers currently held.
+    pub fn modifiers(&self) -> ModifierSet {
+        self.modifiers
+    }
+
+    /// Overrides the held modifiers, for syncing with the real keyboard state.
+    pub fn set_modifiers(&mut self, modifiers: ModifierSet) {
+        self.modifiers = modifiers;
+    }
+
+    /// The first stage of a two-stage binding waiting for its second chord.
+    pub fn pending(&self) -> Option<&KeyCombo> {
+        self.pending.as_ref()
+    }
+
+    /// Forgets the held modifiers and a pending first stage.
+    pub fn reset(&mut self) {
+        self.modifiers = ModifierSet::EMPTY;
+        self.pending = None;
+    }
+
+    /// Feeds the next key event. Pressing a key that is not a modifier either
+    /// completes a pending two-stage binding, starts one, or fires the
+    /// callbacks bound to the chord directly.
+    pub fn feed(&mut self, event: &KeyEvent) -> Resolution<'a> {
+        if let Some(modifier) = modifier(&event.key) {
+            if event.pressed {
+                self.modifiers.insert(modifier);
+            } else {
+                self.modifiers.remove(modifier);
+            }
+            return Resolution::Ignored;
+        }
+        if !event.pressed {
+            return Resolution::Ignored;
+        }
+
+        let combo = KeyCombo::new(event.key.clone(), self.modifiers);
+        let keyfile = self.keyfile;
+        let bound = keyfile.callbacks_for_combo(&combo);
+
+        if let Some(first) = self.pending.take() {
+            let fired: Vec<_> = bound
+                .into_iter()
+                .filter(|c| c.combo_chord().as_ref() == Some(&first))
+                .collect();
+            return if fired.is_empty() {
+                Resolution::Unbound(combo)
+            } else {
+                Resolution::Fired(fired)
+            };
+        }
+
+        let starts_two_stage = keyfile
+            .iter()
+            .any(|c| c.combo_chord().as_ref() == Some(&combo));
+        if starts_two_stage {
+            self.pending = Some(combo.clone());
+            return Resolution::Pending(combo);
+        }
+
+        let fired: Vec<_> = bound
+            .into_iter()
+            .filter(|c| c.combo_chord().is_none())
+            .collect();
+        if fired.is_empty() {
+            Resolution::Unbound(combo)
+        } else {
+            Resolution::Fired(fired)
+        }
+    }
+}
+
+fn modifier(key: &Key) -> Option<Modifier> {
+    match key {
+        Key::LShift => Some(Modifier::LSHIFT),
+        Key::LControl => Some(Modifier::LCONTROL),
+        Key::Other(LALT_SCANCODE) => Some(Modifier::LALT),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeyfileBuilder;
+
+    #[test]
+    fn resolve_key_events() {
+        let keyfile = KeyfileBuilder::new("inline")
+            .bind("AFGearToggle", "G".parse().unwrap(), "Gear")
+            .callback(
+                Callback::builder("SimPilotToggle")
+                    .chord_str("P")
+                    .combo_str("LAlt+C")
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        let mut resolver = Resolver::new(&keyfile);
+        let names = |resolution: Resolution| match resolution {
+            Resolution::Fired(callbacks) => callbacks.iter().map(|c| c.name.clone()).collect(),
+            _ => vec![],
+        };
+
+        assert_eq!(
+            names(resolver.feed(&KeyEvent::down(Key::G))),
+            vec!["AFGearToggle"]
+        );
+        assert!(matches!(
+            resolver.feed(&KeyEvent::up(Key::G)),
+            Resolution::Ignored
+        ));
+
+        resolver.feed(&KeyEvent::down(Key::Other(0x38)));
+        assert!(matches!(
+            resolver.feed(&KeyEvent::down(Key::C)),
+            Resolution::Pending(_)
+        ));
+        assert!(resolver.pending().is_some());
+        resolver.feed(&KeyEvent::up(Key::Other(0x38)));
+        assert_eq!(
+            names(resolver.feed(&KeyEvent::down(Key::P))),
+            vec!["SimPilotToggle"]
+        );
+        assert!(matches!(
+            resolver.feed(&KeyEvent::down(Key::P)),
+            Resolution::Unbound(_)
+        ));
+    }
+}
@@ -0,0 +1,227 @@
+//! Bulk transformations written as [Rhai](https://rhai.rs) scripts.
+//!
+//! The script runs once for every callback with these variables in scope:
+//!
+//! - `name`, `section` and `description`, read only, `""` when absent,
+//! - `chord`, such as `"LCtrl+B"`, `""` when unbound,
+//! - `combo`, the chord pressed before it, `""` if there is none,
+//! - `visibility`, one of `"visible"`, `"locked"`, `"header"` or `"hidden"`.
+//!
+//! Assigning to `chord`, `combo` or `visibility` changes the callback:
+//!
+//! ```text
+//! if name.starts_with("SimICP") && chord.starts_with("LAlt+") {
+//!     chord = "LCtrl+" + chord.sub_string(5);
+//! }
+//! ```
+
+use crate::{Binding, Callback, FalconKeyfile, KeyCombo, ParseComboError, Visibility};
+use rhai::{Engine, Scope};
+use std::fmt;
+
+/// Guards against scripts that never finish, counted per callback.
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// Why a script could not be applied, the key file is left unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptError {
+    Compile(String),
+    Runtime {
+        callback: String,
+        message: String,
+    },
+    /// The script assigned a chord or combo that does not parse.
+    InvalidChord {
+        callback: String,
+        chord: String,
+        error: ParseComboError,
+    },
+    InvalidVisibility {
+        callback: String,
+        visibility: String,
+    },
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptError::Compile(message) => write!(f, "script does not compile: {}", message),
+            ScriptError::Runtime { callback, message } => {
+                write!(f, "script failed on {}: {}", callback, message)
+            }
+            ScriptError::InvalidChord {
+                callback,
+                chord,
+                error,
+            } => write!(f, "invalid chord '{}' for {}: {}", chord, callback, error),
+            ScriptError::InvalidVisibility {
+                callback,
+                visibility,
+            } => write!(f, "invalid visibility '{}' for {}", visibility, callback),
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+fn visibility_name(visibility: Visibility) -> String {
+    match visibility {
+        Visibility::Visible => String::from("visible"),
+        Visibility::Locked => String::from("locked"),
+        Visibility::Header => String::from("header"),
+        Visibility::Hidden => String::from("hidden"),
+        Visibility::Other(value) => value.to_string(),
+    }
+}
+
+fn parse_visibility(name: &str) -> Option<Visibility> {
+    match name {
+        "visible" => Some(Visibility::Visible),
+        "locked" => Some(Visibility::Locked),
+        "header" => Some(Visibility::Header),
+        "hidden" => Some(Visibility::Hidden),
+        other => other.parse().ok().map(Visibility::Other),
+    }
+}
+
+fn chord_text(combo: Option<KeyCombo>) -> String {
+    combo.map(|c| c.to_string()).unwrap_or_default()
+}
+
+fn parse_chord(callback: &str, chord: &str) -> Result<Option<KeyCombo>, ScriptError> {
+    if chord.trim().is_empty() {
+        return Ok(None);
+    }
+    chord
+        .parse()
+        .map(Some)
+        .map_err(|error| ScriptError::InvalidChord {
+            callback: String::from(callback),
+            chord: String::from(chord),
+            error,
+        })
+}
+
+impl FalconKeyfile {
+    /// Runs `script` on every callback, see the module documentation for
+    /// what it can read and change. Returns the names of the changed
+    /// callbacks, sorted. On any error nothing is changed.
+    pub fn transform_with_script(&mut self, script: &str) -> Result<Vec<String>, ScriptError> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let ast = engine
+            .compile(script)
+            .map_err(|e| ScriptError::Compile(e.to_string()))?;
+
+        let mut names: Vec<_> = self.callbacks.keys().cloned().collect();
+        names.sort();
+        let mut changed: Vec<Callback> = vec![];
+        for name in names {
+            let callback = &self.callbacks[&name];
+            let chord = chord_text(callback.chord());
+            let combo = chord_text(callback.combo_chord());
+            let visibility = visibility_name(callback.visibility);
+
+            let mut scope = Scope::new();
+            scope.push_constant("name", name.clone());
+            scope.push_constant("section", callback.section.clone().unwrap_or_default());
+            scope.push_constant(
+                "description",
+                callback.description.clone().unwrap_or_default(),
+            );
+            scope.push("chord", chord.clone());
+            scope.push("combo", combo.clone());
+            scope.push("visibility", visibility.clone());
+            engine
+                .run_ast_with_scope(&mut scope, &ast)
+                .map_err(|e| ScriptError::Runtime {
+                    callback: name.clone(),
+                    message: e.to_string(),
+                })?;
+
+            let read = |variable: &str| {
+                scope
+                    .get_value::<String>(variable)
+                    .ok_or_else(|| ScriptError::Runtime {
+                        callback: name.clone(),
+                        message: format!("{} has to be a string", variable),
+                    })
+            };
+            let (new_chord, new_combo, new_visibility) =
+                (read("chord")?, read("combo")?, read("visibility")?);
+            if new_chord == chord && new_combo == combo && new_visibility == visibility {
+                continue;
+            }
+
+            let mut callback = callback.clone();
+            callback.visibility = parse_visibility(&new_visibility).ok_or_else(|| {
+                ScriptError::InvalidVisibility {
+                    callback: name.clone(),
+                    visibility: new_visibility.clone(),
+                }
+            })?;
+            let primary = parse_chord(&name, &new_chord)?;
+            let secondary = parse_chord(&name, &new_combo)?;
+            callback.replace_binding(primary.map(|chord| match secondary {
+                Some(first) => Binding::Sequence(first, chord),
+                None => Binding::Single(chord),
+            }));
+            changed.push(callback);
+        }
+
+        let names = changed.iter().map(|c| c.name.clone()).collect();
+        for callback in changed {
+            self.callbacks.insert(callback.name.clone(), callback);
+        }
+        self.reindex();
+        Ok(names)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModifierSet;
+
+    #[test]
+    fn rewrite_chords_with_a_script() {
+        let mut keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let on_alt = keyfile
+            .iter()
+            .filter(|c| c.is_bound() && c.modifiers == ModifierSet::from_mask(4))
+            .count();
+        assert!(on_alt > 0);
+
+        let changed = keyfile
+            .transform_with_script(
+                r#"
+                if chord.starts_with("LAlt+") {
+                    chord = "LCtrl+LAlt+" + chord.sub_string(5);
+                }
+                if name == "SimCursorUpLeft" { visibility = "hidden"; }
+                "#,
+            )
+            .unwrap();
+        assert_eq!(changed.len(), on_alt + 1);
+        assert!(
+            !keyfile
+                .iter()
+                .any(|c| c.is_bound() && c.modifiers == ModifierSet::from_mask(4))
+        );
+        assert_eq!(
+            keyfile.callback("SimCursorUpLeft").unwrap().visibility,
+            Visibility::Hidden
+        );
+
+        let before = keyfile.clone();
+        let error = keyfile
+            .transform_with_script(r#"if chord != "" { chord = "LCtrl+Nope"; }"#)
+            .unwrap_err();
+        assert!(matches!(error, ScriptError::InvalidChord { .. }));
+        assert!(keyfile.diff(&before).is_empty());
+        assert!(matches!(
+            keyfile.transform_with_script("chord = "),
+            Err(ScriptError::Compile(_))
+        ));
+    }
+}
@@ -0,0 +1,177 @@
+use crate::fuzzy::{MATCH, fuzzy_score};
+use crate::{Callback, FalconKeyfile};
+
+impl FalconKeyfile {
+    /// The callbacks whose name matches `pattern`, sorted by name. `*`
+    /// matches any run of characters and `?` a single one, so `SimHUD*`
+    /// finds the HUD callbacks and `Sim*Toggle` all toggles. A pattern
+    /// without wildcards matches the names starting with it.
+    ///
+    /// Case is ignored after `set_ignore_case`.
+    pub fn find(&self, pattern: &str) -> Vec<&Callback> {
+        let pattern: Vec<char> = if pattern.contains(['*', '?']) {
+            pattern.chars().collect()
+        } else {
+            pattern.chars().chain(std::iter::once('*')).collect()
+        };
+        let mut found: Vec<_> = self
+            .callbacks
+            .values()
+            .filter(|c| {
+                let name: Vec<char> = c.name.chars().collect();
+                glob_match(&pattern, &name, self.ignore_case)
+            })
+            .collect();
+        found.sort_by(|a, b| a.name.cmp(&b.name));
+        found
+    }
+
+    /// The callbacks whose description matches `query`, best first, for
+    /// users who know the cockpit function rather than the callback name.
+    /// Case is ignored. Descriptions containing the query come first, then
+    /// ones containing all of its words, then close fuzzy matches.
+    pub fn search_descriptions(&self, query: &str) -> Vec<&Callback> {
+        let query = query.trim().to_lowercase();
+        let words: Vec<&str> = query.split_whitespace().collect();
+        // a fuzzy match needs at least the score of matching every character
+        // without any bonus, long descriptions match most queries otherwise
+        let threshold = MATCH as u32 * query.chars().count() as u32;
+
+        let mut found: Vec<_> = self
+            .callbacks
+            .values()
+            .filter_map(|c| {
+                let description = c.description.as_deref()?.to_lowercase();
+                let score = if description.contains(&query) {
+                    (2, 0)
+                } else if words.iter().all(|w| description.contains(w)) {
+                    (1, 0)
+                } else {
+                    let score = fuzzy_score(&query, &description, true)?;
+                    (0, score)
+                };
+                (score.0 > 0 || score.1 >= threshold).then_some((score, c))
+            })
+            .collect();
+        found.sort_by(|(x, a), (y, b)| y.cmp(x).then_with(|| a.name.cmp(&b.name)));
+        found.into_iter().map(|(_, c)| c).collect()
+    }
+
+    /// The callbacks whose name matches `regex`, grouped by section. Callbacks
+    /// without a section come first, then the sections in file order, each
+    /// sorted by name. Sections without a match are left out.
+    #[cfg(feature = "regex")]
+    pub fn find_regex(&self, regex: &regex::Regex) -> Vec<(Option<&str>, Vec<&Callback>)> {
+        let mut found: Vec<_> = self
+            .callbacks
+            .values()
+            .filter(|c| regex.is_match(&c.name))
+            .collect();
+        found.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut groups: Vec<(Option<&str>, Vec<&Callback>)> = vec![];
+        for callback in found {
+            let section = callback.section.as_deref();
+            match groups.iter_mut().find(|(s, _)| *s == section) {
+                Some((_, callbacks)) => callbacks.push(callback),
+                None => groups.push((section, vec![callback])),
+            }
+        }
+        // sections only known from callbacks, e.g. ones added by a builder, go last
+        groups.sort_by_key(|(section, _)| match section {
+            None => (0, None),
+            Some(s) => match self.sections.iter().position(|known| known == s) {
+                Some(index) => (index + 1, None),
+                None => (usize::MAX, Some(*s)),
+            },
+        });
+        groups
+    }
+}
+
+/// Iterative wildcard matching that backtracks only to the last `*`.
+fn glob_match(pattern: &[char], text: &[char], ignore_case: bool) -> bool {
+    let same = |a: char, b: char| a == b || (ignore_case && a.eq_ignore_ascii_case(&b));
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || same(c, text[t]) => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn find_by_regex_grouped_by_section() {
+        let keyfile = FalconKeyfile::from_path("test-data/T16000M-FCS-Full.key").unwrap();
+        let regex = regex::Regex::new("^Sim(ICP|HUD)").unwrap();
+        let groups = keyfile.find_regex(&regex);
+        assert!(groups.len() > 1);
+        let sections: Vec<_> = keyfile
+            .sections()
+            .iter()
+            .map(|s| Some(s.as_str()))
+            .collect();
+        let positions: Vec<_> = groups
+            .iter()
+            .map(|(section, _)| sections.iter().position(|s| s == section))
+            .collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+        for (section, callbacks) in groups {
+            assert!(callbacks.iter().all(|c| c.section.as_deref() == section));
+            assert!(callbacks.iter().all(|c| regex.is_match(&c.name)));
+        }
+    }
+
+    #[test]
+    fn search_by_description() {
+        let keyfile = FalconKeyfile::from_path("test-data/T16000M-FCS-Full.key").unwrap();
+        let found = keyfile.search_descriptions("master arm");
+        assert!(found.iter().any(|c| c.name == "SimStepMasterArm"));
+        assert!(found.iter().any(|c| c.name == "SimMasterArmUp"));
+
+        let found = keyfile.search_descriptions("Hook Toggle");
+        assert_eq!(found[0].name, "SimHookToggle");
+        assert!(keyfile.search_descriptions("zzzz").is_empty());
+    }
+
+    #[test]
+    fn find_by_prefix_and_glob() {
+        let keyfile = FalconKeyfile::from_path("test-data/T16000M-FCS-Full.key").unwrap();
+        let hud = keyfile.find("SimHUD");
+        assert!(!hud.is_empty());
+        assert!(hud.iter().all(|c| c.name.starts_with("SimHUD")));
+        assert_eq!(hud.len(), keyfile.find("SimHUD*").len());
+
+        let toggles = keyfile.find("Sim*Toggle");
+        assert!(toggles.iter().any(|c| c.name == "SimPilotToggle"));
+        assert!(
+            toggles
+                .iter()
+                .all(|c| c.name.starts_with("Sim") && c.name.ends_with("Toggle"))
+        );
+        assert!(keyfile.find("simhud*").is_empty());
+        assert_eq!(keyfile.find("SimPilotTogg?e").len(), 1);
+    }
+}
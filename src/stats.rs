@@ -0,0 +1,82 @@
+use crate::{FalconKeyfile, ModifierSet};
+
+/// Number of modifier layers, one per combination of `ModifierSet::ALL`.
+const LAYERS: usize = ModifierSet::ALL.to_mask() as usize + 1;
+
+/// Counts summarizing a keyfile, see `FalconKeyfile::stats`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyfileStats {
+    /// Callbacks with a keyboard line, `SimDoNothing` not included.
+    pub callbacks: usize,
+    /// Callbacks bound to a key.
+    pub bound: usize,
+    /// Callbacks without a key.
+    pub unbound: usize,
+    /// Bound callbacks per section in file order, sections without any left out.
+    /// Callbacks outside of any section are counted under `"Other"`.
+    pub by_section: Vec<(String, usize)>,
+    /// Bound callbacks indexed by the modifier mask of their chord.
+    pub by_layer: [usize; LAYERS],
+    /// Joystick button and POV hat lines.
+    pub joystick_bindings: usize,
+    /// Chords that more than one callback is bound to.
+    pub duplicate_chords: usize,
+}
+
+impl KeyfileStats {
+    /// Bound callbacks on the layer of `modifiers`.
+    pub fn layer(&self, modifiers: ModifierSet) -> usize {
+        self.by_layer[modifiers.to_mask() as usize]
+    }
+}
+
+impl FalconKeyfile {
+    /// Counts of the bindings by kind, section and modifier layer.
+    pub fn stats(&self) -> KeyfileStats {
+        let bound: Vec<_> = self.callbacks.values().filter(|c| c.is_bound()).collect();
+
+        let mut by_section: Vec<(String, usize)> =
+            self.sections.iter().map(|s| (s.clone(), 0)).collect();
+        let mut by_layer = [0; LAYERS];
+        for callback in &bound {
+            let section = callback.section.as_deref().unwrap_or("Other");
+            match by_section.iter_mut().find(|(name, _)| name == section) {
+                Some((_, count)) => *count += 1,
+                None => by_section.push((String::from(section), 1)),
+            }
+            by_layer[callback.modifiers.to_mask() as usize] += 1;
+        }
+        by_section.retain(|(_, count)| *count > 0);
+
+        KeyfileStats {
+            callbacks: self.callbacks.len(),
+            bound: bound.len(),
+            unbound: self.callbacks.len() - bound.len(),
+            by_section,
+            by_layer,
+            joystick_bindings: self.joystick.len(),
+            duplicate_chords: self.conflicts().len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FalconKeyfile, Modifier, ModifierSet};
+
+    #[test]
+    fn summarize_keyfile() {
+        let keyfile = FalconKeyfile::from_path("test-data/T16000M-FCS-Full.key").unwrap();
+        let stats = keyfile.stats();
+
+        assert_eq!(stats.callbacks, 1191);
+        assert_eq!(stats.bound, 486);
+        assert_eq!(stats.unbound, 705);
+        assert_eq!(stats.by_section[0], (String::from("2.01 TEST PANEL"), 7));
+        assert_eq!(stats.by_layer, [107, 82, 57, 45, 64, 60, 63, 8]);
+        assert_eq!(stats.layer(ModifierSet::EMPTY), 107);
+        assert_eq!(stats.layer(Modifier::LALT.into()), 64);
+        assert_eq!(stats.joystick_bindings, 57);
+        assert_eq!(stats.duplicate_chords, 1);
+    }
+}
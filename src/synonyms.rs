@@ -0,0 +1,136 @@
+use crate::{Callback, FalconKeyfile};
+use std::collections::HashMap;
+
+/// Everyday phrases and the callback name or description fragments they
+/// stand for, used by `FalconKeyfile::search`.
+const BUILTIN: &[(&str, &[&str])] = &[
+    ("gear", &["AFGear", "LandingGear", "GEAR"]),
+    ("landing gear", &["AFGear", "LandingGear"]),
+    ("countermeasures", &["CMS", "Chaff", "Flare", "EWS"]),
+    ("chaff", &["Chaff", "CMS"]),
+    ("flares", &["Flare", "CMS"]),
+    ("speed brake", &["AFBrakes"]),
+    ("speedbrake", &["AFBrakes"]),
+    ("airbrake", &["AFBrakes"]),
+    ("autopilot", &["SimAP", "AUTOPILOT"]),
+    ("refuel", &["FuelDoor", "AIR REFUEL"]),
+    ("refueling", &["FuelDoor", "AIR REFUEL"]),
+    ("bombs", &["Pickle"]),
+    ("release", &["Pickle"]),
+    ("gun", &["Trigger"]),
+    ("fire", &["Trigger", "Pickle"]),
+    ("radio", &["Radio", "Comm", "UHF", "VHF"]),
+    ("look", &["OTWView", "Padlock"]),
+    ("flaps", &["Flap"]),
+    ("eject", &["Eject"]),
+];
+
+/// A table of synonyms for resolving natural phrasing to callbacks, e.g.
+/// "countermeasures" to the CMS, chaff and flare callbacks. Phrases are
+/// matched as whole words ignoring case.
+#[derive(Debug, Clone, Default)]
+pub struct Synonyms {
+    terms: HashMap<String, Vec<String>>,
+}
+
+impl Synonyms {
+    /// An empty table, `search` then only matches the query itself.
+    pub fn new() -> Synonyms {
+        Synonyms::default()
+    }
+
+    /// The table shipped with the crate.
+    pub fn builtin() -> Synonyms {
+        let mut synonyms = Synonyms::new();
+        for (phrase, terms) in BUILTIN {
+            synonyms.insert(phrase, terms.iter().copied());
+        }
+        synonyms
+    }
+
+    /// Adds `terms` to the ones `phrase` stands for.
+    pub fn insert<'a>(&mut self, phrase: &str, terms: impl IntoIterator<Item = &'a str>) {
+        self.terms
+            .entry(phrase.trim().to_lowercase())
+            .or_default()
+            .extend(terms.into_iter().map(String::from));
+    }
+
+    /// The query followed by the terms of every phrase it contains.
+    pub fn expand(&self, query: &str) -> Vec<String> {
+        let query = query
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        let padded = format!(" {} ", query);
+        let mut phrases: Vec<_> = self
+            .terms
+            .iter()
+            .filter(|(phrase, _)| padded.contains(&format!(" {} ", phrase)))
+            .collect();
+        phrases.sort_by_key(|(phrase, _)| *phrase);
+
+        let mut expanded = vec![query];
+        for term in phrases.into_iter().flat_map(|(_, terms)| terms) {
+            if !expanded.iter().any(|t| t.eq_ignore_ascii_case(term)) {
+                expanded.push(term.clone());
+            }
+        }
+        expanded
+    }
+}
+
+impl FalconKeyfile {
+    /// The callbacks for a natural query such as "drop chaff", in the order
+    /// of `Synonyms::expand`: matches of the query itself first, then those
+    /// of its synonyms. A term matches callbacks whose name contains it,
+    /// ignoring case and spaces, or whose description matches it as in
+    /// `search_descriptions`.
+    pub fn search(&self, query: &str, synonyms: &Synonyms) -> Vec<&Callback> {
+        let mut found: Vec<&Callback> = vec![];
+        for term in synonyms.expand(query) {
+            let fragment: String = term.split_whitespace().collect::<String>().to_lowercase();
+            let mut by_name: Vec<_> = self
+                .callbacks
+                .values()
+                .filter(|c| !fragment.is_empty() && c.name.to_lowercase().contains(&fragment))
+                .collect();
+            by_name.sort_by(|a, b| a.name.cmp(&b.name));
+
+            for callback in by_name.into_iter().chain(self.search_descriptions(&term)) {
+                if !found.iter().any(|c| c.name == callback.name) {
+                    found.push(callback);
+                }
+            }
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_phrases_through_synonyms() {
+        let synonyms = Synonyms::builtin();
+        let expanded = synonyms.expand("Drop  Countermeasures");
+        assert_eq!(expanded[0], "drop countermeasures");
+        assert!(expanded.iter().any(|t| t == "Chaff"));
+        assert_eq!(synonyms.expand("regear"), vec!["regear"]);
+
+        let keyfile = FalconKeyfile::from_path("test-data/T16000M-FCS-Full.key").unwrap();
+        let found = keyfile.search("countermeasures", &synonyms);
+        assert!(found.iter().any(|c| c.name == "SimDropChaff"));
+        assert!(found.iter().any(|c| c.name == "SimDropFlare"));
+        assert!(
+            keyfile
+                .search("countermeasures", &Synonyms::new())
+                .is_empty()
+        );
+
+        let found = keyfile.search("speed brake", &synonyms);
+        assert!(found.iter().any(|c| c.name == "AFBrakesToggle"));
+    }
+}
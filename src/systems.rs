@@ -0,0 +1,201 @@
+use crate::{Callback, FalconKeyfile};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The part of the jet a callback operates, for organizing kneeboards the
+/// way pilots think about the cockpit. Unlike `Category` this follows a
+/// curated table rather than the naming of the callbacks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CockpitSystem {
+    /// The switches on the stick and the throttle.
+    Hotas,
+    /// The integrated control panel and the data entry display.
+    IcpDed,
+    /// The MFDs and their OSBs.
+    Mfds,
+    /// Electronic warfare: CMDS, ECM and RWR.
+    Ews,
+    /// Exterior and interior lighting.
+    Lighting,
+    Views,
+    /// Radios, intercom and the radio menus.
+    Comms,
+    Other,
+}
+
+/// Name prefixes and their system, the longest matching prefix wins. Full
+/// callback names are listed for single switches.
+const SYSTEMS: &[(&str, CockpitSystem)] = &[
+    // stick
+    ("SimTrigger", CockpitSystem::Hotas),
+    ("SimPickle", CockpitSystem::Hotas),
+    ("SimMissileStep", CockpitSystem::Hotas),
+    ("SimPinkySwitch", CockpitSystem::Hotas),
+    ("SimHotasPinkyShift", CockpitSystem::Hotas),
+    ("SimAPOverride", CockpitSystem::Hotas),
+    ("SimTMS", CockpitSystem::Hotas),
+    ("SimDMS", CockpitSystem::Hotas),
+    ("SimCMS", CockpitSystem::Hotas),
+    ("AFElevatorTrim", CockpitSystem::Hotas),
+    ("AFAileronTrim", CockpitSystem::Hotas),
+    // throttle
+    ("AFBrakes", CockpitSystem::Hotas),
+    ("SimCursor", CockpitSystem::Hotas),
+    ("SimCommsSwitch", CockpitSystem::Hotas),
+    ("SimTransmitCom", CockpitSystem::Hotas),
+    ("SimToggleMissileCage", CockpitSystem::Hotas),
+    ("SimDeselectOverride", CockpitSystem::Hotas),
+    ("SimSelectMRMOverride", CockpitSystem::Hotas),
+    ("SimSelectSRMOverride", CockpitSystem::Hotas),
+    ("SimRangeKnob", CockpitSystem::Hotas),
+    ("SimRadarElevation", CockpitSystem::Hotas),
+    ("SimRadarCursorZero", CockpitSystem::Hotas),
+    ("SimThrottleIdleDetent", CockpitSystem::Hotas),
+    ("SimICP", CockpitSystem::IcpDed),
+    ("SimDed", CockpitSystem::IcpDed),
+    ("SimDrift", CockpitSystem::IcpDed),
+    ("SimWarnReset", CockpitSystem::IcpDed),
+    ("SimRetUp", CockpitSystem::IcpDed),
+    ("SimRetDn", CockpitSystem::IcpDed),
+    ("SimSymWheel", CockpitSystem::IcpDed),
+    ("SimBrtWheel", CockpitSystem::IcpDed),
+    ("SimFlirLevel", CockpitSystem::IcpDed),
+    ("SimCBEOSB", CockpitSystem::Mfds),
+    ("SimMFD", CockpitSystem::Mfds),
+    ("SimRadarGain", CockpitSystem::Mfds),
+    ("OTWStepMFD", CockpitSystem::Mfds),
+    ("OTWSwapMFDS", CockpitSystem::Mfds),
+    ("SimEWS", CockpitSystem::Ews),
+    ("SimEws", CockpitSystem::Ews),
+    ("SimECM", CockpitSystem::Ews),
+    ("SimEcm", CockpitSystem::Ews),
+    ("SimRWR", CockpitSystem::Ews),
+    ("SimRwr", CockpitSystem::Ews),
+    ("SimXMit", CockpitSystem::Ews),
+    ("SimXMTASPIS", CockpitSystem::Ews),
+    ("SimDropChaff", CockpitSystem::Ews),
+    ("SimDropFlare", CockpitSystem::Ews),
+    ("SimExtl", CockpitSystem::Lighting),
+    ("SimAntiCol", CockpitSystem::Lighting),
+    ("SimFuselageLight", CockpitSystem::Lighting),
+    ("SimWingLight", CockpitSystem::Lighting),
+    ("SimLights", CockpitSystem::Lighting),
+    ("SimStepAARLights", CockpitSystem::Lighting),
+    ("SimStepAnticol", CockpitSystem::Lighting),
+    ("SimStepFormationLights", CockpitSystem::Lighting),
+    ("SimInstrumentLight", CockpitSystem::Lighting),
+    ("SimInteriorLight", CockpitSystem::Lighting),
+    ("SimDedBrightness", CockpitSystem::Lighting),
+    ("OTW", CockpitSystem::Views),
+    ("FOV", CockpitSystem::Views),
+    ("Recenter", CockpitSystem::Views),
+    ("Radio", CockpitSystem::Comms),
+    ("Flight", CockpitSystem::Comms),
+    ("Element", CockpitSystem::Comms),
+    ("Wingman", CockpitSystem::Comms),
+    ("ATC", CockpitSystem::Comms),
+    ("AWACS", CockpitSystem::Comms),
+    ("Tanker", CockpitSystem::Comms),
+    ("SimBupUhf", CockpitSystem::Comms),
+    ("SimComm", CockpitSystem::Comms),
+    ("SimStepComm", CockpitSystem::Comms),
+    ("SimAud", CockpitSystem::Comms),
+];
+
+impl CockpitSystem {
+    pub const ALL: [CockpitSystem; 8] = [
+        CockpitSystem::Hotas,
+        CockpitSystem::IcpDed,
+        CockpitSystem::Mfds,
+        CockpitSystem::Ews,
+        CockpitSystem::Lighting,
+        CockpitSystem::Views,
+        CockpitSystem::Comms,
+        CockpitSystem::Other,
+    ];
+
+    /// The system a callback name belongs to.
+    pub fn of(callback_name: &str) -> CockpitSystem {
+        SYSTEMS
+            .iter()
+            .filter(|(prefix, _)| callback_name.starts_with(prefix))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(CockpitSystem::Other, |(_, system)| *system)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CockpitSystem::Hotas => "HOTAS",
+            CockpitSystem::IcpDed => "ICP/DED",
+            CockpitSystem::Mfds => "MFDs",
+            CockpitSystem::Ews => "EWS",
+            CockpitSystem::Lighting => "Lighting",
+            CockpitSystem::Views => "Views",
+            CockpitSystem::Comms => "Comms",
+            CockpitSystem::Other => "Other",
+        }
+    }
+}
+
+impl fmt::Display for CockpitSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Callback {
+    pub fn cockpit_system(&self) -> CockpitSystem {
+        CockpitSystem::of(&self.name)
+    }
+}
+
+impl FalconKeyfile {
+    /// The callbacks grouped by cockpit system, each group sorted by name.
+    /// Systems without callbacks are left out.
+    pub fn by_cockpit_system(&self) -> BTreeMap<CockpitSystem, Vec<&Callback>> {
+        let mut groups: BTreeMap<CockpitSystem, Vec<&Callback>> = BTreeMap::new();
+        for callback in self.callbacks.values() {
+            groups
+                .entry(callback.cockpit_system())
+                .or_default()
+                .push(callback);
+        }
+        for callbacks in groups.values_mut() {
+            callbacks.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_callbacks_to_systems() {
+        assert_eq!(CockpitSystem::of("SimTMSUp"), CockpitSystem::Hotas);
+        assert_eq!(
+            CockpitSystem::of("SimCommsSwitchLeft"),
+            CockpitSystem::Hotas
+        );
+        assert_eq!(CockpitSystem::of("SimComm1PowerOn"), CockpitSystem::Comms);
+        assert_eq!(
+            CockpitSystem::of("SimDedBrightnessCW"),
+            CockpitSystem::Lighting
+        );
+        assert_eq!(CockpitSystem::of("SimICPDEDUP"), CockpitSystem::IcpDed);
+        assert_eq!(CockpitSystem::of("OTWSwapMFDS"), CockpitSystem::Mfds);
+        assert_eq!(CockpitSystem::of("OTWViewLeft"), CockpitSystem::Views);
+        assert_eq!(CockpitSystem::of("SimEWSModeAuto"), CockpitSystem::Ews);
+
+        let keyfile = FalconKeyfile::from_path("test-data/T16000M-FCS-Full.key").unwrap();
+        let groups = keyfile.by_cockpit_system();
+        assert!(
+            groups[&CockpitSystem::Hotas]
+                .iter()
+                .any(|c| c.name == "SimPickle")
+        );
+        assert_eq!(groups.values().map(Vec::len).sum::<usize>(), keyfile.len());
+    }
+}
@@ -0,0 +1,124 @@
+//! `proptest` strategies for property testing code built on this crate, e.g.
+//!
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn diff_with_itself_is_empty(keyfile in test_util::keyfile(0..100)) {
+//!         prop_assert!(keyfile.diff(&keyfile).is_empty());
+//!     }
+//! }
+//! ```
+
+use crate::{Callback, FalconKeyfile, Key, KeyCombo, ModifierSet, Visibility};
+use proptest::prelude::*;
+use proptest::sample::SizeRange;
+use std::collections::HashMap;
+
+/// Any named key except the left modifier keys, which key files use as
+/// modifiers rather than keys.
+pub fn key() -> impl Strategy<Value = Key> {
+    let keys: Vec<Key> = (1..=0xFF)
+        .map(Key::from_scancode)
+        .filter(|key| {
+            !matches!(
+                key,
+                Key::Other(_) | Key::Unknown | Key::LShift | Key::LControl
+            )
+        })
+        .collect();
+    proptest::sample::select(keys)
+}
+
+pub fn modifiers() -> impl Strategy<Value = ModifierSet> {
+    (0..=ModifierSet::ALL.to_mask()).prop_map(ModifierSet::from_mask)
+}
+
+pub fn key_combo() -> impl Strategy<Value = KeyCombo> {
+    (key(), modifiers()).prop_map(|(key, modifiers)| KeyCombo::new(key, modifiers))
+}
+
+/// Callback names in the style of BMS, such as `SimCmsLeft`.
+pub fn callback_name() -> impl Strategy<Value = String> {
+    "(Sim|AF|OTW)[A-Z][a-z]{2,10}([A-Z][a-z]{2,8}){0,2}"
+}
+
+pub fn visibility() -> impl Strategy<Value = Visibility> {
+    prop_oneof![
+        4 => Just(Visibility::Visible),
+        1 => Just(Visibility::Locked),
+        1 => Just(Visibility::Hidden),
+    ]
+}
+
+/// A callback that is unbound, bound to a chord or bound to a combo followed
+/// by a chord.
+pub fn callback() -> impl Strategy<Value = Callback> {
+    (
+        callback_name(),
+        proptest::option::weighted(0.8, key_combo()),
+        proptest::option::weighted(0.1, key_combo()),
+        visibility(),
+        proptest::option::of("[A-Z]{3,6}: [A-Za-z ]{3,30}"),
+    )
+        .prop_map(|(name, chord, combo, visibility, description)| {
+            callback_from_parts(name, chord, combo, visibility, description)
+        })
+}
+
+fn callback_from_parts(
+    name: String,
+    chord: Option<KeyCombo>,
+    combo: Option<KeyCombo>,
+    visibility: Visibility,
+    description: Option<String>,
+) -> Callback {
+    // a combo sequence without a chord is not a binding
+    let combo = combo.filter(|_| chord.is_some());
+    Callback {
+        name,
+        key_code: chord.as_ref().map_or(0xFFFF, |c| c.key.scancode()),
+        readable_key_code: chord.as_ref().map_or(Key::Unknown, |c| c.key.clone()),
+        modifiers: chord.as_ref().map_or(ModifierSet::EMPTY, |c| c.modifiers),
+        combo_key_code: combo.as_ref().map_or(0, |c| c.key.scancode()),
+        readable_combo_key_code: combo.as_ref().map_or(Key::Unknown, |c| c.key.clone()),
+        combo_modifiers: combo.as_ref().map_or(ModifierSet::EMPTY, |c| c.modifiers),
+        visibility,
+        description,
+        section: None,
+        line: None,
+        raw_line: None,
+    }
+}
+
+/// A key file with a number of callbacks in `size`, all with distinct names.
+/// Chords may be shared like in real key files.
+pub fn keyfile(size: impl Into<SizeRange>) -> impl Strategy<Value = FalconKeyfile> {
+    proptest::collection::vec(callback(), size).prop_map(|callbacks| {
+        let callbacks: HashMap<_, _> = callbacks
+            .into_iter()
+            .map(|callback| (callback.name.clone(), callback))
+            .collect();
+        FalconKeyfile::new(String::from("generated.key"), callbacks)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn generated_combos_parse_back(combo in key_combo()) {
+            prop_assert_eq!(combo.to_string().parse::<KeyCombo>().unwrap(), combo);
+        }
+
+        #[test]
+        fn generated_keyfiles_are_consistent(keyfile in keyfile(0..40)) {
+            prop_assert!(keyfile.diff(&keyfile).is_empty());
+            for callback in keyfile.iter().filter(|c| c.is_bound()) {
+                let bound = keyfile.callbacks_for_combo(&callback.primary_combo());
+                prop_assert!(bound.iter().any(|c| c.name == callback.name));
+            }
+        }
+    }
+}
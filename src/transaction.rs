@@ -0,0 +1,177 @@
+use crate::{Conflict, EditError, FalconKeyfile, KeyCombo};
+use std::fmt;
+
+/// A single change of a keyfile, see the methods of the same name on
+/// `FalconKeyfile`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Edit {
+    SetBinding { name: String, combo: KeyCombo },
+    RemoveBinding { name: String },
+    RenameCallback { old: String, new: String },
+    SwapBindings { a: String, b: String },
+}
+
+impl Edit {
+    pub(crate) fn apply(&self, keyfile: &mut FalconKeyfile) -> Result<(), EditError> {
+        match self {
+            Edit::SetBinding { name, combo } => keyfile.set_binding(name, combo.clone()),
+            Edit::RemoveBinding { name } => keyfile.remove_binding(name),
+            Edit::RenameCallback { old, new } => keyfile.rename_callback(old, new),
+            Edit::SwapBindings { a, b } => keyfile.swap_bindings(a, b),
+        }
+    }
+}
+
+/// Why a transaction was rolled back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionError {
+    /// The edit at this position of the transaction failed.
+    Edit { index: usize, error: EditError },
+    /// The edits lead to conflicts that did not exist before.
+    Conflicts(Vec<Conflict>),
+}
+
+impl fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionError::Edit { index, error } => {
+                write!(f, "edit {} failed: {}", index, error)
+            }
+            TransactionError::Conflicts(conflicts) => {
+                write!(f, "edits lead to {} new conflicts", conflicts.len())
+            }
+        }
+    }
+}
+
+impl std::error::Error for TransactionError {}
+
+/// Edits staged on a keyfile that are applied all together or not at all,
+/// created with `FalconKeyfile::edit`.
+#[derive(Debug)]
+pub struct Transaction<'a> {
+    keyfile: &'a mut FalconKeyfile,
+    edits: Vec<Edit>,
+    allow_conflicts: bool,
+}
+
+impl Transaction<'_> {
+    pub fn set_binding(&mut self, name: &str, combo: KeyCombo) -> &mut Self {
+        self.stage(Edit::SetBinding {
+            name: String::from(name),
+            combo,
+        })
+    }
+
+    pub fn remove_binding(&mut self, name: &str) -> &mut Self {
+        self.stage(Edit::RemoveBinding {
+            name: String::from(name),
+        })
+    }
+
+    pub fn rename_callback(&mut self, old: &str, new: &str) -> &mut Self {
+        self.stage(Edit::RenameCallback {
+            old: String::from(old),
+            new: String::from(new),
+        })
+    }
+
+    pub fn swap_bindings(&mut self, a: &str, b: &str) -> &mut Self {
+        self.stage(Edit::SwapBindings {
+            a: String::from(a),
+            b: String::from(b),
+        })
+    }
+
+    pub fn stage(&mut self, edit: Edit) -> &mut Self {
+        self.edits.push(edit);
+        self
+    }
+
+    /// Commit even if the edits lead to new conflicts.
+    pub fn allow_conflicts(&mut self) -> &mut Self {
+        self.allow_conflicts = true;
+        self
+    }
+
+    /// The staged edits in order.
+    pub fn edits(&self) -> &[Edit] {
+        &self.edits
+    }
+
+    /// Applies the staged edits, leaving the keyfile untouched if one of them
+    /// fails or, unless allowed, they lead to new conflicts.
+    pub fn commit(self) -> Result<(), TransactionError> {
+        let mut staged = self.keyfile.clone();
+        for (index, edit) in self.edits.iter().enumerate() {
+            edit.apply(&mut staged)
+                .map_err(|error| TransactionError::Edit { index, error })?;
+        }
+
+        if !self.allow_conflicts {
+            let before = self.keyfile.conflicts();
+            let new: Vec<_> = staged
+                .conflicts()
+                .into_iter()
+                .filter(|conflict| !before.contains(conflict))
+                .collect();
+            if !new.is_empty() {
+                return Err(TransactionError::Conflicts(new));
+            }
+        }
+
+        *self.keyfile = staged;
+        Ok(())
+    }
+
+    /// Drops the staged edits.
+    pub fn rollback(self) {}
+}
+
+impl FalconKeyfile {
+    /// Starts staging edits to apply at once with `Transaction::commit`.
+    pub fn edit(&mut self) -> Transaction<'_> {
+        Transaction {
+            keyfile: self,
+            edits: vec![],
+            allow_conflicts: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FreeComboOptions;
+
+    #[test]
+    fn commit_all_or_nothing() {
+        let mut keyfile = FalconKeyfile::from_path("test-data/basic.key").unwrap();
+        let free = keyfile.free_combos(&FreeComboOptions::default());
+        let taken: KeyCombo = "LCtrl+UpArrow".parse().unwrap();
+
+        let mut transaction = keyfile.edit();
+        transaction
+            .set_binding("SimCursorUpLeft", free[0].clone())
+            .rename_callback("SimCursorUpLeft", "SimCursorNW")
+            .remove_binding("SimNoSuchCallback");
+        let error = transaction.commit().unwrap_err();
+        assert!(matches!(error, TransactionError::Edit { index: 2, .. }));
+        assert!(keyfile.contains("SimCursorUpLeft"));
+
+        let mut transaction = keyfile.edit();
+        transaction.set_binding("SimCursorUpLeft", taken.clone());
+        assert!(matches!(
+            transaction.commit(),
+            Err(TransactionError::Conflicts(_))
+        ));
+        assert!(!keyfile.callback("SimCursorUpLeft").unwrap().is_bound());
+
+        let mut transaction = keyfile.edit();
+        transaction
+            .set_binding("SimCursorUpLeft", free[0].clone())
+            .rename_callback("SimCursorUpLeft", "SimCursorNW");
+        transaction.commit().unwrap();
+        assert_eq!(keyfile.callbacks_for_combo(&free[0])[0].name, "SimCursorNW");
+    }
+}
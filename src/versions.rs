@@ -0,0 +1,799 @@
+//! Which callbacks exist in which BMS version, embedded from
+//! `data/callback-changes.tsv` on top of the callback table.
+
+use crate::lint::ESSENTIAL;
+use crate::{Callback, FalconKeyfile, Key, KnownCallbacks, ModifierSet, Visibility};
+use levenshtein::levenshtein;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::OnceLock;
+
+const CHANGES: &str = include_str!("../data/callback-changes.tsv");
+
+/// The BMS releases the callback table knows about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BmsVersion {
+    V4_34,
+    V4_35,
+    V4_36,
+    V4_37,
+}
+
+impl BmsVersion {
+    pub const ALL: [BmsVersion; 4] = [
+        BmsVersion::V4_34,
+        BmsVersion::V4_35,
+        BmsVersion::V4_36,
+        BmsVersion::V4_37,
+    ];
+    /// The version the callback table was taken from.
+    pub const LATEST: BmsVersion = BmsVersion::V4_37;
+
+    fn from_column(column: &str) -> Option<BmsVersion> {
+        BmsVersion::ALL
+            .into_iter()
+            .find(|version| version.to_string() == column)
+    }
+}
+
+impl fmt::Display for BmsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BmsVersion::V4_34 => write!(f, "4.34"),
+            BmsVersion::V4_35 => write!(f, "4.35"),
+            BmsVersion::V4_36 => write!(f, "4.36"),
+            BmsVersion::V4_37 => write!(f, "4.37"),
+        }
+    }
+}
+
+/// A callback that was added, removed or renamed in some version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct VersionChange<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) added: Option<BmsVersion>,
+    pub(crate) removed: Option<BmsVersion>,
+    pub(crate) replaced_by: Option<&'a str>,
+}
+
+impl VersionChange<'_> {
+    fn exists_in(&self, version: BmsVersion) -> bool {
+        self.added.is_none_or(|added| added <= version)
+            && self.removed.is_none_or(|removed| version < removed)
+    }
+}
+
+pub(crate) fn parse_changes(table: &str) -> Vec<VersionChange<'_>> {
+    let version = |column: &str| BmsVersion::from_column(column);
+    table
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+        .filter_map(|line| {
+            let columns: Vec<_> = line.split('\t').map(str::trim).collect();
+            Some(VersionChange {
+                name: columns.first()?,
+                added: columns.get(1).and_then(|c| version(c)),
+                removed: columns.get(2).and_then(|c| version(c)),
+                replaced_by: columns.get(3).copied().filter(|c| *c != "-"),
+            })
+        })
+        .collect()
+}
+
+/// The embedded changes.
+pub(crate) fn changes() -> &'static [VersionChange<'static>] {
+    static CHANGES_CELL: OnceLock<Vec<VersionChange<'static>>> = OnceLock::new();
+    CHANGES_CELL.get_or_init(|| parse_changes(CHANGES))
+}
+
+/// Whether BMS `version` knows the callback `name`.
+pub(crate) fn exists_in(name: &str, version: BmsVersion, changes: &[VersionChange]) -> bool {
+    match changes.iter().find(|change| change.name == name) {
+        Some(change) => change.exists_in(version),
+        None => KnownCallbacks::lookup(name).is_some(),
+    }
+}
+
+/// The outcome of `FalconKeyfile::detect_version`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionGuess {
+    pub version: BmsVersion,
+    /// From 0 to 1: the share of the file's callbacks `version` knows, split
+    /// between all versions that fit equally well.
+    pub confidence: f64,
+    /// Older versions that fit the file as well as `version`.
+    pub alternatives: Vec<BmsVersion>,
+    /// The callbacks of the file that only exist in some versions, sorted.
+    pub evidence: Vec<String>,
+}
+
+/// A problem `FalconKeyfile::validate` found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+    /// A callback the BMS version does not know, possibly a typo of `suggestion`.
+    Unknown {
+        name: String,
+        line: Option<usize>,
+        suggestion: Option<String>,
+    },
+    /// A callback that was removed in `removed_in` or before.
+    Deprecated {
+        name: String,
+        line: Option<usize>,
+        removed_in: BmsVersion,
+        replaced_by: Option<String>,
+    },
+    /// A callback every setup should bind that is bound nowhere.
+    MissingRecommended { name: String },
+}
+
+impl ValidationIssue {
+    pub fn name(&self) -> &str {
+        match self {
+            ValidationIssue::Unknown { name, .. } => name,
+            ValidationIssue::Deprecated { name, .. } => name,
+            ValidationIssue::MissingRecommended { name } => name,
+        }
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            ValidationIssue::Unknown { line, .. } => *line,
+            ValidationIssue::Deprecated { line, .. } => *line,
+            ValidationIssue::MissingRecommended { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(line) = self.line() {
+            write!(f, "line {}: ", line)?;
+        }
+        match self {
+            ValidationIssue::Unknown {
+                name, suggestion, ..
+            } => {
+                write!(f, "unknown callback {}", name)?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, ", did you mean {}?", suggestion)?;
+                }
+                Ok(())
+            }
+            ValidationIssue::Deprecated {
+                name,
+                removed_in,
+                replaced_by,
+                ..
+            } => {
+                write!(f, "callback {} was removed in BMS {}", name, removed_in)?;
+                if let Some(replaced_by) = replaced_by {
+                    write!(f, ", use {} instead", replaced_by)?;
+                }
+                Ok(())
+            }
+            ValidationIssue::MissingRecommended { name } => {
+                write!(f, "recommended callback {} is not bound", name)
+            }
+        }
+    }
+}
+
+/// A callback in a keyfile that a newer BMS version no longer knows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeprecatedCallback {
+    pub name: String,
+    pub line: Option<usize>,
+    /// The first version without the callback.
+    pub removed_in: BmsVersion,
+    /// The callback that took its place, if any.
+    pub replaced_by: Option<String>,
+}
+
+/// Outcome of `FalconKeyfile::migrate`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Old and new names of the renamed callbacks, sorted by old name.
+    pub renamed: Vec<(String, String)>,
+    /// Callbacks that were left as they are.
+    pub issues: Vec<MigrationIssue>,
+}
+
+/// A callback `FalconKeyfile::migrate` could not rename.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationIssue {
+    /// The callback was removed without a replacement.
+    Removed {
+        name: String,
+        line: Option<usize>,
+        removed_in: BmsVersion,
+    },
+    /// The keyfile already defines the replacement.
+    AlreadyDefined {
+        name: String,
+        line: Option<usize>,
+        replacement: String,
+    },
+}
+
+impl fmt::Display for MigrationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MigrationIssue::Removed {
+                name, removed_in, ..
+            } => write!(
+                f,
+                "{} was removed in BMS {} without a replacement",
+                name, removed_in
+            ),
+            MigrationIssue::AlreadyDefined {
+                name, replacement, ..
+            } => write!(
+                f,
+                "{} is replaced by {}, which is already defined",
+                name, replacement
+            ),
+        }
+    }
+}
+
+impl FalconKeyfile {
+    /// A key file with every callback of BMS `version`, all unbound, with
+    /// their descriptions and sections, for authors to fill in with
+    /// `set_binding` and friends. Sections are ordered by their number and
+    /// callbacks without a known section come first.
+    pub fn template(version: BmsVersion) -> FalconKeyfile {
+        FalconKeyfile::template_with(version, changes())
+    }
+
+    pub(crate) fn template_with(version: BmsVersion, changes: &[VersionChange]) -> FalconKeyfile {
+        let unbound = |name: &str, description: Option<&str>, section: Option<&str>| Callback {
+            name: String::from(name),
+            key_code: 0xFFFF,
+            readable_key_code: Key::Unknown,
+            modifiers: ModifierSet::EMPTY,
+            combo_key_code: 0,
+            readable_combo_key_code: Key::Unknown,
+            combo_modifiers: ModifierSet::EMPTY,
+            visibility: Visibility::Visible,
+            description: description.map(String::from),
+            section: section.map(String::from),
+            line: None,
+            raw_line: None,
+        };
+
+        let mut callbacks: HashMap<String, Callback> = KnownCallbacks::all()
+            .iter()
+            .filter(|known| exists_in(known.name, version, changes))
+            .map(|known| {
+                let callback = unbound(known.name, Some(known.description), Some(known.category));
+                (callback.name.clone(), callback)
+            })
+            .collect();
+        for change in changes.iter().filter(|c| c.exists_in(version)) {
+            callbacks
+                .entry(String::from(change.name))
+                .or_insert_with(|| unbound(change.name, None, None));
+        }
+
+        let mut sections: Vec<String> = callbacks
+            .values()
+            .filter_map(|c| c.section.clone())
+            .collect();
+        sections.sort();
+        sections.dedup();
+
+        let mut keyfile = FalconKeyfile::new(format!("BMS {} template.key", version), callbacks);
+        keyfile.sections = sections;
+        keyfile
+    }
+
+    /// Guesses the BMS version the file was made for from the callbacks it
+    /// uses. Among versions knowing the same number of its callbacks the
+    /// newest one wins. `None` if none of its callbacks was added or removed
+    /// in some version, as then every version fits.
+    pub fn detect_version(&self) -> Option<VersionGuess> {
+        self.detect_version_with(changes())
+    }
+
+    pub(crate) fn detect_version_with(&self, changes: &[VersionChange]) -> Option<VersionGuess> {
+        let mut names: Vec<&str> = self
+            .callbacks
+            .keys()
+            .map(String::as_str)
+            .chain(self.joystick.iter().map(|b| b.callback.as_str()))
+            .filter(|name| *name != "SimDoNothing")
+            .collect();
+        names.sort();
+        names.dedup();
+        if names.is_empty() {
+            return None;
+        }
+
+        let evidence: Vec<String> = names
+            .iter()
+            .filter(|name| changes.iter().any(|change| change.name == **name))
+            .map(|name| String::from(*name))
+            .collect();
+        if evidence.is_empty() {
+            return None;
+        }
+
+        let fits = |version: BmsVersion| {
+            names
+                .iter()
+                .filter(|name| exists_in(name, version, changes))
+                .count()
+        };
+        let best = BmsVersion::ALL.into_iter().map(fits).max()?;
+        let mut tied: Vec<_> = BmsVersion::ALL
+            .into_iter()
+            .filter(|version| fits(*version) == best)
+            .collect();
+        let version = tied.pop()?;
+        let confidence = best as f64 / names.len() as f64 / (tied.len() + 1) as f64;
+        Some(VersionGuess {
+            version,
+            confidence,
+            alternatives: tied,
+            evidence,
+        })
+    }
+
+    /// Checks the callback names of all keyboard and joystick lines against
+    /// the callbacks of BMS `version`, and the recommended callbacks against
+    /// the bound ones. Issues with a line come first, sorted by line.
+    pub fn validate(&self, version: BmsVersion) -> Vec<ValidationIssue> {
+        self.validate_with(version, changes())
+    }
+
+    pub(crate) fn validate_with(
+        &self,
+        version: BmsVersion,
+        changes: &[VersionChange],
+    ) -> Vec<ValidationIssue> {
+        let names = self
+            .callbacks
+            .values()
+            .map(|c| (c.name.as_str(), c.line))
+            .chain(self.joystick.iter().map(|b| (b.callback.as_str(), b.line)))
+            .filter(|(name, _)| *name != "SimDoNothing");
+
+        let mut issues = vec![];
+        for (name, line) in names {
+            if exists_in(name, version, changes) {
+                continue;
+            }
+            let removed = changes
+                .iter()
+                .find(|change| change.name == name)
+                .and_then(|change| {
+                    change
+                        .removed
+                        .filter(|removed| *removed <= version)
+                        .map(|removed| (removed, change.replaced_by))
+                });
+            issues.push(match removed {
+                Some((removed_in, replaced_by)) => ValidationIssue::Deprecated {
+                    name: String::from(name),
+                    line,
+                    removed_in,
+                    replaced_by: replaced_by.map(String::from),
+                },
+                None => ValidationIssue::Unknown {
+                    name: String::from(name),
+                    line,
+                    suggestion: suggest(name, version, changes),
+                },
+            });
+        }
+        issues.sort_by_key(|issue| {
+            (
+                issue.line().is_none(),
+                issue.line(),
+                issue.name().to_string(),
+            )
+        });
+
+        for name in ESSENTIAL {
+            let bound = self.callbacks.get(name).is_some_and(|c| c.is_bound())
+                || self.joystick.iter().any(|b| b.callback == name);
+            if !bound && exists_in(name, version, changes) {
+                issues.push(ValidationIssue::MissingRecommended {
+                    name: String::from(name),
+                });
+            }
+        }
+        issues
+    }
+
+    /// Keyboard and joystick lines of callbacks that were removed in some BMS
+    /// version, sorted by line.
+    pub fn deprecated_callbacks(&self) -> Vec<DeprecatedCallback> {
+        self.deprecated_with(changes())
+    }
+
+    pub(crate) fn deprecated_with(&self, changes: &[VersionChange]) -> Vec<DeprecatedCallback> {
+        let mut deprecated: Vec<_> = self
+            .callbacks
+            .values()
+            .map(|c| (c.name.as_str(), c.line))
+            .chain(self.joystick.iter().map(|b| (b.callback.as_str(), b.line)))
+            .filter_map(|(name, line)| {
+                let change = changes.iter().find(|change| change.name == name)?;
+                Some(DeprecatedCallback {
+                    name: String::from(name),
+                    line,
+                    removed_in: change.removed?,
+                    replaced_by: change.replaced_by.map(String::from),
+                })
+            })
+            .collect();
+        deprecated.sort_by(|a, b| (a.line, &a.name).cmp(&(b.line, &b.name)));
+        deprecated
+    }
+
+    /// Renames the callbacks that were renamed after BMS `from` up to and
+    /// including `to`, following chains of renames. Keyboard lines whose new
+    /// name is already taken are kept under their old name, together with
+    /// their joystick lines.
+    pub fn migrate(&mut self, from: BmsVersion, to: BmsVersion) -> MigrationReport {
+        self.migrate_with(from, to, changes())
+    }
+
+    pub(crate) fn migrate_with(
+        &mut self,
+        from: BmsVersion,
+        to: BmsVersion,
+        changes: &[VersionChange],
+    ) -> MigrationReport {
+        let mut report = MigrationReport::default();
+
+        let mut names: Vec<_> = self.callbacks.keys().cloned().collect();
+        names.sort();
+        for name in names {
+            let line = self.callbacks[&name].line;
+            match replacement(&name, from, to, changes).map(|new| new.map(String::from)) {
+                Ok(None) => {}
+                Ok(Some(new)) if self.callbacks.contains_key(&new) => {
+                    report.issues.push(MigrationIssue::AlreadyDefined {
+                        name,
+                        line,
+                        replacement: new,
+                    })
+                }
+                Ok(Some(new)) => {
+                    let mut callback = self.callbacks.remove(&name).unwrap();
+                    callback.name = new.clone();
+                    self.callbacks.insert(new.clone(), callback);
+                    report.renamed.push((name, new));
+                }
+                Err(removed_in) => report.issues.push(MigrationIssue::Removed {
+                    name,
+                    line,
+                    removed_in,
+                }),
+            }
+        }
+
+        // joystick lines follow their keyboard line, or stay if it could not
+        // be renamed
+        let rejected: HashSet<String> = report
+            .issues
+            .iter()
+            .filter_map(|issue| match issue {
+                MigrationIssue::AlreadyDefined { name, .. } => Some(name.clone()),
+                MigrationIssue::Removed { .. } => None,
+            })
+            .collect();
+        for binding in self.joystick.iter_mut() {
+            match replacement(&binding.callback, from, to, changes) {
+                Ok(None) => {}
+                Ok(Some(_)) if rejected.contains(&binding.callback) => {}
+                Ok(Some(new)) => {
+                    let rename = (binding.callback.clone(), String::from(new));
+                    if !report.renamed.contains(&rename) {
+                        report.renamed.push(rename);
+                    }
+                    binding.callback = String::from(new);
+                }
+                Err(removed_in) => report.issues.push(MigrationIssue::Removed {
+                    name: binding.callback.clone(),
+                    line: binding.line,
+                    removed_in,
+                }),
+            }
+        }
+        report.renamed.sort();
+
+        self.reindex();
+        report
+    }
+}
+
+/// The name `name` has in `to`, `None` if it was not renamed after `from`, or
+/// the version it was removed in without a replacement.
+fn replacement<'a>(
+    name: &'a str,
+    from: BmsVersion,
+    to: BmsVersion,
+    changes: &[VersionChange<'a>],
+) -> Result<Option<&'a str>, BmsVersion> {
+    let mut current = name;
+    // bounded by the number of changes so a cycle in the table cannot hang
+    for _ in 0..=changes.len() {
+        let change = changes.iter().find(|change| {
+            change.name == current
+                && change
+                    .removed
+                    .is_some_and(|removed| from < removed && removed <= to)
+        });
+        match change {
+            None => break,
+            Some(VersionChange {
+                replaced_by: Some(next),
+                ..
+            }) => current = next,
+            Some(change) => return Err(change.removed.unwrap()),
+        }
+    }
+    Ok((current != name).then_some(current))
+}
+
+/// A callback of `version` close enough to `name` to be what was meant.
+fn suggest(name: &str, version: BmsVersion, changes: &[VersionChange]) -> Option<String> {
+    KnownCallbacks::all()
+        .iter()
+        .map(|known| known.name)
+        .chain(changes.iter().map(|change| change.name))
+        .filter(|known| exists_in(known, version, changes))
+        .map(|known| (levenshtein(name, known), known))
+        .filter(|(distance, _)| *distance <= 3)
+        .min()
+        .map(|(_, known)| String::from(known))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParseOptions, parse_str};
+
+    #[test]
+    fn template_lists_every_callback_unbound() {
+        let template = FalconKeyfile::template(BmsVersion::LATEST);
+        assert_eq!(template.len(), KnownCallbacks::all().len());
+        assert_eq!(template.unbound_callbacks().len(), template.len());
+        assert!(template.sections().windows(2).all(|w| w[0] < w[1]));
+        let issues = template.validate(BmsVersion::LATEST);
+        assert!(
+            issues
+                .iter()
+                .all(|i| matches!(i, ValidationIssue::MissingRecommended { .. }))
+        );
+
+        let changes = parse_changes("SimNewThing\t4.37\t-\t-\nAFGearToggle\t-\t4.36\t-\n");
+        let old = FalconKeyfile::template_with(BmsVersion::V4_35, &changes);
+        assert!(old.contains("AFGearToggle"));
+        assert!(!old.contains("SimNewThing"));
+        let new = FalconKeyfile::template_with(BmsVersion::V4_37, &changes);
+        assert!(!new.contains("AFGearToggle"));
+        assert!(new.callback("SimNewThing").unwrap().section.is_none());
+
+        let text = template.canonicalize();
+        let parsed = parse_str(String::from("template.key"), &text, &ParseOptions::strict())
+            .unwrap()
+            .keyfile;
+        assert_eq!(parsed.len(), template.len());
+        assert_eq!(parsed.sections(), template.sections());
+    }
+
+    #[test]
+    fn validate_against_version() {
+        let changes = parse_changes(
+            "# test
+SimOldThing\t-\t4.36\tSimNewThing
+SimNewThing\t4.36\t-\t-
+",
+        );
+        let content = "# header
+SimOldThing -1 0 0x30 0 0 0 1 \"Old\"
+AFGearTogle -1 0 0x22 0 0 0 1 \"Typo\"
+SimPickle 2 -1 -2 0 0x0 -1
+SimNewThing -1 0 0x31 0 0 0 1 \"New\"
+";
+        let keyfile = parse_str(String::from("inline"), content, &ParseOptions::default())
+            .unwrap()
+            .keyfile;
+
+        let issues = keyfile.validate_with(BmsVersion::V4_37, &changes);
+        assert_eq!(
+            issues[0],
+            ValidationIssue::Deprecated {
+                name: String::from("SimOldThing"),
+                line: Some(2),
+                removed_in: BmsVersion::V4_36,
+                replaced_by: Some(String::from("SimNewThing")),
+            }
+        );
+        assert_eq!(
+            issues[1].to_string(),
+            "line 3: unknown callback AFGearTogle, did you mean AFGearToggle?"
+        );
+        assert!(
+            issues[2..]
+                .iter()
+                .all(|issue| matches!(issue, ValidationIssue::MissingRecommended { .. }))
+        );
+        assert!(issues.iter().any(|issue| issue.name() == "AFGearToggle"));
+        assert!(!issues.iter().any(|issue| issue.name() == "SimPickle"));
+
+        // SimOldThing still exists in 4.35, its replacement does not yet
+        let issues = keyfile.validate_with(BmsVersion::V4_35, &changes);
+        assert_eq!(issues[0].name(), "AFGearTogle");
+        assert!(
+            matches!(&issues[1], ValidationIssue::Unknown { name, .. } if name == "SimNewThing")
+        );
+    }
+
+    #[test]
+    fn detect_version_from_callbacks() {
+        let changes = parse_changes(
+            "SimOldThing\t-\t4.36\tSimNewThing
+SimNewThing\t4.36\t-\t-
+SimNewerThing\t4.37\t-\t-
+",
+        );
+        let content = "# header
+SimNewThing -1 0 0x30 0 0 0 1 \"New\"
+AFGearToggle -1 0 0x22 0 0 0 1 \"Gear\"
+";
+        let keyfile = parse_str(String::from("inline"), content, &ParseOptions::default())
+            .unwrap()
+            .keyfile;
+        let guess = keyfile.detect_version_with(&changes).unwrap();
+        assert_eq!(guess.version, BmsVersion::V4_37);
+        assert_eq!(guess.alternatives, vec![BmsVersion::V4_36]);
+        assert_eq!(guess.confidence, 0.5);
+        assert_eq!(guess.evidence, vec![String::from("SimNewThing")]);
+
+        let content = "# header
+SimOldThing -1 0 0x30 0 0 0 1 \"Old\"
+AFGearToggle -1 0 0x22 0 0 0 1 \"Gear\"
+SimNewThing 3 -1 -2 0 0x0 -1
+";
+        let keyfile = parse_str(String::from("inline"), content, &ParseOptions::default())
+            .unwrap()
+            .keyfile;
+        let guess = keyfile.detect_version_with(&changes).unwrap();
+        assert_eq!(guess.version, BmsVersion::V4_37);
+        assert!(guess.confidence < 1.0);
+
+        let content = "# header
+SimOldThing -1 0 0x30 0 0 0 1 \"Old\"
+SimNewerThing -1 0 0x31 0 0 0 1 \"Newer\"
+";
+        let keyfile = parse_str(String::from("inline"), content, &ParseOptions::default())
+            .unwrap()
+            .keyfile;
+        let guess = keyfile.detect_version_with(&changes).unwrap();
+        assert_eq!(guess.version, BmsVersion::V4_37);
+        assert_eq!(
+            guess.alternatives,
+            vec![BmsVersion::V4_34, BmsVersion::V4_35]
+        );
+
+        let empty = FalconKeyfile::new(String::from("empty"), HashMap::new());
+        assert_eq!(empty.detect_version_with(&changes), None);
+        let content = "# header
+AFGearToggle -1 0 0x22 0 0 0 1 \"Gear\"
+";
+        let keyfile = parse_str(String::from("inline"), content, &ParseOptions::default())
+            .unwrap()
+            .keyfile;
+        assert_eq!(keyfile.detect_version_with(&changes), None);
+    }
+
+    #[test]
+    fn detect_version_with_the_embedded_table() {
+        let keyfile = FalconKeyfile::from_path("test-data/T16000M-FCS-Full.key").unwrap();
+        let versioned = keyfile
+            .iter()
+            .any(|c| changes().iter().any(|change| change.name == c.name));
+        let guess = keyfile.detect_version();
+        assert_eq!(guess.is_some(), versioned);
+        assert_eq!(keyfile.metadata().version(), guess.map(|g| g.version));
+        // the file shipped with 4.37
+        if let Some(guess) = keyfile.detect_version() {
+            assert_eq!(guess.version, BmsVersion::V4_37);
+        }
+    }
+
+    #[test]
+    fn migrate_renamed_callbacks() {
+        let changes = parse_changes(
+            "SimOldThing\t-\t4.35\tSimOlderThing
+SimOlderThing\t4.35\t4.36\tSimNewThing
+SimGoneThing\t-\t4.36\t-
+",
+        );
+        let content = "# header
+SimOldThing -1 0 0x30 0 0 0 1 \"Old\"
+SimGoneThing -1 0 0x31 0 0 0 1 \"Gone\"
+SimOldThing 3 -1 -2 0 0x0 -1
+";
+        let mut keyfile = parse_str(String::from("inline"), content, &ParseOptions::default())
+            .unwrap()
+            .keyfile;
+
+        let report = keyfile.migrate_with(BmsVersion::V4_34, BmsVersion::V4_37, &changes);
+        assert_eq!(
+            report.renamed,
+            vec![(String::from("SimOldThing"), String::from("SimNewThing"))]
+        );
+        assert_eq!(
+            report.issues,
+            vec![MigrationIssue::Removed {
+                name: String::from("SimGoneThing"),
+                line: Some(3),
+                removed_in: BmsVersion::V4_36,
+            }]
+        );
+        assert!(keyfile.callback("SimNewThing").is_some());
+        assert!(keyfile.callback("SimOldThing").is_none());
+        assert_eq!(keyfile.joystick_bindings()[0].callback, "SimNewThing");
+
+        let content = "# header
+SimOldThing -1 0 0x30 0 0 0 1 \"Old\"
+SimNewThing -1 0 0x31 0 0 0 1 \"New\"
+SimOldThing 3 -1 -2 0 0x0 -1
+";
+        let mut keyfile = parse_str(String::from("inline"), content, &ParseOptions::default())
+            .unwrap()
+            .keyfile;
+        let report = keyfile.migrate_with(BmsVersion::V4_34, BmsVersion::V4_37, &changes);
+        assert!(report.renamed.is_empty());
+        assert!(matches!(
+            &report.issues[..],
+            [MigrationIssue::AlreadyDefined { name, .. }] if name == "SimOldThing"
+        ));
+        assert_eq!(keyfile.joystick_bindings()[0].callback, "SimOldThing");
+    }
+
+    #[test]
+    fn migrate_with_the_embedded_table() {
+        let mut keyfile = FalconKeyfile::from_path("test-data/T16000M-FCS-Full.key").unwrap();
+        let before = keyfile.clone();
+        for change in changes() {
+            assert!(
+                change.added.is_some() || change.removed.is_some(),
+                "{:?}",
+                change
+            );
+        }
+        // a current file has nothing to migrate
+        let report = keyfile.migrate(BmsVersion::V4_34, BmsVersion::LATEST);
+        assert!(report.renamed.is_empty() && report.issues.is_empty());
+        assert!(keyfile.diff(&before).is_empty());
+    }
+
+    #[test]
+    fn find_deprecated_callbacks() {
+        let changes =
+            parse_changes("SimOldThing\t-\t4.35\tSimNewThing\nSimGoneThing\t-\t4.36\t-\n");
+        let content = "# header
+SimGoneThing -1 0 0x31 0 0 0 1 \"Gone\"
+AFGearToggle -1 0 0x22 0 0 0 1 \"Gear\"
+SimOldThing 3 -1 -2 0 0x0 -1
+";
+        let keyfile = parse_str(String::from("inline"), content, &ParseOptions::default())
+            .unwrap()
+            .keyfile;
+
+        let deprecated = keyfile.deprecated_with(&changes);
+        assert_eq!(deprecated.len(), 2);
+        assert_eq!(deprecated[0].name, "SimGoneThing");
+        assert_eq!(deprecated[0].removed_in, BmsVersion::V4_36);
+        assert_eq!(deprecated[1].line, Some(4));
+        assert_eq!(deprecated[1].replaced_by.as_deref(), Some("SimNewThing"));
+    }
+}
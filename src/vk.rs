@@ -0,0 +1,148 @@
+//! Windows virtual-key codes, the `VK_*` constants.
+
+use crate::Key;
+
+/// Keys and their virtual-key codes. `Return` and `NumpadEnter` share
+/// `VK_RETURN`, Windows tells them apart by the extended key flag only.
+const VK_CODES: [(Key, u16); 103] = [
+    (Key::Escape, 0x1B),
+    (Key::Num1, 0x31),
+    (Key::Num2, 0x32),
+    (Key::Num3, 0x33),
+    (Key::Num4, 0x34),
+    (Key::Num5, 0x35),
+    (Key::Num6, 0x36),
+    (Key::Num7, 0x37),
+    (Key::Num8, 0x38),
+    (Key::Num9, 0x39),
+    (Key::Num0, 0x30),
+    (Key::Minus, 0xBD),
+    (Key::Equals, 0xBB),
+    (Key::Backspace, 0x08),
+    (Key::Tab, 0x09),
+    (Key::A, 0x41),
+    (Key::B, 0x42),
+    (Key::C, 0x43),
+    (Key::D, 0x44),
+    (Key::E, 0x45),
+    (Key::F, 0x46),
+    (Key::G, 0x47),
+    (Key::H, 0x48),
+    (Key::I, 0x49),
+    (Key::J, 0x4A),
+    (Key::K, 0x4B),
+    (Key::L, 0x4C),
+    (Key::M, 0x4D),
+    (Key::N, 0x4E),
+    (Key::O, 0x4F),
+    (Key::P, 0x50),
+    (Key::Q, 0x51),
+    (Key::R, 0x52),
+    (Key::S, 0x53),
+    (Key::T, 0x54),
+    (Key::U, 0x55),
+    (Key::V, 0x56),
+    (Key::W, 0x57),
+    (Key::X, 0x58),
+    (Key::Y, 0x59),
+    (Key::Z, 0x5A),
+    (Key::LeftBracket, 0xDB),
+    (Key::RightBracket, 0xDD),
+    (Key::Return, 0x0D),
+    (Key::LControl, 0xA2),
+    (Key::Semicolon, 0xBA),
+    (Key::Apostrophe, 0xDE),
+    (Key::BackQuote, 0xC0),
+    (Key::LShift, 0xA0),
+    (Key::Backslash, 0xDC),
+    (Key::Comma, 0xBC),
+    (Key::Period, 0xBE),
+    (Key::Slash, 0xBF),
+    (Key::Multiply, 0x6A),
+    (Key::Space, 0x20),
+    (Key::CapsLock, 0x14),
+    (Key::F1, 0x70),
+    (Key::F2, 0x71),
+    (Key::F3, 0x72),
+    (Key::F4, 0x73),
+    (Key::F5, 0x74),
+    (Key::F6, 0x75),
+    (Key::F7, 0x76),
+    (Key::F8, 0x77),
+    (Key::F9, 0x78),
+    (Key::F10, 0x79),
+    (Key::F11, 0x7A),
+    (Key::F12, 0x7B),
+    (Key::F13, 0x7C),
+    (Key::F14, 0x7D),
+    (Key::F15, 0x7E),
+    (Key::Numlock, 0x90),
+    (Key::ScrollLock, 0x91),
+    (Key::Numpad0, 0x60),
+    (Key::Numpad1, 0x61),
+    (Key::Numpad2, 0x62),
+    (Key::Numpad3, 0x63),
+    (Key::Numpad4, 0x64),
+    (Key::Numpad5, 0x65),
+    (Key::Numpad6, 0x66),
+    (Key::Numpad7, 0x67),
+    (Key::Numpad8, 0x68),
+    (Key::Numpad9, 0x69),
+    (Key::Subtract, 0x6D),
+    (Key::Add, 0x6B),
+    (Key::Decimal, 0x6E),
+    (Key::NumpadEnter, 0x0D),
+    (Key::RControl, 0xA3),
+    (Key::Divide, 0x6F),
+    (Key::PrintScr, 0x2C),
+    (Key::Home, 0x24),
+    (Key::UpArrow, 0x26),
+    (Key::PageUp, 0x21),
+    (Key::LeftArrow, 0x25),
+    (Key::RightArrow, 0x27),
+    (Key::End, 0x23),
+    (Key::DownArrow, 0x28),
+    (Key::PageDown, 0x22),
+    (Key::Insert, 0x2D),
+    (Key::Delete, 0x2E),
+    (Key::LWin, 0x5B),
+    (Key::RWin, 0x5C),
+    (Key::Apps, 0x5D),
+];
+
+impl Key {
+    /// The virtual-key code of the key, `None` for `Unknown` and unnamed keys.
+    pub fn to_vk(&self) -> Option<u16> {
+        VK_CODES
+            .iter()
+            .find(|(key, _)| key == self)
+            .map(|(_, vk)| *vk)
+    }
+
+    /// The key of a virtual-key code, `VK_RETURN` is `Return`. Left and right
+    /// variants only, codes such as `VK_SHIFT` have no key.
+    pub fn from_vk(vk: u16) -> Option<Key> {
+        VK_CODES
+            .iter()
+            .find(|(_, code)| *code == vk)
+            .map(|(key, _)| key.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::named_keys;
+
+    #[test]
+    fn virtual_key_round_trip() {
+        assert_eq!(Key::A.to_vk(), Some(0x41));
+        assert_eq!(Key::F13.to_vk(), Some(0x7C));
+        assert_eq!(Key::from_vk(0x26), Some(Key::UpArrow));
+        assert_eq!(Key::from_vk(0x10), None);
+        assert_eq!(Key::Unknown.to_vk(), None);
+        for key in named_keys().filter(|key| *key != Key::NumpadEnter) {
+            assert_eq!(key.to_vk().and_then(Key::from_vk), Some(key));
+        }
+    }
+}
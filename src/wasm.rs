@@ -0,0 +1,120 @@
+//! Bindings for JavaScript through `wasm-bindgen`, see the README for how
+//! to build the module.
+//!
+//! Everything that is not a plain string or number is returned as JSON text,
+//! callbacks in the shape of the `serde` representation of `Callback` and
+//! whole key files as documented in `export::json`.
+
+use crate::{Callback, FalconKeyfile, KeyCombo, ParseOptions, parse_str};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// A parsed key file.
+#[wasm_bindgen(js_name = Keyfile)]
+pub struct WasmKeyfile {
+    keyfile: FalconKeyfile,
+    warnings: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct JsConflict {
+    chord: String,
+    callbacks: Vec<String>,
+}
+
+#[wasm_bindgen(js_class = Keyfile)]
+impl WasmKeyfile {
+    /// Parses the text of a key file, skipping malformed lines.
+    #[wasm_bindgen(constructor)]
+    pub fn new(name: String, content: &str) -> Result<WasmKeyfile, JsError> {
+        WasmKeyfile::parse(name, content).map_err(|e| JsError::new(&e))
+    }
+
+    /// The problems found while parsing as a JSON array of messages.
+    pub fn warnings(&self) -> String {
+        to_json(&self.warnings)
+    }
+
+    /// The whole key file as a JSON document.
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> String {
+        self.keyfile.to_json()
+    }
+
+    /// The callback as JSON, `undefined` if there is none by that name.
+    pub fn callback(&self, name: &str) -> Option<String> {
+        self.keyfile.callbacks.get(name).map(to_json)
+    }
+
+    /// The names of the callbacks in the file, sorted.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<_> = self.keyfile.names().map(String::from).collect();
+        names.sort();
+        names
+    }
+
+    /// The callbacks triggered by a chord like `LAlt+C` as a JSON array.
+    #[wasm_bindgen(js_name = callbacksForChord)]
+    pub fn callbacks_for_chord(&self, chord: &str) -> Result<String, JsError> {
+        self.chord_callbacks(chord).map_err(|e| JsError::new(&e))
+    }
+
+    /// Chords bound to more than one callback as a JSON array of objects with
+    /// `chord` and `callbacks`.
+    pub fn conflicts(&self) -> String {
+        let conflicts: Vec<_> = self
+            .keyfile
+            .conflicts()
+            .into_iter()
+            .map(|conflict| JsConflict {
+                chord: conflict.binding().to_string(),
+                callbacks: conflict.callbacks,
+            })
+            .collect();
+        to_json(&conflicts)
+    }
+}
+
+impl WasmKeyfile {
+    fn parse(name: String, content: &str) -> Result<WasmKeyfile, String> {
+        let report =
+            parse_str(name, content, &ParseOptions::default()).map_err(|e| e.to_string())?;
+        Ok(WasmKeyfile {
+            keyfile: report.keyfile,
+            warnings: report.warnings.iter().map(ToString::to_string).collect(),
+        })
+    }
+
+    fn chord_callbacks(&self, chord: &str) -> Result<String, String> {
+        let combo: KeyCombo = chord.parse().map_err(|e| format!("{}", e))?;
+        let callbacks: Vec<&Callback> = self.keyfile.callbacks_for_combo(&combo);
+        Ok(to_json(&callbacks))
+    }
+}
+
+fn to_json(value: &impl Serialize) -> String {
+    serde_json::to_string(value).expect("bindings are always serializable")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_as_json() {
+        let content = std::fs::read_to_string("test-data/basic.key").unwrap();
+        let keyfile = WasmKeyfile::parse(String::from("basic.key"), &content).unwrap();
+
+        let callbacks: serde_json::Value =
+            serde_json::from_str(&keyfile.chord_callbacks("P").unwrap()).unwrap();
+        assert!(
+            callbacks
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|c| c["name"] == "SimPilotToggle")
+        );
+        assert!(keyfile.callback("SimPilotToggle").is_some());
+        assert!(keyfile.chord_callbacks("NoSuchKey").is_err());
+    }
+}
@@ -0,0 +1,226 @@
+//! Re-parsing a key file whenever it changes on disk, e.g. to keep an overlay
+//! in sync while the bindings are edited in BMS.
+//!
+//! The directory of the file is watched rather than the file itself since
+//! BMS and most editors replace the file instead of writing to it.
+
+use crate::{FalconKeyfile, KeyfileDiff, PathError};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::time::Duration;
+
+/// The key file after a change and what changed compared to the previous
+/// snapshot. `diff` is empty when only joystick bindings changed.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub keyfile: FalconKeyfile,
+    pub diff: KeyfileDiff,
+}
+
+#[derive(Debug)]
+pub enum WatchError {
+    /// The file could not be parsed when the watch started.
+    Path(PathError),
+    /// The file system cannot be watched.
+    Notify(notify::Error),
+}
+
+impl fmt::Display for WatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WatchError::Path(error) => write!(f, "{}", error),
+            WatchError::Notify(error) => write!(f, "cannot watch key file: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for WatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WatchError::Path(error) => Some(error),
+            WatchError::Notify(error) => Some(error),
+        }
+    }
+}
+
+/// Watches a key file until dropped. Changes that leave the bindings as they
+/// were are not reported, a file that fails to parse is reported as an error
+/// and the next successful parse is compared to the last good snapshot.
+pub struct KeyfileWatcher {
+    current: FalconKeyfile,
+    receiver: Receiver<Result<Snapshot, PathError>>,
+    _watcher: RecommendedWatcher,
+}
+
+impl KeyfileWatcher {
+    /// Parses the key file at `path` and starts watching it.
+    pub fn new(path: impl AsRef<Path>) -> Result<KeyfileWatcher, WatchError> {
+        let path = path.as_ref().to_path_buf();
+        let current = FalconKeyfile::from_path(&path).map_err(WatchError::Path)?;
+        let directory = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let mut previous = current.clone();
+        let file_name = path.file_name().map(ToOwned::to_owned);
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                let relevant = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                    && event
+                        .paths
+                        .iter()
+                        .any(|p| p.file_name() == file_name.as_deref());
+                if !relevant {
+                    return;
+                }
+                let update = match FalconKeyfile::from_path(&path) {
+                    Ok(keyfile) => {
+                        let diff = previous.diff(&keyfile);
+                        // the diff leaves out joystick bindings
+                        if diff.is_empty() && previous.fingerprint() == keyfile.fingerprint() {
+                            return;
+                        }
+                        previous = keyfile.clone();
+                        Ok(Snapshot { keyfile, diff })
+                    }
+                    // Caught in the middle of a write, the next event brings the rest.
+                    Err(PathError {
+                        error: crate::KeyFileError::Empty,
+                        ..
+                    }) => return,
+                    Err(error) => Err(error),
+                };
+                let _ = sender.send(update);
+            })
+            .map_err(WatchError::Notify)?;
+        watcher
+            .watch(&directory, RecursiveMode::NonRecursive)
+            .map_err(WatchError::Notify)?;
+
+        Ok(KeyfileWatcher {
+            current,
+            receiver,
+            _watcher: watcher,
+        })
+    }
+
+    /// The most recent successfully parsed version of the file.
+    pub fn current(&self) -> &FalconKeyfile {
+        &self.current
+    }
+
+    /// Waits for the next change.
+    pub fn recv(&mut self) -> Result<Snapshot, PathError> {
+        let update = self
+            .receiver
+            .recv()
+            .expect("the watcher lives as long as the receiver");
+        self.update(update)
+    }
+
+    /// Waits up to `timeout` for the next change, `None` if there was none.
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Option<Result<Snapshot, PathError>> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(update) => Some(self.update(update)),
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => None,
+        }
+    }
+
+    /// The next change if there already is one.
+    pub fn try_recv(&mut self) -> Option<Result<Snapshot, PathError>> {
+        match self.receiver.try_recv() {
+            Ok(update) => Some(self.update(update)),
+            Err(TryRecvError::Empty | TryRecvError::Disconnected) => None,
+        }
+    }
+
+    fn update(&mut self, update: Result<Snapshot, PathError>) -> Result<Snapshot, PathError> {
+        if let Ok(snapshot) = &update {
+            self.current = snapshot.keyfile.clone();
+        }
+        update
+    }
+}
+
+impl FalconKeyfile {
+    /// Parses the key file at `path` and watches it for changes.
+    pub fn watch(path: impl AsRef<Path>) -> Result<KeyfileWatcher, WatchError> {
+        KeyfileWatcher::new(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Watches a copy of `source` in a fresh directory, replaces `from` with
+    /// `to` in it and waits for the change.
+    fn watch_change(test: &str, source: &str, from: &str, to: &str) -> (KeyfileWatcher, Snapshot) {
+        let directory =
+            std::env::temp_dir().join(format!("falcon-watch-{}-{}", test, std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("watched.key");
+        let original = std::fs::read_to_string(source).unwrap();
+        std::fs::write(&path, &original).unwrap();
+
+        let mut watcher = FalconKeyfile::watch(&path).unwrap();
+        let changed = original.replace(from, to);
+        assert_ne!(changed, original);
+        let replacement = directory.join("watched.key.tmp");
+        std::fs::write(&replacement, changed).unwrap();
+        std::fs::rename(&replacement, &path).unwrap();
+
+        let snapshot = watcher
+            .recv_timeout(Duration::from_secs(5))
+            .expect("a change is reported")
+            .unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+        (watcher, snapshot)
+    }
+
+    #[test]
+    fn report_rebound_callbacks() {
+        let (watcher, snapshot) = watch_change(
+            "keyboard",
+            "test-data/basic.key",
+            "SimPilotToggle -1 0 0x19 0 0x2E 4",
+            "SimPilotToggle -1 0 0x1A 0 0x2E 4",
+        );
+        assert_eq!(snapshot.diff.rebound.len(), 1);
+        assert_eq!(snapshot.diff.rebound[0].name(), "SimPilotToggle");
+        assert!(watcher.current().callback("SimPilotToggle").is_some());
+    }
+
+    #[test]
+    fn report_rebound_joystick_buttons() {
+        let (watcher, snapshot) = watch_change(
+            "joystick",
+            "test-data/T16000M-FCS-Full.key",
+            "SimPickle 2 -1 -2 0 0x0 -1",
+            "SimPickle 5 -1 -2 0 0x0 -1",
+        );
+        assert!(snapshot.diff.is_empty());
+        let pickle = |keyfile: &FalconKeyfile| {
+            keyfile
+                .joystick_bindings()
+                .iter()
+                .find(|b| b.callback == "SimPickle")
+                .map(|b| b.input)
+        };
+        assert_eq!(
+            pickle(&snapshot.keyfile),
+            Some(crate::JoystickInput::Button(5))
+        );
+        assert_eq!(
+            pickle(watcher.current()),
+            Some(crate::JoystickInput::Button(5))
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{Callback, FalconKeyfile, KeyCombo, KeyfileDiff, PathError};
+use std::path::Path;
+
+/// All key files of a directory such as BMS' `User/Config`, sorted by name.
+#[derive(Debug, Clone, Default)]
+pub struct Workspace {
+    keyfiles: Vec<FalconKeyfile>,
+}
+
+/// The key files found by `Workspace::scan` and the ones that failed to parse.
+#[derive(Debug)]
+pub struct WorkspaceScan {
+    pub workspace: Workspace,
+    pub errors: Vec<PathError>,
+}
+
+impl Workspace {
+    /// Parses every `.key` file directly inside `directory`. Files that
+    /// cannot be parsed are reported next to the workspace instead of failing
+    /// the scan.
+    pub fn scan(directory: impl AsRef<Path>) -> std::io::Result<WorkspaceScan> {
+        let mut paths = vec![];
+        for entry in std::fs::read_dir(directory)? {
+            let path = entry?.path();
+            let is_key_file = path
+                .extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("key"));
+            if is_key_file && path.is_file() {
+                paths.push(path);
+            }
+        }
+
+        Ok(parse_paths(&paths))
+    }
+
+    /// Adds a key file, replacing one with the same name.
+    pub fn insert(&mut self, keyfile: FalconKeyfile) {
+        match self.position(&keyfile.name) {
+            Ok(index) => self.keyfiles[index] = keyfile,
+            Err(index) => self.keyfiles.insert(index, keyfile),
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<FalconKeyfile> {
+        self.position(name)
+            .ok()
+            .map(|index| self.keyfiles.remove(index))
+    }
+
+    /// The key file with this file name, e.g. `BMS - Full.key`.
+    pub fn get(&self, name: &str) -> Option<&FalconKeyfile> {
+        self.position(name).ok().map(|index| &self.keyfiles[index])
+    }
+
+    pub fn keyfiles(&self) -> &[FalconKeyfile] {
+        &self.keyfiles
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.keyfiles.iter().map(|k| k.name.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.keyfiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyfiles.is_empty()
+    }
+
+    /// The callbacks bound to exactly this chord in every file that binds it.
+    pub fn bound_to(&self, combo: &KeyCombo) -> Vec<(&str, Vec<&Callback>)> {
+        self.keyfiles
+            .iter()
+            .map(|k| (k.name.as_str(), k.callbacks_for_combo(combo)))
+            .filter(|(_, callbacks)| !callbacks.is_empty())
+            .collect()
+    }
+
+    /// The callback of this name in every file that defines it.
+    pub fn callback_in_files(&self, callback_name: &str) -> Vec<(&str, &Callback)> {
+        self.keyfiles
+            .iter()
+            .filter_map(|k| Some((k.name.as_str(), k.callbacks.get(callback_name)?)))
+            .collect()
+    }
+
+    /// The differences from the file named `old` to the one named `new`,
+    /// `None` if either is not in the workspace.
+    pub fn diff(&self, old: &str, new: &str) -> Option<KeyfileDiff> {
+        Some(self.get(old)?.diff(self.get(new)?))
+    }
+
+    fn position(&self, name: &str) -> Result<usize, usize> {
+        self.keyfiles
+            .binary_search_by(|k| k.name.as_str().cmp(name))
+    }
+}
+
+/// Parses the key files at `paths` in parallel, keeping the ones that parse in
+/// a workspace and reporting the others.
+#[cfg(feature = "rayon")]
+pub fn parse_all<P: AsRef<Path> + Sync>(paths: &[P]) -> WorkspaceScan {
+    parse_paths(paths)
+}
+
+fn parse_paths<P: AsRef<Path> + Sync>(paths: &[P]) -> WorkspaceScan {
+    #[cfg(feature = "rayon")]
+    let results: Vec<_> = {
+        use rayon::prelude::*;
+        paths.par_iter().map(FalconKeyfile::from_path).collect()
+    };
+    #[cfg(not(feature = "rayon"))]
+    let results: Vec<_> = paths.iter().map(FalconKeyfile::from_path).collect();
+
+    let mut workspace = Workspace::default();
+    let mut errors = vec![];
+    for result in results {
+        match result {
+            Ok(keyfile) => workspace.insert(keyfile),
+            Err(error) => errors.push(error),
+        }
+    }
+    WorkspaceScan { workspace, errors }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_across_files() {
+        let scan = Workspace::scan("test-data").unwrap();
+        let workspace = scan.workspace;
+        assert_eq!(workspace.len() + scan.errors.len(), 3);
+        assert!(workspace.get("basic.key").is_some());
+
+        let combo: KeyCombo = "LAlt+Q".parse().unwrap();
+        let files: Vec<_> = workspace
+            .bound_to(&combo)
+            .into_iter()
+            .map(|(f, _)| f)
+            .collect();
+        assert!(files.contains(&"basic.key"));
+        assert!(files.contains(&"T16000M-FCS-Full.key"));
+
+        let diff = workspace.diff("basic.key", "T16000M-FCS-Full.key").unwrap();
+        assert!(!diff.is_empty());
+        assert!(workspace.diff("basic.key", "missing.key").is_none());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parse_in_parallel() {
+        let scan = parse_all(&[
+            "test-data/basic.key",
+            "test-data/T16000M-FCS-Full.key",
+            "missing.key",
+        ]);
+        assert_eq!(
+            scan.workspace.names().collect::<Vec<_>>(),
+            ["T16000M-FCS-Full.key", "basic.key"]
+        );
+        assert_eq!(scan.errors.len(), 1);
+        assert_eq!(scan.errors[0].path, std::path::Path::new("missing.key"));
+    }
+}